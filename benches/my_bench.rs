@@ -56,7 +56,7 @@ mod benchmarks {
     use super::*;
     use linux_conf_parser::core::{
         directory_parser::DirectoryParser, file_parser, schema::LoadSchema, ParseFiles,
-        SchemaLoader,
+        ParseOptions, SchemaLoader,
     };
     use rustc_hash::FxHashMap;
     use std::path::Path;
@@ -100,6 +100,62 @@ mod benchmarks {
             .unwrap(); // トレイトメソッドを呼び出し
     });
 
+    /// 多数のファイル（16個のサブディレクトリ）をパースするベンチマーク
+    ///
+    /// スレッド数ごとの結果を比較することで、並列パースのスケーリングを確認する
+    fn bench_parse_many_files(b: &mut Bencher, jobs: usize) {
+        let dir: String = format!("test_data/many_{}", jobs);
+        let content: String = "net.ipv4.tcp_syncookies = 1\nfs.file-max = 2097152\n".repeat(50);
+        for i in 0..1000 {
+            let path: PathBuf = PathBuf::from(&dir)
+                .join(format!("sub{:02}", i % 16))
+                .join(format!("file{:04}.conf", i));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &content).unwrap();
+        }
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            jobs,
+            ..ParseOptions::default()
+        };
+
+        b.iter(|| {
+            let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+            DirectoryParser
+                .parse_all_conf_files_with_options(
+                    &[dir.as_str()],
+                    &FxHashMap::default(),
+                    &mut result_map,
+                    &options,
+                )
+                .unwrap();
+        });
+        fs::remove_dir_all(&dir).unwrap_or_else(|_| {
+            eprintln!("Error: テストデータのクリーンアップに失敗しました。");
+        });
+    }
+
+    #[bench]
+    fn bench_parse_many_files_1_job(b: &mut Bencher) {
+        bench_parse_many_files(b, 1);
+    }
+
+    #[bench]
+    fn bench_parse_many_files_2_jobs(b: &mut Bencher) {
+        bench_parse_many_files(b, 2);
+    }
+
+    #[bench]
+    fn bench_parse_many_files_4_jobs(b: &mut Bencher) {
+        bench_parse_many_files(b, 4);
+    }
+
+    #[bench]
+    fn bench_parse_many_files_8_jobs(b: &mut Bencher) {
+        bench_parse_many_files(b, 8);
+    }
+
     // 高負荷ベンチマーク
     // create_bench!(bench_parse_conf_to_map, || {
     //     // 1万行の設定ファイルを生成
@@ -0,0 +1,365 @@
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::i18n::{tr, Lang, Msg};
+
+/// 型定義ファイルの雛形のデフォルト出力先
+const DEFAULT_SCHEMA_OUTPUT: &str = "output.txt";
+
+/// 使い方の表示（現在の言語）
+pub fn usage() -> String {
+    tr(Msg::Usage, &[])
+}
+
+/// 実行するサブコマンド
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Check,
+    Dump,
+    /// マージ後の設定を正規化したsysctl.confとして出力（出力先が無い場合は標準出力）
+    Merge {
+        output: Option<PathBuf>,
+    },
+    SchemaInit {
+        output: PathBuf,
+    },
+    Explain {
+        key: String,
+    },
+    /// キーまたはその配下のキーの有効な値を表示
+    Get {
+        key: String,
+    },
+    /// マージ後の設定を実行中の値と比較
+    Drift,
+    /// マージ後の設定を`/proc/sys`に書き込む
+    Apply,
+    /// 設定されたキーの実行中の値をスナップショットとして保存（出力先が無い場合は標準出力）
+    Snapshot {
+        output: Option<PathBuf>,
+    },
+    /// ディレクトリを監視し、変更されたファイルを再検証して変化を表示
+    Watch,
+    /// スナップショットの値を`/proc/sys`に書き戻す
+    Restore {
+        snapshot: PathBuf,
+    },
+    /// キーの値を設定ファイルに書き込む（コメントと書式は保持する）
+    Set {
+        key: String,
+        value: String,
+    },
+    /// キーを設定している行を設定ファイルから削除
+    Unset {
+        key: String,
+    },
+    /// 2つのルートまたは出力結果の設定を比較
+    Diff {
+        old: PathBuf,
+        new: PathBuf,
+    },
+    Help,
+}
+
+/// 設定の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    Flat,
+    Yaml,
+    Toml,
+    Env,
+    Sarif,
+    Unified,
+}
+
+impl OutputFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            OutputFormat::Json => "json",
+            OutputFormat::Flat => "flat",
+            OutputFormat::Yaml => "yaml",
+            OutputFormat::Toml => "toml",
+            OutputFormat::Env => "env",
+            OutputFormat::Sarif => "sarif",
+            OutputFormat::Unified => "unified",
+        }
+    }
+}
+
+/// コマンドライン引数の解析結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cli {
+    pub command: Command,
+    pub schema: PathBuf,
+    pub root: Option<PathBuf>,
+    /// レイヤーを適用したファイルシステムで探索するコンテナイメージ
+    pub image: Option<PathBuf>,
+    pub dirs: Vec<String>,
+    /// set・unset で編集するファイル（未指定時は有効な値を設定しているファイル）
+    pub file: Option<PathBuf>,
+    /// 出力形式（未指定時はコマンドごとのデフォルト）
+    pub format: Option<OutputFormat>,
+    pub quiet: bool,
+    /// 警告のみの場合も終了コード3で終了する
+    pub fail_on_warnings: bool,
+    /// merge で各キーの定義元をコメントとして出力する
+    pub annotate: bool,
+    /// get で有効な値を設定したファイルと行番号を表示する
+    pub show_source: bool,
+    /// get でスキーマの型を表示する
+    pub show_type: bool,
+    /// 実行中の値を読み書きする`/proc/sys`の形式のディレクトリ
+    pub proc_sys: PathBuf,
+    /// apply で書き込まずに結果のみを表示する
+    pub dry_run: bool,
+    /// ファイルごとのパース結果のキャッシュファイル
+    pub cache: Option<PathBuf>,
+    /// ファイルの探索・パースを並列に行うスレッド数（0の場合はCPU数）
+    pub jobs: usize,
+    /// メッセージの言語（未指定時は環境変数から判定）
+    pub lang: Option<Lang>,
+}
+
+impl Cli {
+    /// コマンドライン引数（プログラム名を除く）を解析
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Cli, String> {
+        let mut schema: PathBuf = PathBuf::from(Config::SCHEMA_FILE_PATH);
+        let mut root: Option<PathBuf> = None;
+        let mut image: Option<PathBuf> = None;
+        let mut dirs: Vec<String> = Vec::new();
+        let mut file: Option<PathBuf> = None;
+        let mut format: Option<OutputFormat> = None;
+        let mut quiet: bool = false;
+        let mut fail_on_warnings: bool = false;
+        let mut annotate: bool = false;
+        let mut show_source: bool = false;
+        let mut show_type: bool = false;
+        let mut proc_sys: PathBuf = PathBuf::from(Config::PROC_SYS_PATH);
+        let mut dry_run: bool = false;
+        let mut cache: Option<PathBuf> = None;
+        let mut jobs: usize = 0;
+        let mut lang: Option<Lang> = None;
+        let mut help: bool = false;
+        let mut positionals: Vec<String> = Vec::new();
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // `--flag=value` 形式にも対応
+            let (flag, inline_value): (&str, Option<String>) = match arg.split_once('=') {
+                Some((flag, value)) if arg.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            let mut value = |name: &str| -> Result<String, String> {
+                match inline_value.clone().or_else(|| args.next()) {
+                    Some(value) => Ok(value),
+                    None => Err(tr(Msg::OptionRequiresValue, &[&name])),
+                }
+            };
+
+            match flag {
+                "--schema" => schema = PathBuf::from(value(flag)?),
+                "--root" => root = Some(PathBuf::from(value(flag)?)),
+                "--image" => image = Some(PathBuf::from(value(flag)?)),
+                "--dir" => dirs.push(value(flag)?),
+                "--file" => file = Some(PathBuf::from(value(flag)?)),
+                "--format" => {
+                    format = match value(flag)?.as_str() {
+                        "json" => Some(OutputFormat::Json),
+                        "flat" => Some(OutputFormat::Flat),
+                        "yaml" => Some(OutputFormat::Yaml),
+                        "toml" => Some(OutputFormat::Toml),
+                        "env" => Some(OutputFormat::Env),
+                        "sarif" => Some(OutputFormat::Sarif),
+                        "unified" => Some(OutputFormat::Unified),
+                        other => return Err(tr(Msg::UnsupportedFormat, &[&other])),
+                    }
+                }
+                "--lang" => {
+                    let value: String = value(flag)?;
+                    match Lang::parse(&value) {
+                        Some(parsed) => lang = Some(parsed),
+                        None => return Err(tr(Msg::UnsupportedLang, &[&value])),
+                    }
+                }
+                "-q" | "--quiet" => quiet = true,
+                "--fail-on-warnings" => fail_on_warnings = true,
+                "--annotate" => annotate = true,
+                "--show-source" => show_source = true,
+                "--show-type" => show_type = true,
+                "--proc-sys" => proc_sys = PathBuf::from(value(flag)?),
+                "--dry-run" => dry_run = true,
+                "--cache" => cache = Some(PathBuf::from(value(flag)?)),
+                "-j" | "--jobs" => {
+                    let count: String = value(flag)?;
+                    jobs = count.parse().map_err(|_| tr(Msg::InvalidJobs, &[&count]))?;
+                }
+                "-h" | "--help" => help = true,
+                // `--` 以降は全て位置引数（`-`で始まる値の指定用）
+                "--" => positionals.extend(args.by_ref()),
+                // 負の数は set の値として扱う
+                _ if flag.starts_with('-') && flag.len() > 1 && flag.parse::<f64>().is_err() => {
+                    return Err(tr(Msg::UnknownOption, &[&arg]));
+                }
+                _ => positionals.push(arg),
+            }
+        }
+
+        let command: Command = if help {
+            Command::Help
+        } else {
+            parse_command(&positionals)?
+        };
+
+        // コマンドごとに使用できる出力形式を確認
+        if let Some(format) = format {
+            if !supported_formats(&command).contains(&format) {
+                return Err(tr(Msg::FormatNotAvailable, &[&format.as_str()]));
+            }
+        }
+
+        // 編集するファイルは set・unset でのみ指定できる
+        if file.is_some()
+            && !matches!(
+                command,
+                Command::Set { .. } | Command::Unset { .. } | Command::Help
+            )
+        {
+            return Err(tr(Msg::FileOptionNotAvailable, &[]));
+        }
+
+        // イメージは設定の読み込みのみを行うコマンドで使用できる
+        if image.is_some() {
+            if root.is_some() {
+                return Err(tr(Msg::ImageRootConflict, &[]));
+            }
+            if !matches!(
+                command,
+                Command::Check
+                    | Command::Dump
+                    | Command::Get { .. }
+                    | Command::Merge { .. }
+                    | Command::SchemaInit { .. }
+                    | Command::Help
+            ) {
+                return Err(tr(Msg::ImageNotAvailable, &[]));
+            }
+        }
+
+        Ok(Cli {
+            command,
+            schema,
+            root,
+            image,
+            dirs,
+            file,
+            format,
+            quiet,
+            fail_on_warnings,
+            annotate,
+            show_source,
+            show_type,
+            proc_sys,
+            dry_run,
+            cache,
+            jobs,
+            lang,
+        })
+    }
+
+    /// 探索するディレクトリの一覧（未指定時は本番想定のディレクトリ）
+    ///
+    /// `--root`指定時、これらのディレクトリはルート配下で解決される
+    pub fn directories(&self) -> Vec<String> {
+        if self.dirs.is_empty() {
+            Config::DEFAULT_DIRECTORIES
+                .iter()
+                .map(|dir| dir.to_string())
+                .collect()
+        } else {
+            self.dirs.clone()
+        }
+    }
+}
+
+/// コマンドごとに使用できる出力形式
+fn supported_formats(command: &Command) -> &'static [OutputFormat] {
+    match command {
+        // check の json / sarif は診断の出力形式
+        Command::Check => &[OutputFormat::Json, OutputFormat::Sarif],
+        Command::Dump => &[
+            OutputFormat::Json,
+            OutputFormat::Flat,
+            OutputFormat::Yaml,
+            OutputFormat::Toml,
+            OutputFormat::Env,
+        ],
+        Command::Explain { .. } | Command::Get { .. } => &[OutputFormat::Json],
+        Command::Diff { .. } => &[OutputFormat::Json, OutputFormat::Unified],
+        Command::Drift | Command::Apply | Command::Restore { .. } | Command::Watch => {
+            &[OutputFormat::Json]
+        }
+        // スナップショットは常にJSON
+        Command::Merge { .. }
+        | Command::Snapshot { .. }
+        | Command::Set { .. }
+        | Command::Unset { .. }
+        | Command::SchemaInit { .. }
+        | Command::Help => &[],
+    }
+}
+
+/// 位置引数からサブコマンドを決定
+fn parse_command(positionals: &[String]) -> Result<Command, String> {
+    let args: Vec<&str> = positionals.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [] | ["check"] => Ok(Command::Check),
+        ["dump"] => Ok(Command::Dump),
+        ["drift"] => Ok(Command::Drift),
+        ["apply"] => Ok(Command::Apply),
+        ["watch"] => Ok(Command::Watch),
+        ["snapshot"] => Ok(Command::Snapshot { output: None }),
+        ["snapshot", output] => Ok(Command::Snapshot {
+            output: Some(PathBuf::from(output)),
+        }),
+        ["restore", snapshot] => Ok(Command::Restore {
+            snapshot: PathBuf::from(snapshot),
+        }),
+        ["merge"] => Ok(Command::Merge { output: None }),
+        ["merge", output] => Ok(Command::Merge {
+            output: Some(PathBuf::from(output)),
+        }),
+        ["help"] => Ok(Command::Help),
+        // `output` は旧来のコマンド名
+        ["schema", "init"] | ["output"] => Ok(Command::SchemaInit {
+            output: PathBuf::from(DEFAULT_SCHEMA_OUTPUT),
+        }),
+        ["schema", "init", output] | ["output", output] => Ok(Command::SchemaInit {
+            output: PathBuf::from(output),
+        }),
+        ["explain", key] => Ok(Command::Explain {
+            key: key.to_string(),
+        }),
+        ["get", key] | ["query", key] => Ok(Command::Get {
+            key: key.to_string(),
+        }),
+        ["set", key, value] => Ok(Command::Set {
+            key: key.to_string(),
+            value: value.to_string(),
+        }),
+        ["unset", key] => Ok(Command::Unset {
+            key: key.to_string(),
+        }),
+        ["diff", old, new] => Ok(Command::Diff {
+            old: PathBuf::from(old),
+            new: PathBuf::from(new),
+        }),
+        ["explain"] => Err(tr(Msg::ExplainRequiresKey, &[])),
+        ["get"] | ["query"] => Err(tr(Msg::GetRequiresKey, &[])),
+        ["diff"] | ["diff", _] => Err(tr(Msg::DiffRequiresPaths, &[])),
+        ["set"] | ["set", _] => Err(tr(Msg::SetRequiresKeyValue, &[])),
+        ["unset"] => Err(tr(Msg::UnsetRequiresKey, &[])),
+        ["restore"] => Err(tr(Msg::RestoreRequiresPath, &[])),
+        _ => Err(tr(Msg::UnknownCommand, &[&positionals.join(" ")])),
+    }
+}
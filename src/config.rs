@@ -3,4 +3,24 @@ pub struct Config;
 impl Config {
     pub const SCHEMA_FILE_PATH: &'static str = "schema.txt";
     pub const MAX_VALUE_LENGTH: usize = 4096;
+    /// イメージのレイヤーを展開した後の大きさの上限（バイト、圧縮爆弾への対策）
+    pub const MAX_DECOMPRESSED_SIZE: usize = 1 << 30;
+    /// 実行中のカーネルパラメータのディレクトリ（`--proc-sys`未指定時に使用）
+    pub const PROC_SYS_PATH: &'static str = "/proc/sys";
+    /// watch で変更を待機する最大時間（ミリ秒、inotifyが使用できない場合のポーリング間隔）
+    pub const WATCH_INTERVAL_MS: u64 = 2000;
+    /// watch で変更を検出してから再読み込みするまでの待機時間（ミリ秒、連続した書き込みをまとめる）
+    pub const WATCH_DEBOUNCE_MS: u64 = 100;
+    /// set で値を設定するファイルが無い場合に作成するドロップインの名前（ベンダー以外の最初の探索ディレクトリに作成）
+    pub const LOCAL_DROP_IN_NAME: &'static str = "99-local.conf";
+    /// ベンダーが提供するファイルのディレクトリ（set・unset では編集しない）
+    pub const VENDOR_DIRECTORIES: [&'static str; 3] = ["/usr/local/lib", "/usr/lib", "/lib"];
+    /// 本番想定の探索ディレクトリ（`--dir`未指定時に使用、優先順位の高い順）
+    pub const DEFAULT_DIRECTORIES: [&'static str; 5] = [
+        "/etc/sysctl.d",
+        "/run/sysctl.d",
+        "/usr/local/lib/sysctl.d",
+        "/usr/lib/sysctl.d",
+        "/lib/sysctl.d",
+    ];
 }
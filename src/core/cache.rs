@@ -0,0 +1,280 @@
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::diagnostic::{Diagnostic, DiagnosticCode, Location};
+use super::file_parser::ConfEntry;
+use crate::i18n;
+
+/// キャッシュファイルの識別子（`format`フィールドの値）
+pub const CACHE_FORMAT: &str = "linux-conf-parser-cache";
+/// キャッシュファイルの形式のバージョン（エントリや診断の形式を変えた場合に上げる）
+pub const CACHE_VERSION: u64 = 2;
+
+/// ファイルの変更の検出に使用する情報（更新日時とサイズ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    /// 更新日時（UNIX時間、ナノ秒）
+    pub modified: u128,
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// ファイルの更新日時とサイズを取得（取得できない場合はNone）
+    pub fn of(path: &Path) -> Option<FileStamp> {
+        let metadata: fs::Metadata = fs::metadata(path).ok()?;
+        let modified: u128 = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()?
+            .as_nanos();
+        Some(FileStamp {
+            modified,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// 1つのファイルのパース結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedFile {
+    pub stamp: FileStamp,
+    pub entries: Vec<ConfEntry>,
+    /// ファイル内の警告
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// ファイルごとのパース結果のキャッシュ
+///
+/// パスと更新日時・サイズが一致するファイルはパースせずに結果を再利用する。
+/// 型定義ファイルの内容・メッセージの言語・キャッシュの形式が変わった場合は全て破棄する。
+#[derive(Debug, Clone, Default)]
+pub struct ParseCache {
+    /// キャッシュを作成した時点の型定義と言語を表す値
+    key: String,
+    files: BTreeMap<PathBuf, CachedFile>,
+    /// 今回の実行で参照・更新したファイル
+    used: FxHashSet<PathBuf>,
+    /// 前回から内容が変わったか（変わっていない場合は保存しない）
+    dirty: bool,
+}
+
+impl ParseCache {
+    /// キャッシュファイルを読み込む
+    ///
+    /// ファイルが無い・壊れている・`schema`や言語が異なる場合は空のキャッシュとなる
+    pub fn load(path: &Path, schema: &FxHashMap<String, String>) -> ParseCache {
+        let key: String = cache_key(schema);
+        let files: BTreeMap<PathBuf, CachedFile> = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            .and_then(|value| files_from_json(&value, &key))
+            .unwrap_or_default();
+        ParseCache {
+            key,
+            files,
+            used: FxHashSet::default(),
+            dirty: false,
+        }
+    }
+
+    /// ファイルが変更されていなければ（`stamp`が一致すれば）、キャッシュしたパース結果を返す
+    pub fn get(&mut self, path: &Path, stamp: FileStamp) -> Option<&CachedFile> {
+        match self.files.get(path) {
+            Some(file) if file.stamp == stamp => {
+                self.used.insert(path.to_path_buf());
+                self.files.get(path)
+            }
+            _ => None,
+        }
+    }
+
+    /// パース結果を追加（パース前に取得した`stamp`を使用し、パース中の変更を見逃さない）
+    pub fn insert(
+        &mut self,
+        path: &Path,
+        stamp: FileStamp,
+        entries: Vec<ConfEntry>,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        self.files.insert(
+            path.to_path_buf(),
+            CachedFile {
+                stamp,
+                entries,
+                diagnostics,
+            },
+        );
+        self.used.insert(path.to_path_buf());
+        self.dirty = true;
+    }
+
+    /// キャッシュファイルに保存（一時ファイルに書き込んでから置き換える）
+    ///
+    /// 今回参照しなかったファイルのうち、既に存在しないものは削除する
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        let before: usize = self.files.len();
+        let used: &FxHashSet<PathBuf> = &self.used;
+        self.files
+            .retain(|file, _| used.contains(file) || file.exists());
+        if !self.dirty && self.files.len() == before && path.exists() {
+            return Ok(());
+        }
+
+        let files: Map<String, Value> = self
+            .files
+            .iter()
+            .map(|(file, cached)| (file.to_string_lossy().to_string(), file_to_json(cached)))
+            .collect();
+        let value: Value = json!({
+            "format": CACHE_FORMAT,
+            "version": CACHE_VERSION,
+            "key": self.key,
+            "files": files,
+        });
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut temporary: PathBuf = path.to_path_buf();
+        temporary.as_mut_os_string().push(".tmp");
+        fs::write(&temporary, value.to_string())?;
+        fs::rename(&temporary, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// 型定義とメッセージの言語から、キャッシュが有効かを判定する値を作成
+fn cache_key(schema: &FxHashMap<String, String>) -> String {
+    let sorted: BTreeMap<&String, &String> = schema.iter().collect();
+    let mut hasher: FxHasher = FxHasher::default();
+    sorted.hash(&mut hasher);
+    format!("{}-{:016x}", i18n::lang().as_str(), hasher.finish())
+}
+
+/// JSONからファイルごとのパース結果を読み込む（形式・バージョン・`key`が異なる場合はNone）
+fn files_from_json(value: &Value, key: &str) -> Option<BTreeMap<PathBuf, CachedFile>> {
+    let object: &Map<String, Value> = value.as_object()?;
+    if object.get("format")?.as_str()? != CACHE_FORMAT
+        || object.get("version")?.as_u64()? != CACHE_VERSION
+        || object.get("key")?.as_str()? != key
+    {
+        return None;
+    }
+    object
+        .get("files")?
+        .as_object()?
+        .iter()
+        .map(|(path, file)| Some((PathBuf::from(path), file_from_json(file)?)))
+        .collect()
+}
+
+fn file_to_json(file: &CachedFile) -> Value {
+    let entries: Vec<Value> = file
+        .entries
+        .iter()
+        .map(|entry| {
+            json!([
+                entry.key,
+                entry.value,
+                entry.line,
+                entry.column,
+                entry.ignore_failure
+            ])
+        })
+        .collect();
+    let diagnostics: Vec<Value> = file.diagnostics.iter().map(diagnostic_to_json).collect();
+    json!({
+        // u128はJSONの数値として扱えないため文字列で保存
+        "modified": file.stamp.modified.to_string(),
+        "size": file.stamp.size,
+        "entries": entries,
+        "diagnostics": diagnostics,
+    })
+}
+
+fn file_from_json(value: &Value) -> Option<CachedFile> {
+    let stamp: FileStamp = FileStamp {
+        modified: value.get("modified")?.as_str()?.parse().ok()?,
+        size: value.get("size")?.as_u64()?,
+    };
+    let entries: Vec<ConfEntry> = value
+        .get("entries")?
+        .as_array()?
+        .iter()
+        .map(|entry| {
+            Some(ConfEntry {
+                key: entry.get(0)?.as_str()?.to_string(),
+                value: entry.get(1)?.as_str()?.to_string(),
+                line: entry.get(2)?.as_u64()? as usize,
+                column: entry.get(3)?.as_u64()? as usize,
+                ignore_failure: entry.get(4)?.as_bool()?,
+            })
+        })
+        .collect::<Option<_>>()?;
+    let diagnostics: Vec<Diagnostic> = value
+        .get("diagnostics")?
+        .as_array()?
+        .iter()
+        .map(diagnostic_from_json)
+        .collect::<Option<_>>()?;
+    Some(CachedFile {
+        stamp,
+        entries,
+        diagnostics,
+    })
+}
+
+/// 診断をJSONに変換（`report`の出力と異なり、読み戻せるよう全ての項目を含める）
+fn diagnostic_to_json(diagnostic: &Diagnostic) -> Value {
+    json!({
+        "code": diagnostic.code.as_str(),
+        "message": diagnostic.message,
+        "key": diagnostic.key,
+        "value": diagnostic.value,
+        "expected": diagnostic.expected,
+        "location": diagnostic.location.as_ref().map(|location| json!([
+            location.file.to_string_lossy(),
+            location.line,
+            location.column,
+            location.end_column,
+        ])),
+        "help": diagnostic.help,
+    })
+}
+
+fn diagnostic_from_json(value: &Value) -> Option<Diagnostic> {
+    let code: &str = value.get("code")?.as_str()?;
+    let code: DiagnosticCode = DiagnosticCode::ALL
+        .into_iter()
+        .find(|candidate| candidate.as_str() == code)?;
+    let text = |name: &str| -> Option<String> {
+        value.get(name).and_then(Value::as_str).map(str::to_string)
+    };
+    let location: Option<Location> = match value.get("location") {
+        Some(Value::Array(location)) => Some(Location {
+            file: PathBuf::from(location.first()?.as_str()?),
+            line: location.get(1)?.as_u64()? as usize,
+            column: location.get(2)?.as_u64()? as usize,
+            end_column: location.get(3)?.as_u64()? as usize,
+        }),
+        _ => None,
+    };
+    let mut diagnostic: Diagnostic = Diagnostic::new(code, text("message")?);
+    diagnostic.key = text("key");
+    diagnostic.value = text("value");
+    diagnostic.expected = text("expected");
+    diagnostic.location = location;
+    diagnostic.help = text("help");
+    Some(diagnostic)
+}
@@ -0,0 +1,168 @@
+use rustc_hash::FxHashMap;
+use std::path::PathBuf;
+
+use crate::i18n::{tr, Msg};
+
+/// 診断の重大度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// 診断コード（出力形式や言語に関係なく固定）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticCode {
+    /// 値の型がスキーマと一致しない
+    TypeMismatch,
+    /// スキーマ型がサポートされていない
+    UnsupportedType,
+    /// キーがスキーマに存在しない
+    UnknownKey,
+    /// ファイルやディレクトリの読み込みに失敗
+    ReadError,
+    /// '='を含まない行
+    MissingSeparator,
+    /// 同じファイル内でキーが重複
+    DuplicateKey,
+    /// 値が長さの上限（`Config::MAX_VALUE_LENGTH`）を超えている
+    ValueTooLong,
+    /// 別のキーと同じ環境変数名になる（`dump --format env`）
+    EnvNameCollision,
+}
+
+impl DiagnosticCode {
+    /// 全ての診断コード
+    pub const ALL: [DiagnosticCode; 8] = [
+        DiagnosticCode::TypeMismatch,
+        DiagnosticCode::UnsupportedType,
+        DiagnosticCode::UnknownKey,
+        DiagnosticCode::ReadError,
+        DiagnosticCode::MissingSeparator,
+        DiagnosticCode::DuplicateKey,
+        DiagnosticCode::ValueTooLong,
+        DiagnosticCode::EnvNameCollision,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiagnosticCode::TypeMismatch => "E001",
+            DiagnosticCode::UnsupportedType => "E002",
+            DiagnosticCode::UnknownKey => "E003",
+            DiagnosticCode::ReadError => "E004",
+            DiagnosticCode::ValueTooLong => "E005",
+            DiagnosticCode::MissingSeparator => "W001",
+            DiagnosticCode::DuplicateKey => "W002",
+            DiagnosticCode::EnvNameCollision => "W003",
+        }
+    }
+
+    pub fn severity(self) -> Severity {
+        match self {
+            DiagnosticCode::MissingSeparator
+            | DiagnosticCode::DuplicateKey
+            | DiagnosticCode::EnvNameCollision => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// 診断コードの短い説明
+    pub fn description(self) -> String {
+        let msg: Msg = match self {
+            DiagnosticCode::TypeMismatch => Msg::DescTypeMismatch,
+            DiagnosticCode::UnsupportedType => Msg::DescUnsupportedType,
+            DiagnosticCode::UnknownKey => Msg::DescUnknownKey,
+            DiagnosticCode::ReadError => Msg::DescReadError,
+            DiagnosticCode::MissingSeparator => Msg::DescMissingSeparator,
+            DiagnosticCode::DuplicateKey => Msg::DescDuplicateKey,
+            DiagnosticCode::ValueTooLong => Msg::DescValueTooLong,
+            DiagnosticCode::EnvNameCollision => Msg::DescEnvNameCollision,
+        };
+        tr(msg, &[])
+    }
+}
+
+/// 設定ファイル内の位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub file: PathBuf,
+    /// 行番号（1始まり）
+    pub line: usize,
+    /// 値の開始列（1始まり、文字単位）
+    pub column: usize,
+    /// 値の終了列（値の直後の列、文字単位）
+    pub end_column: usize,
+}
+
+/// 検証・パースで見つかった問題
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    pub message: String,
+    pub key: Option<String>,
+    pub value: Option<String>,
+    /// 期待される型
+    pub expected: Option<String>,
+    pub location: Option<Location>,
+    /// スキーマの補足説明
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    /// 診断コードの重大度で診断を作成
+    pub fn new(code: DiagnosticCode, message: String) -> Diagnostic {
+        Diagnostic {
+            code,
+            severity: code.severity(),
+            message,
+            key: None,
+            value: None,
+            expected: None,
+            location: None,
+            help: None,
+        }
+    }
+
+    pub fn with_key(mut self, key: &str) -> Diagnostic {
+        self.key = Some(key.to_string());
+        self
+    }
+
+    pub fn with_value(mut self, value: &str) -> Diagnostic {
+        self.value = Some(value.to_string());
+        self
+    }
+
+    pub fn with_expected(mut self, expected: &str) -> Diagnostic {
+        self.expected = Some(expected.to_string());
+        self
+    }
+
+    pub fn with_location(mut self, location: Option<Location>) -> Diagnostic {
+        self.location = location;
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+/// スキーマの補足説明を、対応するキーの診断にヘルプとして付与
+pub fn attach_notes(diagnostics: &mut [Diagnostic], notes: &FxHashMap<String, String>) {
+    for diagnostic in diagnostics {
+        if let Some(note) = diagnostic.key.as_ref().and_then(|key| notes.get(key)) {
+            diagnostic.help = Some(note.clone());
+        }
+    }
+}
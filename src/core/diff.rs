@@ -0,0 +1,180 @@
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::diagnostic::{Diagnostic, Location};
+use super::directory_parser::DirectoryParser;
+use super::file_parser::parse_conf_entries;
+use super::snapshot::Snapshot;
+use super::{ParseFiles, ParseOptions, ParseReport};
+use crate::utils::display::{unescape_key_part, LEAF_VALUE_KEY};
+
+/// マージ後の設定（キーの順）と各キーの定義位置
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResolvedConfig {
+    pub values: BTreeMap<String, String>,
+    /// 定義位置（JSONから読み込んだ場合は無し）
+    pub sources: BTreeMap<String, Location>,
+}
+
+/// キーの変更の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// 1つのキーの差分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyChange {
+    pub key: String,
+    pub kind: ChangeKind,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub old_source: Option<Location>,
+    pub new_source: Option<Location>,
+}
+
+/// 比較対象を読み込む
+///
+/// ディレクトリの場合はルートとして`directories`を探索してマージし、ファイルの場合は
+/// `dump --format json`のJSON、`snapshot`で保存したスナップショット、または`merge`で出力したような
+/// `key = value`形式として読み込む。
+/// パース中に見つかった診断（読み込みエラー・警告）も返す。
+pub fn load_resolved_config(
+    path: &Path,
+    directories: &[&str],
+) -> io::Result<(ResolvedConfig, Vec<Diagnostic>)> {
+    if path.is_dir() {
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            root: Some(path.to_path_buf()),
+            cache: None,
+            jobs: 0,
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        let report: ParseReport = DirectoryParser.parse_all_conf_files_with_options(
+            directories,
+            &FxHashMap::default(),
+            &mut result_map,
+            &options,
+        )?;
+        let config = ResolvedConfig {
+            values: result_map.into_iter().collect(),
+            sources: report
+                .sources
+                .into_iter()
+                .map(|(key, source)| (key, source.location))
+                .collect(),
+        };
+        return Ok((config, report.diagnostics));
+    }
+
+    let content: String = fs::read_to_string(path)?;
+    if content.trim_start().starts_with('{') {
+        let json: Value = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let values: BTreeMap<String, String> = match Snapshot::from_json(&json) {
+            Some(snapshot) => snapshot.values,
+            None => {
+                let mut values: BTreeMap<String, String> = BTreeMap::new();
+                flatten_json(&json, "", &mut values);
+                values
+            }
+        };
+        return Ok((
+            ResolvedConfig {
+                values,
+                sources: BTreeMap::new(),
+            },
+            Vec::new(),
+        ));
+    }
+
+    let (entries, warnings) = parse_conf_entries(path)?;
+    let mut config: ResolvedConfig = ResolvedConfig::default();
+    for entry in entries {
+        config
+            .sources
+            .insert(entry.key.clone(), entry.location(path));
+        config.values.insert(entry.key, entry.value);
+    }
+    Ok((config, warnings))
+}
+
+/// ネストしたJSONを`.`区切りのキーに戻す（`_value`は親のキーの値、配列は空白区切り）
+fn flatten_json(value: &Value, prefix: &str, values: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(object) => {
+            for (key, child) in object {
+                let path: String = if key == LEAF_VALUE_KEY {
+                    prefix.to_string()
+                } else if prefix.is_empty() {
+                    unescape_key_part(key).to_string()
+                } else {
+                    format!("{}.{}", prefix, unescape_key_part(key))
+                };
+                flatten_json(child, &path, values);
+            }
+        }
+        Value::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(scalar_to_string).collect();
+            values.insert(prefix.to_string(), elements.join(" "));
+        }
+        other => {
+            values.insert(prefix.to_string(), scalar_to_string(other));
+        }
+    }
+}
+
+/// JSONの値を設定ファイルの値の表記に変換
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// 2つの設定を比較し、追加・削除・変更されたキーをキーの順に返す
+pub fn diff_configs(old: &ResolvedConfig, new: &ResolvedConfig) -> Vec<KeyChange> {
+    let mut keys: Vec<&String> = old.values.keys().chain(new.values.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let old_value: Option<&String> = old.values.get(key);
+            let new_value: Option<&String> = new.values.get(key);
+            let kind: ChangeKind = match (old_value, new_value) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(old_value), Some(new_value)) if old_value != new_value => ChangeKind::Changed,
+                _ => return None,
+            };
+            Some(KeyChange {
+                key: key.clone(),
+                kind,
+                old_value: old_value.cloned(),
+                new_value: new_value.cloned(),
+                old_source: old.sources.get(key).cloned(),
+                new_source: new.sources.get(key).cloned(),
+            })
+        })
+        .collect()
+}
@@ -1,92 +1,298 @@
 use rustc_hash::{FxHashMap, FxHashSet};
-use std::fs;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::io;
 use std::path::Path;
 use std::path::PathBuf;
 
-use super::file_parser::parse_conf_file;
-use super::schema::validate_against_schema;
-use super::ParseFiles;
+use super::cache::{FileStamp, ParseCache};
+use super::diagnostic::{Diagnostic, DiagnosticCode, Location};
+use super::file_parser::{merge_conf_entries, parse_source_entries, ConfEntry};
+use super::parallel::parallel_map;
+use super::schema::check_against_schema;
+use super::vfs::{FileKind, FileSource, RealFs};
+use super::{ParseFiles, ParseOptions, ParseReport};
+use crate::config::Config;
+use crate::i18n::{tr, Msg};
 
+/// 実際のファイルシステムのディレクトリをパースする（`options.root`の配下で解決）
 pub struct DirectoryParser;
 
 impl ParseFiles for DirectoryParser {
     /// 指定されたディレクトリ内のすべての設定ファイルをパースし、結果を検証
-    fn parse_all_conf_files(
+    fn parse_all_conf_files_with_options(
         &self,
         directories: &[&str],
         schema: &FxHashMap<String, String>,
         result_map: &mut FxHashMap<String, String>,
-    ) -> io::Result<()> {
-        let mut parsed_files: FxHashSet<String> = FxHashSet::default();
-        let mut all_errors: Vec<String> = Vec::new(); // 全てのエラーを収集
-        for dir in directories {
-            let path: &Path = Path::new(dir);
-            if !path.is_dir() {
-                eprintln!(
-                    "Error: 指定されたディレクトリ '{}' が存在しません。",
-                    path.display()
+        options: &ParseOptions,
+    ) -> io::Result<ParseReport> {
+        let source: RealFs = RealFs::new(options.root.clone());
+        parse_source(&source, directories, schema, result_map, options)
+    }
+
+    /// 診断の位置は`options.root`を解決済みの実際のパスとなる
+    fn file_source(&self) -> &dyn FileSource {
+        static HOST: RealFs = RealFs { root: None };
+        &HOST
+    }
+}
+
+/// 指定された読み込み元（メモリ上のファイル・tarアーカイブなど）のディレクトリをパースする
+///
+/// `options.root`は使用しない（ルートの指定は読み込み元で行う）
+pub struct SourceParser<S: FileSource> {
+    pub source: S,
+}
+
+impl<S: FileSource> SourceParser<S> {
+    pub fn new(source: S) -> Self {
+        SourceParser { source }
+    }
+}
+
+impl<S: FileSource> ParseFiles for SourceParser<S> {
+    /// `source`内の指定されたディレクトリのすべての設定ファイルをパースし、結果を検証
+    fn parse_all_conf_files_with_options(
+        &self,
+        directories: &[&str],
+        schema: &FxHashMap<String, String>,
+        result_map: &mut FxHashMap<String, String>,
+        options: &ParseOptions,
+    ) -> io::Result<ParseReport> {
+        parse_source(&self.source, directories, schema, result_map, options)
+    }
+
+    fn file_source(&self) -> &dyn FileSource {
+        &self.source
+    }
+}
+
+/// 読み込み元`source`のディレクトリ内のすべての設定ファイルをパースし、結果を検証
+fn parse_source(
+    source: &dyn FileSource,
+    directories: &[&str],
+    schema: &FxHashMap<String, String>,
+    result_map: &mut FxHashMap<String, String>,
+    options: &ParseOptions,
+) -> io::Result<ParseReport> {
+    let mut report: ParseReport = ParseReport::default();
+    let mut read_errors: Vec<String> = Vec::new(); // 読み込みエラーを収集
+
+    // キャッシュを使用する場合は、変更されていないファイルのパース結果を再利用
+    let mut cache: Option<ParseCache> = options
+        .cache
+        .as_deref()
+        .map(|path| ParseCache::load(path, schema));
+
+    // 優先順位の低い順に.confファイルを収集
+    let files: Vec<PathBuf> =
+        collect_source_files(source, directories, &mut read_errors, options.jobs);
+
+    // キャッシュに無いファイルを並列にパース（結果は探索順に並ぶ）
+    let mut parsed: Vec<Option<ParsedEntries>> = files
+        .iter()
+        .map(|path| {
+            let cached = cache.as_mut()?.get(path, source.stamp(path)?)?;
+            report.cached_files += 1;
+            Some(Ok((cached.entries.clone(), cached.diagnostics.clone())))
+        })
+        .collect();
+    let pending: Vec<usize> = (0..files.len())
+        .filter(|index| parsed[*index].is_none())
+        .collect();
+    let results: Vec<(Option<FileStamp>, ParsedEntries)> =
+        parallel_map(&pending, options.jobs, |index| {
+            // パース中に変更された場合は次回に再パースされるよう、パース前の状態を記録
+            let path: &Path = &files[*index];
+            (source.stamp(path), parse_source_entries(source, path))
+        });
+    for (index, (stamp, result)) in pending.into_iter().zip(results) {
+        if let (Some(cache), Some(stamp), Ok((entries, warnings))) = (&mut cache, stamp, &result) {
+            cache.insert(&files[index], stamp, entries.clone(), warnings.clone());
+        }
+        parsed[index] = Some(result);
+    }
+
+    // 優先順位を保つため、探索順にマージ
+    for (path, parsed) in files.iter().zip(parsed) {
+        match parsed.expect("全てのファイルがパースされていません") {
+            Ok((entries, warnings)) => {
+                merge_conf_entries(
+                    path,
+                    entries,
+                    result_map,
+                    &mut report.sources,
+                    schema,
+                    options.verbose,
                 );
-                continue;
-            }
-            if let Err(e) = parse_conf_dir(path, &mut parsed_files, result_map) {
-                all_errors.push(format!(
-                    "ディレクトリ '{}' のパースに失敗しました: {}",
-                    path.display(),
-                    e
-                ));
+                report.files += 1;
+                report.diagnostics.extend(warnings);
             }
+            Err(e) => read_errors.push(tr(Msg::FileParseFailed, &[&path.display(), &e])),
         }
+    }
 
-        // パース結果をスキーマに基づいて検証
-        if let Err(validation_error) = validate_against_schema(result_map, schema) {
-            all_errors.push(validation_error); // エラーを収集
+    // キャッシュの保存に失敗しても結果には影響しない
+    if let (Some(cache), Some(cache_path)) = (&mut cache, &options.cache) {
+        if let Err(e) = cache.save(cache_path) {
+            eprintln!("{}", tr(Msg::CacheSaveFailed, &[&cache_path.display(), &e]));
         }
+    }
+    report.diagnostics.extend(
+        read_errors
+            .into_iter()
+            .map(|message| Diagnostic::new(DiagnosticCode::ReadError, message)),
+    );
+
+    // パース結果をスキーマに基づいて検証
+    if options.validate {
+        // 各キーが最後に定義された位置
+        let locations: FxHashMap<String, Location> = report
+            .sources
+            .iter()
+            .map(|(key, source)| (key.clone(), source.location.clone()))
+            .collect();
+        report
+            .diagnostics
+            .extend(check_against_schema(result_map, schema, &locations));
+    }
+
+    Ok(report)
+}
 
-        // すべてのエラーを出力
-        if !all_errors.is_empty() {
-            for error in all_errors {
-                eprintln!("{}", error);
+/// 1つのファイルのパース結果（エントリと警告）
+type ParsedEntries = io::Result<(Vec<ConfEntry>, Vec<Diagnostic>)>;
+
+/// 指定されたディレクトリ群から.confファイルを探索順に収集
+///
+/// systemd-sysctlと同じ規則で、全てのディレクトリのファイルを名前（ディレクトリからの相対パス）順に並べる。
+/// 同じ名前のファイルは先に指定されたディレクトリのもののみを使用し、`/dev/null`へのリンクは
+/// 同じ名前のファイルを除外する（マスク）。サブディレクトリも再帰的に探索する。
+/// `root`が指定された場合、ディレクトリとシンボリックリンクはその配下で解決される。
+/// 同じファイルは一度だけ収集する。ディレクトリの読み込みに失敗した場合は
+/// `errors`にメッセージを追加し、それまでに見つかったファイルは結果に含める。
+pub fn collect_conf_files(
+    directories: &[&str],
+    root: Option<&Path>,
+    errors: &mut Vec<String>,
+) -> Vec<PathBuf> {
+    collect_conf_files_with_jobs(directories, root, errors, 1)
+}
+
+/// `collect_conf_files`と同じ結果を、指定されたディレクトリごとに最大`jobs`個のスレッドで探索して返す
+pub fn collect_conf_files_with_jobs(
+    directories: &[&str],
+    root: Option<&Path>,
+    errors: &mut Vec<String>,
+    jobs: usize,
+) -> Vec<PathBuf> {
+    let source: RealFs = RealFs::new(root.map(Path::to_path_buf));
+    collect_source_files(&source, directories, errors, jobs)
+}
+
+/// 読み込み元`source`から.confファイルを探索順に収集（`collect_conf_files_with_jobs`と同じ規則）
+///
+/// 返すパスは`source`で解決したパスとなる
+pub fn collect_source_files(
+    source: &dyn FileSource,
+    directories: &[&str],
+    errors: &mut Vec<String>,
+    jobs: usize,
+) -> Vec<PathBuf> {
+    // 存在しないディレクトリの通知は指定された順に表示
+    let mut targets: Vec<(&Path, PathBuf)> = Vec::new();
+    for dir in directories {
+        let path: &Path = Path::new(dir);
+        match source.resolve(path) {
+            Ok(host_path) if source.kind(&host_path) == Some(FileKind::Directory) => {
+                targets.push((path, host_path))
+            }
+            // 標準のディレクトリが無いのは通常の構成のため通知しない（systemd-sysctlと同じ）
+            Ok(_) if Config::DEFAULT_DIRECTORIES.contains(dir) => {}
+            Ok(host_path) => {
+                eprintln!("{}", tr(Msg::DirectoryNotFound, &[&host_path.display()]))
             }
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "設定ファイルにエラーがあります。",
-            ));
+            Err(e) => errors.push(tr(Msg::DirectoryParseFailed, &[&path.display(), &e])),
         }
-        Ok(())
     }
+
+    let found: Vec<(Vec<FoundFile>, Option<String>)> =
+        parallel_map(&targets, jobs, |(path, host_path)| {
+            let mut seen: FxHashSet<PathBuf> = FxHashSet::default();
+            let mut files: Vec<FoundFile> = Vec::new();
+            let error: Option<String> =
+                find_conf_files(source, path, Path::new(""), &mut seen, &mut files)
+                    .err()
+                    .map(|e| tr(Msg::DirectoryParseFailed, &[&host_path.display(), &e]));
+            (files, error)
+        });
+
+    // 同じ名前のファイル（マスクを含む）は先に指定されたディレクトリのものを使用
+    let mut by_name: BTreeMap<OsString, Option<PathBuf>> = BTreeMap::new();
+    for (found, error) in found {
+        errors.extend(error);
+        for (name, host_path) in found {
+            by_name.entry(name.into_os_string()).or_insert(host_path);
+        }
+    }
+
+    // 全てのディレクトリのファイルを名前順に並べ、マスクと複数の名前で見つかったファイルを除外
+    let mut seen: FxHashSet<PathBuf> = FxHashSet::default();
+    by_name
+        .into_values()
+        .flatten()
+        .filter(|file| seen.insert(file.clone()))
+        .collect()
 }
 
-/// 再帰的にディレクトリ内の.confファイルを探索してパース
-fn parse_conf_dir(
+/// 探索で見つかった.confファイル（ディレクトリからの相対パスと解決したパス、マスクの場合はNone）
+type FoundFile = (PathBuf, Option<PathBuf>);
+
+/// 再帰的にディレクトリ内の.confファイルを名前順に探索
+///
+/// `name`は探索を開始したディレクトリからの`path`の相対パス。
+/// `seen`には収集済みのファイルと探索済みのディレクトリ（解決したパス）を記録する
+fn find_conf_files(
+    source: &dyn FileSource,
     path: &Path,
-    parsed_files: &mut FxHashSet<String>,
-    result_map: &mut FxHashMap<String, String>,
+    name: &Path,
+    seen: &mut FxHashSet<PathBuf>,
+    files: &mut Vec<FoundFile>,
 ) -> io::Result<()> {
-    let entries: fs::ReadDir = fs::read_dir(path).map_err(|e| {
+    let host_path: PathBuf = source.resolve(path)?;
+    // シンボリックリンクによるループを避ける
+    if !seen.insert(host_path.clone()) {
+        return Ok(());
+    }
+
+    // 読み込み順は読み込み元に依存するため、名前順に並べ替える
+    let mut names: Vec<OsString> = source.read_dir(&host_path).inspect_err(|e| {
         eprintln!(
-            "Error: ディレクトリ '{}' の読み込みに失敗しました: {}",
-            path.display(),
-            e
+            "{}",
+            tr(Msg::DirectoryReadFailed, &[&host_path.display(), &e])
         );
-        e
     })?;
+    names.sort();
 
-    for entry in entries {
-        let entry: fs::DirEntry = entry.map_err(|e| {
-            eprintln!(
-                "Error: ディレクトリ内のエントリへのアクセスに失敗しました: {}",
-                e
-            );
-            e
-        })?;
-        let path: PathBuf = entry.path();
-
-        // ファイルのパスを文字列に変換
-        if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("conf") {
-            parse_conf_file(&path, parsed_files, result_map)?;
-        } else if path.is_dir() {
-            parse_conf_dir(&path, parsed_files, result_map)?;
+    for entry_name in names {
+        let path: PathBuf = path.join(&entry_name);
+        let name: PathBuf = name.join(&entry_name);
+        let host_path: PathBuf = source.resolve(&path)?;
+        let is_conf: bool = path.extension().and_then(|s| s.to_str()) == Some("conf");
+
+        // /dev/nullへのリンクは同じ名前の優先順位の低いファイルを無効にする
+        if is_conf && source.is_dev_null(&host_path) {
+            files.push((name, None));
+            continue;
+        }
+        match source.kind(&host_path) {
+            // 既に収集済みならスキップ
+            Some(FileKind::File) if is_conf && seen.insert(host_path.clone()) => {
+                files.push((name, Some(host_path)))
+            }
+            Some(FileKind::Directory) => find_conf_files(source, &path, &name, seen, files)?,
+            _ => {}
         }
     }
 
@@ -0,0 +1,217 @@
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use super::explain::explain_key;
+use super::syntax::ConfDocument;
+use super::sysroot::resolve_in_root;
+use crate::config::Config;
+use crate::i18n::{tr, Msg};
+
+/// 設定ファイルの編集結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditOutcome {
+    /// 編集したファイル
+    pub file: PathBuf,
+    /// 編集前のファイル内の有効な値（キーが無い・ファイルが無い場合はNone）
+    pub previous: Option<String>,
+    /// 内容が変わり、書き込んだか
+    pub changed: bool,
+}
+
+/// キーを設定しているファイルと値（探索順、最後のものが有効）
+///
+/// 読み込めないファイルがある場合は有効な値のファイルを決定できないため、エラーとする
+pub fn setting_files(
+    key: &str,
+    directories: &[&str],
+    root: Option<&Path>,
+) -> io::Result<Vec<(PathBuf, String)>> {
+    let explanation = explain_key(key, directories, root, None)?;
+    if !explanation.diagnostics.is_empty() {
+        let messages: Vec<&str> = explanation
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        return Err(io::Error::other(messages.join("\n")));
+    }
+    Ok(explanation.occurrences)
+}
+
+/// 値を設定するファイルを決定
+///
+/// `file`が指定された場合はそのファイル、無ければ現在有効な値を設定しているファイルとする。
+/// どのファイルでも設定されていない場合は、最も優先されるベンダー以外の探索ディレクトリのドロップイン（`99-local.conf`）とする。
+/// 有効な値がベンダーのファイル（`/usr/lib`など）の場合も同じディレクトリのドロップインとし、
+/// `99-local.conf`がベンダーのファイルより先に読み込まれる名前の場合は`<ベンダーのファイル名>.local.conf`とする。
+/// `root`が指定された場合、`file`とドロップインはその配下で解決する。
+/// ベンダーのファイルは編集しないため、`file`がベンダーのファイルの場合はエラーとなる。
+pub fn edit_target(
+    key: &str,
+    directories: &[&str],
+    root: Option<&Path>,
+    file: Option<&Path>,
+) -> io::Result<PathBuf> {
+    let path: PathBuf = match file {
+        Some(file) => file.to_path_buf(),
+        None => match setting_files(key, directories, root)?.pop() {
+            // 探索で見つかったパスは解決済み
+            Some((path, _)) if !is_vendor_file(&path, root) => return Ok(path),
+            setting => {
+                let directory: &str = directories
+                    .iter()
+                    .copied()
+                    .find(|dir| !in_vendor_directory(Path::new(dir)))
+                    .unwrap_or(Config::DEFAULT_DIRECTORIES[0]);
+                let name: OsString = match setting {
+                    Some((vendor_file, _)) => override_drop_in_name(&vendor_file),
+                    None => OsString::from(Config::LOCAL_DROP_IN_NAME),
+                };
+                Path::new(directory).join(name)
+            }
+        },
+    };
+    let path: PathBuf = match root {
+        Some(root) => resolve_in_root(root, &path)?,
+        None => path,
+    };
+    if is_vendor_file(&path, root) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr(Msg::VendorFileNotEditable, &[&path.display()]),
+        ));
+    }
+    Ok(path)
+}
+
+/// ベンダーのディレクトリ（`Config::VENDOR_DIRECTORIES`）のファイルか
+///
+/// `root`が指定された場合は`root`配下で解決したパス、無い場合はシンボリックリンクのリンク先で判定する
+pub fn is_vendor_file(path: &Path, root: Option<&Path>) -> bool {
+    let path: PathBuf = match root {
+        Some(root) => match path.strip_prefix(root) {
+            Ok(relative) => Path::new("/").join(relative),
+            Err(_) => path.to_path_buf(),
+        },
+        None => fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf()),
+    };
+    in_vendor_directory(&path)
+}
+
+/// ファイル内のキーの値を設定（ファイルが無い場合は作成する）
+///
+/// コメントや他の行の書式はそのまま残し、値が変わらない場合は書き込まない
+pub fn set_in_file(path: &Path, key: &str, value: &str) -> io::Result<EditOutcome> {
+    let mut document: ConfDocument = read_document(path)?;
+    let previous: Option<String> = document.get(key).map(str::to_string);
+    document.set(key, value)?;
+
+    let changed: bool = previous.as_deref() != Some(value);
+    if changed {
+        write_atomic(path, document.to_string().as_bytes())?;
+    }
+    Ok(EditOutcome {
+        file: path.to_path_buf(),
+        previous,
+        changed,
+    })
+}
+
+/// ファイル内のキーを設定している行を全て削除（キーやファイルが無い場合は何もしない）
+pub fn unset_in_file(path: &Path, key: &str) -> io::Result<EditOutcome> {
+    let mut document: ConfDocument = read_document(path)?;
+    let previous: Option<String> = document.get(key).map(str::to_string);
+
+    let changed: bool = document.remove(key) > 0;
+    if changed {
+        write_atomic(path, document.to_string().as_bytes())?;
+    }
+    Ok(EditOutcome {
+        file: path.to_path_buf(),
+        previous,
+        changed,
+    })
+}
+
+/// ファイルを置き換えて書き込む（書き込み途中の内容が読み込まれることはない）
+///
+/// 同じディレクトリの一時ファイルに書き込んで同期した後、名前を変更する。
+/// 既存のファイルの権限を引き継ぎ、シンボリックリンクはリンク先のファイルを置き換える。
+pub fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    let path: PathBuf = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let parent: &Path = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    // 探索の対象にならないよう、`.`で始まり`.conf`で終わらない名前とする
+    let mut temporary_name: OsString = OsString::from(".");
+    temporary_name.push(path.file_name().unwrap_or_default());
+    temporary_name.push(format!(".{}.tmp", process::id()));
+    let temporary: PathBuf = parent.join(temporary_name);
+
+    let result: io::Result<()> = (|| {
+        let mut file: File = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temporary)?;
+        file.write_all(content)?;
+        if let Ok(metadata) = fs::metadata(&path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temporary, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+    result?;
+
+    // 名前の変更を永続化（失敗しても内容は置き換わっている）
+    if let Ok(directory) = File::open(parent) {
+        let _ = directory.sync_all();
+    }
+    Ok(())
+}
+
+/// 2つのパスが同じファイルを指すか（存在しない場合はパスを比較）
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// ベンダーのファイルより後に読み込まれ、その値を上書きするドロップインの名前
+fn override_drop_in_name(vendor_file: &Path) -> OsString {
+    let name: &OsStr = vendor_file.file_name().unwrap_or_default();
+    if OsStr::new(Config::LOCAL_DROP_IN_NAME) > name {
+        return OsString::from(Config::LOCAL_DROP_IN_NAME);
+    }
+    // `x.conf`に対して`x.local.conf`（`.`の後の`conf`より`local`が後に並ぶ）
+    let mut drop_in: OsString = vendor_file.file_stem().unwrap_or_default().to_os_string();
+    drop_in.push(".local.conf");
+    drop_in
+}
+
+fn in_vendor_directory(path: &Path) -> bool {
+    Config::VENDOR_DIRECTORIES
+        .iter()
+        .any(|directory| path.starts_with(directory))
+}
+
+/// 設定ファイルを読み込む（存在しない場合は空とする）
+fn read_document(path: &Path) -> io::Result<ConfDocument> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(ConfDocument::parse(&content)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(ConfDocument::default()),
+        Err(e) => Err(e),
+    }
+}
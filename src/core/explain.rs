@@ -0,0 +1,83 @@
+use rustc_hash::FxHashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::diagnostic::{Diagnostic, DiagnosticCode};
+use super::directory_parser::collect_conf_files;
+use super::file_parser::{normalize_key, parse_conf_to_map};
+use super::schema::validate_against_schema;
+use crate::i18n::{tr, Msg};
+
+/// キーの設定状況（どのファイルで設定され、どの値が有効か）
+#[derive(Debug)]
+pub struct KeyExplanation {
+    pub key: String,
+    /// キーを設定しているファイルと値（探索順、最後のものが有効）
+    pub occurrences: Vec<(PathBuf, String)>,
+    /// スキーマで定義された型
+    pub expected_type: Option<String>,
+    /// 有効な値のスキーマ検証結果
+    pub validation: Result<(), String>,
+    /// 読み込めなかったファイルの読み込みエラー（そのファイルを除いて調べた結果となる）
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl KeyExplanation {
+    /// 最終的に有効となる値
+    pub fn effective_value(&self) -> Option<&str> {
+        self.occurrences.last().map(|(_, value)| value.as_str())
+    }
+}
+
+/// 指定されたキーがどのファイルで設定されているかを調べ、スキーマと照合
+///
+/// `schema`がNoneの場合（スキーマを読み込めない場合など）はスキーマによる検証を行わない。
+/// `key`は`/`区切りでも指定できる（パース時のキーと同じく`.`区切りに変換する）。
+/// 読み込めないファイルは読み込みエラーの診断として記録し、残りのファイルを調べる
+pub fn explain_key(
+    key: &str,
+    directories: &[&str],
+    root: Option<&Path>,
+    schema: Option<&FxHashMap<String, String>>,
+) -> io::Result<KeyExplanation> {
+    let key: &str = &normalize_key(key);
+    let mut errors: Vec<String> = Vec::new();
+    let mut occurrences: Vec<(PathBuf, String)> = Vec::new();
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for path in collect_conf_files(directories, root, &mut errors) {
+        match parse_conf_to_map(&path) {
+            Ok(mut config_map) => {
+                if let Some(value) = config_map.remove(key) {
+                    occurrences.push((path, value));
+                }
+            }
+            Err(e) => diagnostics.push(Diagnostic::new(
+                DiagnosticCode::ReadError,
+                tr(Msg::FileParseFailed, &[&path.display(), &e]),
+            )),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(io::Error::other(errors.join("\n")));
+    }
+
+    // 有効な値のみをスキーマと照合
+    let validation: Result<(), String> = match (occurrences.last(), schema) {
+        (Some((_, value)), Some(schema)) => {
+            let mut config_map: FxHashMap<String, String> = FxHashMap::default();
+            config_map.insert(key.to_string(), value.clone());
+            validate_against_schema(&config_map, schema)
+        }
+        _ => Ok(()),
+    };
+
+    Ok(KeyExplanation {
+        key: key.to_string(),
+        occurrences,
+        expected_type: schema.and_then(|schema| schema.get(key).cloned()),
+        validation,
+        diagnostics,
+    })
+}
@@ -1,73 +1,201 @@
-use rustc_hash::{FxHashMap, FxHashSet};
-use std::fs::{self, File};
-use std::io::{self, BufRead, Error};
+use rustc_hash::FxHashMap;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::io::{self, Error};
 use std::path::Path;
 
 use crate::config::Config;
+use crate::core::diagnostic::{Diagnostic, DiagnosticCode, Location};
+use crate::core::vfs::{FileSource, RealFs};
+use crate::core::EntrySource;
+use crate::i18n::{tr, Msg};
 use crate::utils::display::display_json_map;
 
+/// キーを`.`区切りの形式に変換
+///
+/// systemd-sysctlと同じく、最初の区切り文字で形式を判定する。最初が`/`の場合は`/`と`.`を入れ替え
+/// （`net/ipv4/conf/eth0.100/rp_filter`は`net.ipv4.conf.eth0/100.rp_filter`となる）、
+/// 最初が`.`の場合はそのまま使用する。
+pub fn normalize_key(key: &str) -> Cow<'_, str> {
+    match key.find(['.', '/']) {
+        Some(index) if key[index..].starts_with('/') => Cow::Owned(swap_separators(key)),
+        _ => Cow::Borrowed(key),
+    }
+}
+
+/// `.`と`/`を入れ替える（`.`区切りのキーと`/proc/sys`からの相対パスの変換）
+pub fn swap_separators(key: &str) -> String {
+    key.chars()
+        .map(|c| match c {
+            '.' => '/',
+            '/' => '.',
+            c => c,
+        })
+        .collect()
+}
+
 /// .confファイルのパース処理
+///
+/// `verbose`が有効な場合はファイルごとのパース結果を`schema`の型に従ってJSON形式で表示する。
+/// 各キーの定義位置を`sources`に記録し、警告を返す。
 pub fn parse_conf_file(
     path: &Path,
-    parsed_files: &mut FxHashSet<String>,
     result_map: &mut FxHashMap<String, String>,
-) -> io::Result<()> {
-    let path_str: String = path.to_string_lossy().to_string();
+    sources: &mut BTreeMap<String, EntrySource>,
+    schema: &FxHashMap<String, String>,
+    verbose: bool,
+) -> io::Result<Vec<Diagnostic>> {
+    let (entries, warnings) = parse_conf_entries(path)?;
+    merge_conf_entries(path, entries, result_map, sources, schema, verbose);
+    Ok(warnings)
+}
+
+/// パース済みのエントリを`result_map`に追加し、定義位置を`sources`に記録
+///
+/// `verbose`が有効な場合はファイルのパース結果を`schema`の型に従ってJSON形式で表示する
+pub fn merge_conf_entries(
+    path: &Path,
+    entries: Vec<ConfEntry>,
+    result_map: &mut FxHashMap<String, String>,
+    sources: &mut BTreeMap<String, EntrySource>,
+    schema: &FxHashMap<String, String>,
+    verbose: bool,
+) {
+    if verbose {
+        println!("File: {:?}", path);
+        display_json_map(&entries_to_map(&entries), schema);
+        println!();
+    }
 
-    if parsed_files.contains(&path_str) {
-        // 既にパース済みならスキップ
-        return Ok(());
+    // パース結果をresult_mapに追加
+    for entry in entries {
+        let source: EntrySource = EntrySource {
+            location: entry.location(path),
+            ignore_failure: entry.ignore_failure,
+        };
+        sources.insert(entry.key.clone(), source);
+        result_map.insert(entry.key, entry.value);
     }
+}
 
-    println!("File: {:?}", path);
-    match parse_conf_to_map(path) {
-        Ok(config_map) => {
-            display_json_map(&config_map);
-            println!();
+/// 設定ファイルをパースし、結果をFxHashMap格納
+pub fn parse_conf_to_map(file_path: &Path) -> io::Result<FxHashMap<String, String>> {
+    parse_conf_entries(file_path).map(|(entries, _)| entries_to_map(&entries))
+}
 
-            // パース結果をresult_mapに追加
-            for (key, value) in config_map {
-                result_map.insert(key.to_string(), value);
-            }
+/// エントリをマップに変換（同じキーは後の値が優先）
+fn entries_to_map(entries: &[ConfEntry]) -> FxHashMap<String, String> {
+    entries
+        .iter()
+        .map(|entry| (entry.key.clone(), entry.value.clone()))
+        .collect()
+}
+
+/// 設定ファイル内のエントリ（キー・値と出現位置）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfEntry {
+    pub key: String,
+    pub value: String,
+    /// 行番号（1始まり）
+    pub line: usize,
+    /// 値の開始列（1始まり、文字単位）
+    pub column: usize,
+    /// キーに`-`が付いている（適用に失敗しても無視する）
+    pub ignore_failure: bool,
+}
 
-            // パース済みとしてセットに追加
-            parsed_files.insert(path_str);
+impl ConfEntry {
+    /// ファイル内での値の位置
+    pub fn location(&self, file: &Path) -> Location {
+        Location {
+            file: file.to_path_buf(),
+            line: self.line,
+            column: self.column,
+            end_column: self.column + self.value.chars().count(),
         }
-        Err(e) => {
-            eprintln!(
-                "Error: ファイル '{}' のパースに失敗しました: {}",
-                path.display(),
-                e
-            );
+    }
+}
+
+/// 入力を借用した設定内容のエントリ（キー・値と出現位置）
+///
+/// キーと値は入力の一部を参照する。`/`区切りのキーを`.`区切りに変換した場合のみキーを所有する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfEntryRef<'a> {
+    pub key: Cow<'a, str>,
+    pub value: Cow<'a, str>,
+    /// 行番号（1始まり）
+    pub line: usize,
+    /// 値の開始列（1始まり、文字単位）
+    pub column: usize,
+    /// キーに`-`が付いている（適用に失敗しても無視する）
+    pub ignore_failure: bool,
+}
+
+impl ConfEntryRef<'_> {
+    /// `origin`内での値の位置
+    pub fn location(&self, origin: &Path) -> Location {
+        Location {
+            file: origin.to_path_buf(),
+            line: self.line,
+            column: self.column,
+            end_column: self.column + self.value.chars().count(),
+        }
+    }
+
+    /// 入力から独立したエントリに変換
+    pub fn into_owned(self) -> ConfEntry {
+        ConfEntry {
+            key: self.key.into_owned(),
+            value: self.value.into_owned(),
+            line: self.line,
+            column: self.column,
+            ignore_failure: self.ignore_failure,
         }
     }
+}
 
-    Ok(())
+/// 設定ファイルをパースし、エントリ（出現順）と警告を返す
+///
+/// '='を含まない行と、同じファイル内で重複して設定されたキーを警告とする（`parse_conf_str`と同じ規則）
+pub fn parse_conf_entries(file_path: &Path) -> io::Result<(Vec<ConfEntry>, Vec<Diagnostic>)> {
+    parse_source_entries(&RealFs::default(), file_path)
 }
 
-/// 設定ファイルをパースし、結果をFxHashMap格納
-pub fn parse_conf_to_map(file_path: &Path) -> io::Result<FxHashMap<String, String>> {
-    let file: File = fs::File::open(file_path).map_err(|e: Error| {
-        eprintln!(
-            "Error: ファイル '{}' を開く際にエラーが発生しました: {}",
-            file_path.display(),
-            e
-        );
-        e
+/// 読み込み元`source`の設定ファイルをパースし、エントリ（出現順）と警告を返す
+///
+/// `parse_conf_bytes`と同じく、UTF-8として正しくないファイルは読み込みエラーとする
+pub fn parse_source_entries(
+    source: &dyn FileSource,
+    file_path: &Path,
+) -> io::Result<(Vec<ConfEntry>, Vec<Diagnostic>)> {
+    let content: Cow<[u8]> = source.read(file_path).inspect_err(|e: &Error| {
+        eprintln!("{}", tr(Msg::FileOpenFailed, &[&file_path.display(), &e]));
     })?;
-    let reader: io::BufReader<File> = io::BufReader::new(file);
+    let (entries, warnings) = parse_conf_bytes(&content, file_path).inspect_err(|e: &Error| {
+        eprintln!("{}", tr(Msg::FileReadFailed, &[&file_path.display(), &e]));
+    })?;
+    Ok((
+        entries.into_iter().map(ConfEntryRef::into_owned).collect(),
+        warnings,
+    ))
+}
 
-    let mut map: FxHashMap<String, String> = FxHashMap::default();
+/// メモリ上の設定内容をパースし、入力を借用したエントリ（出現順）と警告を返す
+///
+/// `origin`は警告のメッセージと位置に使用する名前で、ファイルの読み込みは行わない
+/// （アーカイブ内のパスなど、実在しないパスでもよい）。
+/// '='を含まない行と、同じ入力内で重複して設定されたキーを警告とする。
+/// 値が長さの上限を超える行はエラーの診断とし、エントリに含めない。
+pub fn parse_conf_str<'a>(
+    input: &'a str,
+    origin: &Path,
+) -> (Vec<ConfEntryRef<'a>>, Vec<Diagnostic>) {
+    let mut entries: Vec<ConfEntryRef<'a>> = Vec::new();
+    let mut seen_keys: FxHashMap<&'a str, usize> = FxHashMap::default();
+    let mut warnings: Vec<Diagnostic> = Vec::new();
 
-    for line in reader.lines() {
-        let line: String = line.map_err(|e: Error| {
-            eprintln!(
-                "Error: ファイル '{}' の読み込み中にエラーが発生しました: {}",
-                file_path.display(),
-                e
-            );
-            e
-        })?;
+    for (index, line) in input.lines().enumerate() {
+        let line_number: usize = index + 1;
         let trimmed: &str = line.trim();
 
         // 空行とコメント行を無視
@@ -76,17 +204,102 @@ pub fn parse_conf_to_map(file_path: &Path) -> io::Result<FxHashMap<String, Strin
         }
 
         // '='で分割してキーと値を抽出
-        if let Some((key, value)) = trimmed.split_once('=') {
-            let key: &str = key.trim();
-            let value: &str = value.trim();
+        if let Some((raw_key, raw_value)) = line.split_once('=') {
+            // キーの先頭の`-`は適用に失敗しても無視する指定
+            let (key, ignore_failure): (&str, bool) = match raw_key.trim().strip_prefix('-') {
+                Some(key) => (key.trim_start(), true),
+                None => (raw_key.trim(), false),
+            };
+            let value: &str = raw_value.trim();
 
-            // 値が4096文字を超えた場合はパニック
+            // 値の開始位置（'='の後の空白を除く）を列番号に変換
+            let value_offset: usize =
+                raw_key.len() + 1 + (raw_value.len() - raw_value.trim_start().len());
+            let entry: ConfEntryRef<'a> = ConfEntryRef {
+                key: normalize_key(key),
+                value: Cow::Borrowed(value),
+                line: line_number,
+                column: line[..value_offset].chars().count() + 1,
+                ignore_failure,
+            };
+
+            // 値が4096文字を超えた行はエラーとして無視（信頼できない入力で処理を中断しない）
             if value.len() > Config::MAX_VALUE_LENGTH {
-                panic!("Error: キー '{}' の値が4096文字を超えています。👀", key);
+                warnings.push(
+                    Diagnostic::new(
+                        DiagnosticCode::ValueTooLong,
+                        tr(
+                            Msg::ValueTooLong,
+                            &[
+                                &origin.display(),
+                                &line_number,
+                                &key,
+                                &Config::MAX_VALUE_LENGTH,
+                            ],
+                        ),
+                    )
+                    .with_key(key)
+                    .with_location(Some(entry.location(origin))),
+                );
+                continue;
+            }
+
+            if let Some(first_line) = seen_keys.insert(key, line_number) {
+                warnings.push(
+                    Diagnostic::new(
+                        DiagnosticCode::DuplicateKey,
+                        tr(
+                            Msg::DuplicateKey,
+                            &[&origin.display(), &line_number, &key, &first_line],
+                        ),
+                    )
+                    .with_key(key)
+                    .with_value(value)
+                    .with_location(Some(entry.location(origin))),
+                );
             }
-            map.insert(key.to_string(), value.to_string());
+            entries.push(entry);
+        } else {
+            warnings.push(
+                Diagnostic::new(
+                    DiagnosticCode::MissingSeparator,
+                    tr(
+                        Msg::MissingSeparator,
+                        &[&origin.display(), &line_number, &trimmed],
+                    ),
+                )
+                .with_location(Some(Location {
+                    file: origin.to_path_buf(),
+                    line: line_number,
+                    column: line[..line.len() - line.trim_start().len()].chars().count() + 1,
+                    end_column: line.trim_end().chars().count() + 1,
+                })),
+            );
         }
     }
 
-    Ok(map)
+    (entries, warnings)
+}
+
+/// バイト列の設定内容をパースし、エントリ（出現順）と警告を返す
+///
+/// エントリは入力を借用する。UTF-8として正しくない入力は、値を置き換えて`/proc/sys`に
+/// 書き込むことが無いよう`InvalidData`のエラーとする（ファイルのパースと同じ）。
+pub fn parse_conf_bytes<'a>(
+    input: &'a [u8],
+    origin: &Path,
+) -> io::Result<(Vec<ConfEntryRef<'a>>, Vec<Diagnostic>)> {
+    let text: &str = std::str::from_utf8(input).map_err(|e| {
+        // 不正なバイト列の行番号を示す
+        let line: usize = input[..e.valid_up_to()]
+            .iter()
+            .filter(|byte| **byte == b'\n')
+            .count()
+            + 1;
+        Error::new(
+            io::ErrorKind::InvalidData,
+            tr(Msg::InvalidUtf8, &[&origin.display(), &line]),
+        )
+    })?;
+    Ok(parse_conf_str(text, origin))
 }
@@ -0,0 +1,394 @@
+use std::io;
+
+use crate::config::Config;
+use crate::i18n::{tr, Msg};
+
+/// 長さの符号（257〜285）ごとの基準値と追加ビット数
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// 距離の符号（0〜29）ごとの基準値と追加ビット数
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// 符号長の符号長が格納される順
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// CRC-32（gzipのトレーラーの検証に使用）の表
+const CRC_TABLE: [u32; 256] = {
+    let mut table: [u32; 256] = [0; 256];
+    let mut index: usize = 0;
+    while index < 256 {
+        let mut crc: u32 = index as u32;
+        let mut bit: usize = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[index] = crc;
+        index += 1;
+    }
+    table
+};
+
+/// gzip形式のデータか（先頭のマジックナンバーで判定）
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
+
+/// gzip形式のデータを展開（複数のメンバーを連結したデータにも対応）
+///
+/// 展開後の大きさは`Config::MAX_DECOMPRESSED_SIZE`までとする（`decompress_gzip_with_limit`を参照）
+pub fn decompress_gzip(data: &[u8]) -> io::Result<Vec<u8>> {
+    decompress_gzip_with_limit(data, Config::MAX_DECOMPRESSED_SIZE)
+}
+
+/// gzip形式のデータを展開後の大きさが`limit`バイトまでの範囲で展開
+///
+/// 各メンバーのCRC-32と長さを検証し、形式が不正な場合や展開後の大きさが`limit`を超える場合は
+/// `InvalidData`のエラーとなる（信頼できないイメージの圧縮爆弾でメモリを使い果たさない）
+pub fn decompress_gzip_with_limit(data: &[u8], limit: usize) -> io::Result<Vec<u8>> {
+    let mut output: Output = Output {
+        data: Vec::new(),
+        start: 0,
+        limit,
+        exceeded: false,
+    };
+    let mut rest: &[u8] = data;
+    loop {
+        let body: &[u8] = skip_header(rest).ok_or_else(invalid)?;
+        output.start = output.data.len();
+        let mut reader: BitReader = BitReader::new(body);
+        if inflate(&mut reader, &mut output).is_none() {
+            return Err(match output.exceeded {
+                true => too_large(limit),
+                false => invalid(),
+            });
+        }
+
+        // トレーラー（CRC-32と展開後の長さ）を検証
+        let start: usize = output.start;
+        let output_data: &[u8] = &output.data;
+        let consumed: usize = reader.consumed();
+        let trailer: &[u8] = body.get(consumed..consumed + 8).ok_or_else(invalid)?;
+        let crc: u32 = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let size: u32 = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        if crc != crc32(&output_data[start..]) || size != (output_data.len() - start) as u32 {
+            return Err(invalid());
+        }
+
+        // 後続のメンバーが無ければ終了（末尾の0埋めは無視する）
+        rest = &body[consumed + 8..];
+        if !is_gzip(rest) {
+            break;
+        }
+    }
+    Ok(output.data)
+}
+
+/// 展開先（大きさが`limit`を超える書き込みは行わず、`exceeded`とする）
+struct Output {
+    data: Vec<u8>,
+    /// 展開中のメンバーの開始位置（距離はこの位置より前を参照できない）
+    start: usize,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl Output {
+    /// `length`バイトを書き込めるか
+    fn reserve(&mut self, length: usize) -> Option<()> {
+        if length > self.limit - self.data.len() {
+            self.exceeded = true;
+            return None;
+        }
+        Some(())
+    }
+
+    fn push(&mut self, byte: u8) -> Option<()> {
+        self.reserve(1)?;
+        self.data.push(byte);
+        Some(())
+    }
+
+    fn extend(&mut self, bytes: &[u8]) -> Option<()> {
+        self.reserve(bytes.len())?;
+        self.data.extend_from_slice(bytes);
+        Some(())
+    }
+
+    /// `distance`バイト前から`length`バイトを複製
+    fn copy(&mut self, distance: usize, length: usize) -> Option<()> {
+        if distance > self.data.len() - self.start {
+            return None;
+        }
+        self.reserve(length)?;
+        // 重なる範囲の複製にも対応するため1バイトずつ複製
+        let from: usize = self.data.len() - distance;
+        for offset in 0..length {
+            self.data.push(self.data[from + offset]);
+        }
+        Some(())
+    }
+}
+
+/// gzipのヘッダを読み飛ばし、圧縮されたデータを返す
+fn skip_header(data: &[u8]) -> Option<&[u8]> {
+    // マジックナンバー・圧縮方式（8はDEFLATE）
+    if !is_gzip(data) || *data.get(2)? != 8 {
+        return None;
+    }
+    let flags: u8 = *data.get(3)?;
+    let mut position: usize = 10;
+    if flags & 0x04 != 0 {
+        // 拡張フィールド
+        let length: usize = usize::from(u16::from_le_bytes([
+            *data.get(position)?,
+            *data.get(position + 1)?,
+        ]));
+        position += 2 + length;
+    }
+    for flag in [0x08, 0x10] {
+        // ファイル名・コメント（NULで終わる）
+        if flags & flag != 0 {
+            position += data.get(position..)?.iter().position(|byte| *byte == 0)? + 1;
+        }
+    }
+    if flags & 0x02 != 0 {
+        // ヘッダのCRC
+        position += 2;
+    }
+    data.get(position..)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc: u32, byte| {
+        CRC_TABLE[((crc ^ u32::from(*byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// 下位のビットから順に読み込む
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    bits: u64,
+    count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            position: 0,
+            bits: 0,
+            count: 0,
+        }
+    }
+
+    fn read(&mut self, count: u32) -> Option<u32> {
+        while self.count < count {
+            let byte: u8 = *self.data.get(self.position)?;
+            self.position += 1;
+            self.bits |= u64::from(byte) << self.count;
+            self.count += 8;
+        }
+        let value: u32 = (self.bits & ((1 << count) - 1)) as u32;
+        self.bits >>= count;
+        self.count -= count;
+        Some(value)
+    }
+
+    /// 次のバイト境界まで読み飛ばす
+    fn align(&mut self) {
+        let skip: u32 = self.count % 8;
+        self.bits >>= skip;
+        self.count -= skip;
+    }
+
+    /// バイト境界から`length`バイトを読み込む
+    fn read_bytes(&mut self, length: usize, output: &mut Output) -> Option<()> {
+        let mut remaining: usize = length;
+        while remaining > 0 && self.count >= 8 {
+            output.push(self.read(8)? as u8)?;
+            remaining -= 1;
+        }
+        let bytes: &[u8] = self.data.get(self.position..self.position + remaining)?;
+        output.extend(bytes)?;
+        self.position += remaining;
+        Some(())
+    }
+
+    /// 読み込んだバイト数（読み込み途中のバイトを含む）
+    fn consumed(&self) -> usize {
+        self.position - (self.count / 8) as usize
+    }
+}
+
+/// 正規ハフマン符号（符号長ごとの数と、符号長・値の順に並べた値）
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// 値ごとの符号長から作成（符号が過剰な場合はNone）
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let mut counts: [u16; 16] = [0; 16];
+        for length in lengths {
+            counts[usize::from(*length)] += 1;
+        }
+        let mut left: i32 = 1;
+        for count in &counts[1..] {
+            left = (left << 1) - i32::from(*count);
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut offsets: [u16; 16] = [0; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols: Vec<u16> = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                let offset: &mut u16 = &mut offsets[usize::from(*length)];
+                symbols[usize::from(*offset)] = symbol as u16;
+                *offset += 1;
+            }
+        }
+        Some(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Option<u16> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for count in &self.counts[1..] {
+            code |= reader.read(1)? as i32;
+            let count: i32 = i32::from(*count);
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// DEFLATE形式のデータを展開して`output`に追加
+fn inflate(reader: &mut BitReader, output: &mut Output) -> Option<()> {
+    loop {
+        let last: bool = reader.read(1)? == 1;
+        match reader.read(2)? {
+            0 => {
+                // 無圧縮のブロック
+                reader.align();
+                let length: u32 = reader.read(16)?;
+                if length != !reader.read(16)? & 0xffff {
+                    return None;
+                }
+                reader.read_bytes(length as usize, output)?;
+            }
+            1 => {
+                let mut lengths: [u8; 288] = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literals: Huffman = Huffman::new(&lengths)?;
+                let distances: Huffman = Huffman::new(&[5; 30])?;
+                inflate_block(reader, output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = read_dynamic_tables(reader)?;
+                inflate_block(reader, output, &literals, &distances)?;
+            }
+            _ => return None,
+        }
+        if last {
+            return Some(());
+        }
+    }
+}
+
+/// 動的ハフマン符号のブロックの符号表を読み込む
+fn read_dynamic_tables(reader: &mut BitReader) -> Option<(Huffman, Huffman)> {
+    let literal_count: usize = reader.read(5)? as usize + 257;
+    let distance_count: usize = reader.read(5)? as usize + 1;
+    let code_length_count: usize = reader.read(4)? as usize + 4;
+
+    let mut code_lengths: [u8; 19] = [0; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*index] = reader.read(3)? as u8;
+    }
+    let code_length_codes: Huffman = Huffman::new(&code_lengths)?;
+
+    let mut lengths: Vec<u8> = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat): (u8, u32) = match code_length_codes.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + reader.read(2)?),
+            17 => (0, 3 + reader.read(3)?),
+            _ => (0, 11 + reader.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(value, repeat as usize));
+    }
+    if lengths.len() != literal_count + distance_count || lengths[256] == 0 {
+        return None;
+    }
+    Some((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+/// ハフマン符号化されたブロックを展開
+fn inflate_block(
+    reader: &mut BitReader,
+    output: &mut Output,
+    literals: &Huffman,
+    distances: &Huffman,
+) -> Option<()> {
+    loop {
+        let symbol: usize = usize::from(literals.decode(reader)?);
+        if symbol < 256 {
+            output.push(symbol as u8)?;
+            continue;
+        }
+        if symbol == 256 {
+            return Some(());
+        }
+
+        let index: usize = symbol - 257;
+        let length: usize = usize::from(*LENGTH_BASE.get(index)?)
+            + reader.read(u32::from(LENGTH_EXTRA[index]))? as usize;
+        let index: usize = usize::from(distances.decode(reader)?);
+        let distance: usize = usize::from(*DISTANCE_BASE.get(index)?)
+            + reader.read(u32::from(DISTANCE_EXTRA[index]))? as usize;
+        output.copy(distance, length)?;
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, tr(Msg::GzipInvalid, &[]))
+}
+
+fn too_large(limit: usize) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, tr(Msg::GzipTooLarge, &[&limit]))
+}
@@ -0,0 +1,260 @@
+use rustc_hash::FxHashMap;
+use serde_json::Value;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use super::gzip::{decompress_gzip, is_gzip};
+use super::tar::{read_tar, read_tar_headers, TarEntry, TarEntryKind, TarHeader};
+use super::vfs::MemoryFs;
+use crate::i18n::{tr, Msg};
+
+/// 下位のレイヤーのファイルを削除するファイル名の接頭辞
+const WHITEOUT_PREFIX: &str = ".wh.";
+/// 下位のレイヤーのディレクトリの内容を全て削除するファイル名
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+/// zstd形式のデータの先頭のマジックナンバー
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// 入れ子になったインデックスを辿る回数の上限
+const MAX_INDEX_DEPTH: usize = 4;
+/// アーカイブ内のシンボリックリンクを辿る回数の上限
+const MAX_LINK_FOLLOWS: usize = 8;
+
+/// コンテナイメージのレイヤーを順に適用したファイルシステムを作成
+///
+/// `path`は`docker save`で作成したtarball（`manifest.json`）、OCIイメージレイアウトのtarball
+/// またはディレクトリ（`index.json`）とする。複数のイメージを含む場合は最初のイメージを、
+/// 複数のプラットフォームを含む場合は実行中のアーキテクチャのLinux向けのイメージ（無ければ最初のもの）を使用する。
+/// レイヤーは無圧縮またはgzip形式に対応する。レイヤーは1つずつ全体をメモリに読み込むため、
+/// gzip形式のレイヤーの展開後の大きさは`Config::MAX_DECOMPRESSED_SIZE`までとする。
+pub fn load_image(path: &Path) -> io::Result<MemoryFs> {
+    let mut archive: ImageArchive = ImageArchive::open(path)?;
+    let mut files: MemoryFs = MemoryFs::new();
+    for layer in layer_paths(&mut archive)? {
+        let data: Vec<u8> = archive.read(&layer)?;
+        let data: Vec<u8> = if is_gzip(&data) {
+            decompress_gzip(&data)?
+        } else if data.starts_with(&ZSTD_MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                tr(Msg::ImageLayerUnsupported, &[&layer]),
+            ));
+        } else {
+            data
+        };
+        apply_layer(&mut files, &data)?;
+    }
+    Ok(files)
+}
+
+/// レイヤー（tarアーカイブ）を適用
+///
+/// ホワイトアウト（`.wh.<名前>`・`.wh..wh..opq`）は下位のレイヤーのファイルを削除する。
+/// 設定ファイルの探索に不要なため、`.conf`以外のファイルの内容は保持しない。
+pub fn apply_layer(files: &mut MemoryFs, layer: &[u8]) -> io::Result<()> {
+    let entries: Vec<TarEntry> = read_tar(layer)?;
+
+    // ホワイトアウトは下位のレイヤーのみに作用するため、同じレイヤーのファイルより先に削除
+    for entry in &entries {
+        let Some(name) = entry.path.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+        let parent: &Path = entry.path.parent().unwrap_or(Path::new(""));
+        if name == OPAQUE_WHITEOUT {
+            for child in files.children(parent) {
+                files.remove(parent.join(child));
+            }
+        } else if let Some(hidden) = name.strip_prefix(WHITEOUT_PREFIX) {
+            files.remove(parent.join(hidden));
+        }
+    }
+
+    for entry in entries {
+        let is_whiteout: bool = entry
+            .path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| name.starts_with(WHITEOUT_PREFIX));
+        if is_whiteout {
+            continue;
+        }
+        let is_conf: bool = entry.path.extension() == Some(OsStr::new("conf"));
+        if entry.kind == TarEntryKind::File && !is_conf {
+            files.add_tar_entry(TarEntry { data: &[], ..entry });
+        } else {
+            files.add_tar_entry(entry);
+        }
+    }
+    Ok(())
+}
+
+/// イメージの内容の読み込み元
+enum ImageArchive {
+    /// OCIイメージレイアウトのディレクトリ
+    Directory(PathBuf),
+    /// tarball（開く際はエントリの位置のみを保持し、読み込むエントリごとにその全体を読み込む）
+    Tarball {
+        file: File,
+        entries: FxHashMap<PathBuf, TarHeader>,
+    },
+}
+
+impl ImageArchive {
+    fn open(path: &Path) -> io::Result<ImageArchive> {
+        if path.is_dir() {
+            return Ok(ImageArchive::Directory(path.to_path_buf()));
+        }
+        let mut file: File = File::open(path)?;
+        let entries: FxHashMap<PathBuf, TarHeader> = read_tar_headers(&mut file)?
+            .into_iter()
+            .map(|header| (archive_path(&header.path), header))
+            .collect();
+        Ok(ImageArchive::Tarball { file, entries })
+    }
+
+    /// アーカイブのルートからの相対パスのファイルを読み込む
+    fn read(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        let missing =
+            || io::Error::new(io::ErrorKind::NotFound, tr(Msg::ImageBlobMissing, &[&name]));
+        match self {
+            ImageArchive::Directory(root) => fs::read(root.join(archive_path(Path::new(name))))
+                .map_err(|e| match e.kind() {
+                    io::ErrorKind::NotFound => missing(),
+                    _ => e,
+                }),
+            ImageArchive::Tarball { file, entries } => {
+                // 同じレイヤーはシンボリックリンクで共有される場合がある
+                let mut path: PathBuf = archive_path(Path::new(name));
+                for _ in 0..=MAX_LINK_FOLLOWS {
+                    let header: &TarHeader = entries.get(&path).ok_or_else(missing)?;
+                    match &header.kind {
+                        TarEntryKind::File => {
+                            let mut data: Vec<u8> = vec![0; header.size as usize];
+                            file.seek(SeekFrom::Start(header.offset))?;
+                            file.read_exact(&mut data)?;
+                            return Ok(data);
+                        }
+                        TarEntryKind::Symlink(target) => {
+                            path =
+                                archive_path(&path.parent().unwrap_or(Path::new("")).join(target));
+                        }
+                        TarEntryKind::HardLink(target) => path = archive_path(target),
+                        _ => break,
+                    }
+                }
+                Err(missing())
+            }
+        }
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        match self {
+            ImageArchive::Directory(root) => root.join(name).is_file(),
+            ImageArchive::Tarball { entries, .. } => entries.contains_key(Path::new(name)),
+        }
+    }
+
+    /// JSONのファイルを読み込む
+    fn read_json(&mut self, name: &str) -> io::Result<Value> {
+        serde_json::from_slice(&self.read(name)?).map_err(|_| invalid_manifest(name))
+    }
+}
+
+/// 適用する順のレイヤーのアーカイブ内のパス
+fn layer_paths(archive: &mut ImageArchive) -> io::Result<Vec<String>> {
+    if archive.contains("manifest.json") {
+        // docker save の形式
+        let manifest: Value = archive.read_json("manifest.json")?;
+        return manifest
+            .get(0)
+            .and_then(|image| image.get("Layers"))
+            .and_then(Value::as_array)
+            .and_then(|layers| {
+                layers
+                    .iter()
+                    .map(|layer| layer.as_str().map(str::to_string))
+                    .collect::<Option<Vec<String>>>()
+            })
+            .ok_or_else(|| invalid_manifest("manifest.json"));
+    }
+    if !archive.contains("index.json") {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            tr(Msg::ImageManifestMissing, &[]),
+        ));
+    }
+
+    // OCIイメージレイアウト（インデックスからイメージのマニフェストを辿る）
+    let mut name: String = "index.json".to_string();
+    let mut manifest: Value = archive.read_json(&name)?;
+    for _ in 0..MAX_INDEX_DEPTH {
+        let Some(manifests) = manifest.get("manifests").and_then(Value::as_array) else {
+            break;
+        };
+        let selected: &Value = manifests
+            .iter()
+            .find(|candidate| matches_platform(candidate))
+            .or_else(|| manifests.first())
+            .ok_or_else(|| invalid_manifest(&name))?;
+        name = digest_path(selected).ok_or_else(|| invalid_manifest(&name))?;
+        manifest = archive.read_json(&name)?;
+    }
+    manifest
+        .get("layers")
+        .and_then(Value::as_array)
+        .and_then(|layers| layers.iter().map(digest_path).collect::<Option<Vec<_>>>())
+        .ok_or_else(|| invalid_manifest(&name))
+}
+
+/// 記述子が実行中のアーキテクチャのLinux向けか
+fn matches_platform(descriptor: &Value) -> bool {
+    let Some(platform) = descriptor.get("platform") else {
+        return false;
+    };
+    let architecture: &str = match std::env::consts::ARCH {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "x86" => "386",
+        "powerpc64" => "ppc64le",
+        other => other,
+    };
+    platform.get("os").and_then(Value::as_str) == Some("linux")
+        && platform.get("architecture").and_then(Value::as_str) == Some(architecture)
+}
+
+/// 記述子のダイジェスト（`sha256:<16進数>`）に対応するブロブのパス
+fn digest_path(descriptor: &Value) -> Option<String> {
+    let (algorithm, hex) = descriptor.get("digest")?.as_str()?.split_once(':')?;
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '.' | '_' | '-'))
+            && part != "."
+            && part != ".."
+    };
+    (valid(algorithm) && valid(hex)).then(|| format!("blobs/{}/{}", algorithm, hex))
+}
+
+/// アーカイブ内のパスを正規化（`./`や`..`を取り除いた相対パス）
+fn archive_path(path: &Path) -> PathBuf {
+    let mut normalized: PathBuf = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => normalized.push(part),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => {}
+        }
+    }
+    normalized
+}
+
+fn invalid_manifest(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        tr(Msg::ImageManifestInvalid, &[&name]),
+    )
+}
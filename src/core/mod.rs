@@ -1,50 +1,197 @@
+pub mod cache;
+pub mod diagnostic;
+pub mod diff;
 pub mod directory_parser;
+pub mod edit;
+pub mod explain;
 pub mod file_parser;
+pub mod gzip;
+pub mod image;
+pub mod parallel;
+pub mod procfs;
+pub mod query;
 pub mod schema;
+pub mod snapshot;
+pub mod syntax;
+pub mod sysroot;
+pub mod tar;
+pub mod vfs;
+pub mod watch;
 
+use diagnostic::{attach_notes, Diagnostic, DiagnosticCode, Location};
 use rustc_hash::FxHashMap;
-use std::{io, path::Path};
+use std::{
+    collections::BTreeMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::i18n::{tr, Msg};
+use crate::utils::report::{render_human_from, stderr_color};
+use vfs::FileSource;
+
+/// パース処理の動作設定
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// ファイルごとのパース結果や進捗メッセージを表示するか
+    pub verbose: bool,
+    /// パース結果をスキーマに基づいて検証するか
+    pub validate: bool,
+    /// 探索ディレクトリを解決するルートディレクトリ（chroot・展開済みイメージなど）
+    pub root: Option<PathBuf>,
+    /// ファイルごとのパース結果のキャッシュファイル（Noneの場合は使用しない）
+    pub cache: Option<PathBuf>,
+    /// ファイルの探索・パースを並列に行うスレッド数（0の場合は使用できるCPU数）
+    pub jobs: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            verbose: true,
+            validate: true,
+            root: None,
+            cache: None,
+            jobs: 0,
+        }
+    }
+}
+
+/// 有効な値を設定したエントリの出所
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntrySource {
+    /// 値の定義位置
+    pub location: Location,
+    /// キーに`-`が付いている（適用に失敗しても無視する）
+    pub ignore_failure: bool,
+}
+
+/// パース処理の結果（パースしたファイル数・診断・各キーの出所）
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ParseReport {
+    /// パースしたファイル数（キャッシュから読み込んだファイルを含む）
+    pub files: usize,
+    /// キャッシュから読み込んだファイル数
+    pub cached_files: usize,
+    /// 見つかった問題（読み込みエラー・警告・スキーマ検証エラー）
+    pub diagnostics: Vec<Diagnostic>,
+    /// 各キーの有効な値を設定したエントリ（キーの順）
+    pub sources: BTreeMap<String, EntrySource>,
+}
+
+impl ParseReport {
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.is_error()).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics.len() - self.error_count()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+
+    /// ファイルやディレクトリの読み込みエラーがあるか
+    pub fn has_read_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|d| d.code == DiagnosticCode::ReadError)
+    }
+}
 
 pub trait ParseFiles {
+    /// ファイルをパースして検証し、問題があれば標準エラー出力に表示してエラーを返す
     fn parse_all_conf_files(
         &self,
         directories: &[&str],
         schema: &FxHashMap<String, String>,
         result_map: &mut FxHashMap<String, String>,
-    ) -> io::Result<()>;
+    ) -> io::Result<()> {
+        let report: ParseReport = self.parse_all_conf_files_with_options(
+            directories,
+            schema,
+            result_map,
+            &ParseOptions::default(),
+        )?;
+        eprint!(
+            "{}",
+            render_human_from(&report.diagnostics, self.file_source(), stderr_color())
+        );
+        if report.has_errors() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr(Msg::ConfigHasErrors, &[]),
+            ));
+        }
+        Ok(())
+    }
+
+    /// 設定に従ってファイルをパースし、見つかった問題を診断として返す
+    ///
+    /// 診断の表示は呼び出し側で行う
+    fn parse_all_conf_files_with_options(
+        &self,
+        directories: &[&str],
+        schema: &FxHashMap<String, String>,
+        result_map: &mut FxHashMap<String, String>,
+        options: &ParseOptions,
+    ) -> io::Result<ParseReport>;
+
+    /// 診断の位置のファイルを読み込む読み込み元（該当行の表示に使用）
+    fn file_source(&self) -> &dyn FileSource;
 }
 
 pub trait SchemaLoader {
     fn load_schema(&self, schema_file: &Path) -> io::Result<FxHashMap<String, String>>;
+
+    /// キーごとの補足説明（診断のヘルプとして表示）
+    fn load_schema_notes(&self, _schema_file: &Path) -> io::Result<FxHashMap<String, String>> {
+        Ok(FxHashMap::default())
+    }
 }
 
 /// スキーマファイルを読み込み、ディレクトリを再帰的に探索してファイルをパースし、スキーマに基づいて型の整合性を検証
 pub fn validate_schema_and_parse_files(
     schema_file: &str,
     directories: &[&str],
-    parser: &impl ParseFiles,
-    schema: &impl SchemaLoader,
+    parser: &(impl ParseFiles + ?Sized),
+    loader: &impl SchemaLoader,
     result_map: &mut FxHashMap<String, String>,
-) -> io::Result<()> {
+    options: &ParseOptions,
+) -> io::Result<ParseReport> {
     let schema_path: &Path = Path::new(schema_file);
 
-    // スキーマファイルを読み込む
-    let schema: FxHashMap<String, String> = match schema.load_schema(schema_path) {
-        Ok(schema) => schema,
-        Err(e) => {
-            eprintln!("スキーマファイルの読み込みに失敗しました: {}", e);
-            return Err(e);
+    // スキーマファイルを読み込む（検証しない場合は不要）
+    let schema: FxHashMap<String, String> = if options.validate {
+        match loader.load_schema(schema_path) {
+            Ok(schema) => schema,
+            Err(e) => {
+                eprintln!("{}", tr(Msg::SchemaLoadFailed, &[&e]));
+                return Err(e);
+            }
         }
+    } else {
+        FxHashMap::default()
     };
 
     // ディレクトリを探索し、ファイルをパースして結果を検証
-    match parser.parse_all_conf_files(directories, &schema, result_map) {
-        Ok(_) => {
-            println!("全てのファイルが正常にパースされ、スキーマに従っています。");
-            Ok(())
+    match parser.parse_all_conf_files_with_options(directories, &schema, result_map, options) {
+        Ok(mut report) => {
+            // スキーマの補足説明を診断のヘルプとして付与
+            if options.validate {
+                let notes: FxHashMap<String, String> =
+                    loader.load_schema_notes(schema_path).unwrap_or_default();
+                attach_notes(&mut report.diagnostics, &notes);
+            }
+
+            if options.verbose && options.validate && !report.has_errors() {
+                println!("{}", tr(Msg::AllFilesValid, &[]));
+            }
+            Ok(report)
         }
         Err(e) => {
-            eprintln!("設定ファイルのパース中にエラーが発生しました: {}", e);
+            eprintln!("{}", tr(Msg::ParseFailed, &[&e]));
             Err(e)
         }
     }
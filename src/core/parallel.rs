@@ -0,0 +1,64 @@
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+/// 並列処理のスレッド数を決定（0の場合は使用できるCPU数）
+pub fn resolve_jobs(jobs: usize) -> usize {
+    if jobs > 0 {
+        return jobs;
+    }
+    thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+}
+
+/// 要素ごとの処理を最大`jobs`個のスレッドで実行し、結果を元の順に返す
+///
+/// 各スレッドは未処理の要素を順に取り出すため、処理時間に偏りがあっても負荷が分散される。
+/// スレッドが1つで足りる場合は呼び出し元のスレッドで実行する。
+/// 処理中のパニックは呼び出し元のスレッドで再開する。
+pub fn parallel_map<T, R, F>(items: &[T], jobs: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let jobs: usize = resolve_jobs(jobs).min(items.len());
+    if jobs <= 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next: AtomicUsize = AtomicUsize::new(0);
+    let mut slots: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    thread::scope(|scope| {
+        let handles: Vec<thread::ScopedJoinHandle<Vec<(usize, R)>>> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done: Vec<(usize, R)> = Vec::new();
+                    loop {
+                        let index: usize = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            break;
+                        };
+                        done.push((index, f(item)));
+                    }
+                    done
+                })
+            })
+            .collect();
+        for handle in handles {
+            match handle.join() {
+                Ok(done) => {
+                    for (index, result) in done {
+                        slots[index] = Some(result);
+                    }
+                }
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+    });
+    slots
+        .into_iter()
+        .map(|slot| slot.expect("全ての要素が処理されていません"))
+        .collect()
+}
@@ -0,0 +1,175 @@
+use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use super::diagnostic::Location;
+use super::file_parser::{normalize_key, swap_separators};
+use super::EntrySource;
+
+/// 実行中の値と設定値の違いの種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DriftKind {
+    /// 実行中の値が設定値と異なる
+    Mismatch { actual: String },
+    /// キーに対応するファイルがカーネルに存在しない
+    Missing,
+    /// 実行中の値を読み込めない（権限が無いなど）
+    Unreadable { error: String },
+}
+
+impl DriftKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DriftKind::Mismatch { .. } => "mismatch",
+            DriftKind::Missing => "missing",
+            DriftKind::Unreadable { .. } => "unreadable",
+        }
+    }
+}
+
+/// 設定値と実行中の値が一致しないキー
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyDrift {
+    pub key: String,
+    /// マージ後の設定値
+    pub expected: String,
+    pub kind: DriftKind,
+    /// 設定値の定義位置
+    pub source: Option<Location>,
+}
+
+/// キーに対応する`/proc/sys`配下のファイルのパス
+///
+/// systemd-sysctlと同じく、最初の区切り文字で形式を判定する。
+/// 最初が`.`の場合は`.`と`/`を入れ替える（`net.ipv4.conf.eth0/100.rp_filter`は
+/// `net/ipv4/conf/eth0.100/rp_filter`に対応する）。最初が`/`の場合はそのままパスとして扱う。
+/// `..`などの要素は無視し、`proc_root`の外を指さないようにする。
+pub fn sysctl_path(proc_root: &Path, key: &str) -> PathBuf {
+    let path: String = swap_separators(&normalize_key(key));
+    path.split('/')
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .fold(proc_root.to_path_buf(), |path, part| path.join(part))
+}
+
+/// 値を比較用に正規化（`/proc/sys`の複数の値はタブ区切りのため、空白をまとめる）
+pub fn normalize_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// キーの実行中の値を読み込む（正規化済み）
+pub fn read_runtime_value(proc_root: &Path, key: &str) -> io::Result<String> {
+    fs::read_to_string(sysctl_path(proc_root, key)).map(|value| normalize_value(&value))
+}
+
+/// マージ後の設定を`/proc/sys`の形式のディレクトリの値と比較し、違いをキーの順に返す
+pub fn check_drift(
+    values: &FxHashMap<String, String>,
+    sources: &BTreeMap<String, EntrySource>,
+    proc_root: &Path,
+) -> Vec<KeyDrift> {
+    let sorted: BTreeMap<&String, &String> = values.iter().collect();
+    sorted
+        .into_iter()
+        .filter_map(|(key, expected)| {
+            let kind: DriftKind = match read_runtime_value(proc_root, key) {
+                Ok(actual) if actual == normalize_value(expected) => return None,
+                Ok(actual) => DriftKind::Mismatch { actual },
+                Err(e) if e.kind() == io::ErrorKind::NotFound => DriftKind::Missing,
+                Err(e) => DriftKind::Unreadable {
+                    error: e.to_string(),
+                },
+            };
+            Some(KeyDrift {
+                key: key.clone(),
+                expected: expected.clone(),
+                kind,
+                source: sources.get(key).map(|source| source.location.clone()),
+            })
+        })
+        .collect()
+}
+
+/// キーの適用結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyStatus {
+    /// 書き込みに成功
+    Applied,
+    /// ドライラン（書き込みは行わない）
+    DryRun,
+    /// 書き込みに失敗したが、キーに`-`が付いているため無視
+    Ignored { error: String },
+    /// 書き込みに失敗
+    Failed { error: String },
+}
+
+impl ApplyStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplyStatus::Applied => "applied",
+            ApplyStatus::DryRun => "dry-run",
+            ApplyStatus::Ignored { .. } => "ignored",
+            ApplyStatus::Failed { .. } => "failed",
+        }
+    }
+}
+
+/// 1つのキーの適用結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyResult {
+    pub key: String,
+    pub value: String,
+    /// 書き込み先のファイル
+    pub path: PathBuf,
+    pub status: ApplyStatus,
+}
+
+/// マージ後の設定を`/proc/sys`の形式のディレクトリにキーの順に書き込む
+///
+/// 書き込み先のファイルは作成しない（カーネルに存在しないキーは失敗とする）。
+/// `-`が付いたキーの失敗は無視し、`dry_run`が有効な場合は書き込まずに結果のみを返す。
+pub fn apply_values(
+    values: &FxHashMap<String, String>,
+    sources: &BTreeMap<String, EntrySource>,
+    proc_root: &Path,
+    dry_run: bool,
+) -> Vec<ApplyResult> {
+    let sorted: BTreeMap<&String, &String> = values.iter().collect();
+    sorted
+        .into_iter()
+        .map(|(key, value)| {
+            let path: PathBuf = sysctl_path(proc_root, key);
+            let status: ApplyStatus = if dry_run {
+                ApplyStatus::DryRun
+            } else {
+                match write_runtime_value(&path, value) {
+                    Ok(()) => ApplyStatus::Applied,
+                    Err(e) if sources.get(key).is_some_and(|s| s.ignore_failure) => {
+                        ApplyStatus::Ignored {
+                            error: e.to_string(),
+                        }
+                    }
+                    Err(e) => ApplyStatus::Failed {
+                        error: e.to_string(),
+                    },
+                }
+            };
+            ApplyResult {
+                key: key.clone(),
+                value: value.clone(),
+                path,
+                status,
+            }
+        })
+        .collect()
+}
+
+/// 既存のファイルに値を書き込む
+fn write_runtime_value(path: &Path, value: &str) -> io::Result<()> {
+    let mut file: fs::File = fs::OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(path)?;
+    file.write_all(format!("{}\n", value).as_bytes())
+}
@@ -0,0 +1,86 @@
+use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
+use std::io;
+
+use super::file_parser::normalize_key;
+use super::{EntrySource, ParseFiles, ParseOptions, ParseReport};
+
+/// 問い合わせに一致したキーの有効な値
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch {
+    pub key: String,
+    /// 優先順位を適用した後の値
+    pub value: String,
+    /// 有効な値を設定したエントリ
+    pub source: Option<EntrySource>,
+    /// スキーマで定義された型
+    pub expected_type: Option<String>,
+}
+
+/// キーが問い合わせに一致するか
+///
+/// 問い合わせと同じキーと、その配下のキー（`net.ipv4`の場合は`net.ipv4.ip_forward`など）が一致する。
+/// `/proc/sys`と同様に`/`区切りも使用でき（最初の区切りが`/`の場合）、末尾の区切りは無視する。
+pub fn matches_query(key: &str, query: &str) -> bool {
+    matches_normalized(key, &normalize_query(query))
+}
+
+/// マージ後の設定から問い合わせに一致するキーを選択（キーの順）
+pub fn select_keys(
+    query: &str,
+    result_map: &FxHashMap<String, String>,
+    sources: &BTreeMap<String, EntrySource>,
+    schema: &FxHashMap<String, String>,
+) -> Vec<QueryMatch> {
+    let query: String = normalize_query(query);
+    let sorted: BTreeMap<&String, &String> = result_map
+        .iter()
+        .filter(|(key, _)| matches_normalized(key, &query))
+        .collect();
+    sorted
+        .into_iter()
+        .map(|(key, value)| QueryMatch {
+            key: key.clone(),
+            value: value.clone(),
+            source: sources.get(key).cloned(),
+            expected_type: schema.get(key).cloned(),
+        })
+        .collect()
+}
+
+/// ディレクトリをパースし、問い合わせに一致するキーの有効な値を返す
+///
+/// 有効な値はsystemd-sysctlと同じ優先順位（`collect_source_files`の順）でマージした結果となる。
+/// スキーマによる検証は行わない（`schema`は型の表示にのみ使用する）。
+/// パース時の警告と読み込みエラーは`ParseReport`の診断として返す。
+pub fn query_config(
+    query: &str,
+    directories: &[&str],
+    parser: &(impl ParseFiles + ?Sized),
+    schema: &FxHashMap<String, String>,
+    options: &ParseOptions,
+) -> io::Result<(Vec<QueryMatch>, ParseReport)> {
+    let options = ParseOptions {
+        verbose: false,
+        validate: false,
+        ..options.clone()
+    };
+    let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+    let report: ParseReport =
+        parser.parse_all_conf_files_with_options(directories, schema, &mut result_map, &options)?;
+    let matches: Vec<QueryMatch> = select_keys(query, &result_map, &report.sources, schema);
+    Ok((matches, report))
+}
+
+fn matches_normalized(key: &str, query: &str) -> bool {
+    !query.is_empty()
+        && (key == query
+            || key
+                .strip_prefix(query)
+                .is_some_and(|rest| rest.starts_with('.')))
+}
+
+/// 問い合わせを`.`区切りのキーの形式に変換（キーと同じく最初の区切り文字で形式を判定）
+fn normalize_query(query: &str) -> String {
+    normalize_key(query.trim()).trim_matches('.').to_string()
+}
@@ -1,52 +1,71 @@
 use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{self, BufRead, BufReader, Error};
 use std::path::Path;
 
+use super::diagnostic::{Diagnostic, DiagnosticCode, Location};
 use super::SchemaLoader;
+use crate::i18n::{tr, Msg};
 
 pub struct LoadSchema;
 
 impl SchemaLoader for LoadSchema {
     /// スキーマファイルを読み込み、キーと型のペアを返す
     fn load_schema(&self, file_path: &Path) -> io::Result<FxHashMap<String, String>> {
-        let file: fs::File = fs::File::open(file_path).map_err(|e: Error| {
-            eprintln!(
-                "Error: スキーマファイル '{}' を開く際にエラーが発生しました: {}",
-                file_path.display(),
-                e
-            );
-            e
+        let schema: FxHashMap<String, String> = read_schema_entries(file_path)?
+            .into_iter()
+            .map(|(key, value_type, _)| (key, value_type))
+            .collect();
+        Ok(schema)
+    }
+
+    /// スキーマファイルを読み込み、キーと補足説明（行末の`#`以降）のペアを返す
+    fn load_schema_notes(&self, file_path: &Path) -> io::Result<FxHashMap<String, String>> {
+        let notes: FxHashMap<String, String> = read_schema_entries(file_path)?
+            .into_iter()
+            .filter_map(|(key, _, note)| note.map(|note| (key, note)))
+            .collect();
+        Ok(notes)
+    }
+}
+
+/// スキーマファイルの各行をキー・型・補足説明に分解
+///
+/// `key -> int  # 0〜100 の範囲` のように、型の後ろの`#`以降は補足説明として扱う
+fn read_schema_entries(file_path: &Path) -> io::Result<Vec<(String, String, Option<String>)>> {
+    let file: fs::File = fs::File::open(file_path).inspect_err(|e: &Error| {
+        eprintln!("{}", tr(Msg::SchemaOpenFailed, &[&file_path.display(), &e]));
+    })?;
+    let reader: BufReader<File> = io::BufReader::new(file);
+    let mut entries: Vec<(String, String, Option<String>)> = Vec::new();
+
+    for line in reader.lines() {
+        let line: String = line.inspect_err(|e: &Error| {
+            eprintln!("{}", tr(Msg::SchemaReadFailed, &[&file_path.display(), &e]));
         })?;
-        let reader: BufReader<File> = io::BufReader::new(file);
-        let mut schema: FxHashMap<String, String> = FxHashMap::default();
-
-        for line in reader.lines() {
-            let line: String = line.map_err(|e: Error| {
-                eprintln!(
-                    "Error: スキーマファイル '{}' の読み込み中にエラーが発生しました: {}",
-                    file_path.display(),
-                    e
-                );
-                e
-            })?;
-            let trimmed: &str = line.trim();
-
-            // 空行やコメント行を無視
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-
-            // "->" で分割してキーと型を抽出
-            if let Some((key, value_type)) = trimmed.split_once("->") {
-                let key: String = key.trim().to_string();
-                let value_type: String = value_type.trim().to_string();
-                schema.insert(key, value_type);
-            }
+        let trimmed: &str = line.trim();
+
+        // 空行やコメント行を無視
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
         }
 
-        Ok(schema)
+        // "->" で分割してキーと型を抽出
+        if let Some((key, value_type)) = trimmed.split_once("->") {
+            let (value_type, note): (&str, Option<&str>) = match value_type.split_once('#') {
+                Some((value_type, note)) => (value_type, Some(note.trim())),
+                None => (value_type, None),
+            };
+            entries.push((
+                key.trim().to_string(),
+                value_type.trim().to_string(),
+                note.filter(|note| !note.is_empty()).map(str::to_string),
+            ));
+        }
     }
+
+    Ok(entries)
 }
 
 /// 数値かどうかを判定するヘルパー関数
@@ -56,34 +75,32 @@ fn is_numeric(value: &str) -> bool {
     re.is_match(value)
 }
 
+/// 値から最も具体的な型を推論（bool → int → float → string の順に判定）
+///
+/// 空の値は型を決められないため`None`を返す
+pub fn infer_type(value: &str) -> Option<&'static str> {
+    if value.is_empty() {
+        None
+    } else if is_bool(value) {
+        Some("bool")
+    } else if is_int(value) {
+        Some("int")
+    } else if is_numeric(value) {
+        Some("float")
+    } else {
+        Some("string")
+    }
+}
+
 /// 設定ファイルの内容をスキーマと照合して検証
 pub fn validate_against_schema(
     config_map: &FxHashMap<String, String>,
     schema: &FxHashMap<String, String>,
 ) -> Result<(), String> {
-    let mut errors: Vec<String> = Vec::new();
-
-    for (key, value) in config_map {
-        if let Some(expected_type) = schema.get(key) {
-            match expected_type.as_str() {
-                "string" => validate_string(key, value, expected_type, &mut errors),
-                "bool" => validate_bool(key, value, expected_type, &mut errors),
-                "int" => validate_int(key, value, expected_type, &mut errors),
-                "float" => validate_float(key, value, expected_type, &mut errors),
-                _ => {
-                    errors.push(format!(
-                        "\x1b[31mError: キー '{}' のスキーマ型 '{}' はサポートされていません。\x1b[0m",
-                        key, expected_type
-                    ));
-                }
-            }
-        } else {
-            errors.push(format!(
-                "\x1b[31mError: キー '{}' はスキーマに存在しません。\x1b[0m",
-                key
-            ));
-        }
-    }
+    let errors: Vec<String> = check_against_schema(config_map, schema, &FxHashMap::default())
+        .iter()
+        .map(|diagnostic| format!("Error: {}", diagnostic.message))
+        .collect();
 
     if errors.is_empty() {
         Ok(())
@@ -92,42 +109,89 @@ pub fn validate_against_schema(
     }
 }
 
-/// 文字列の検証
-fn validate_string(key: &str, value: &str, expected_type: &str, errors: &mut Vec<String>) {
-    if value.is_empty() || value == "true" || value == "false" || is_numeric(value) {
-        errors.push(format!(
-            "\x1b[31mError: キー '{}' の値 '{}' の型が一致しません。期待される型は '{}'\x1b[0m",
-            key, value, expected_type
-        ));
+/// 設定ファイルの内容をスキーマと照合し、問題を診断としてキーの順に返す
+///
+/// `locations`に定義位置があるキーは、診断にその位置を含める
+pub fn check_against_schema(
+    config_map: &FxHashMap<String, String>,
+    schema: &FxHashMap<String, String>,
+    locations: &FxHashMap<String, Location>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    // 結果が実行ごとに変わらないよう、キーの順に検証する
+    let sorted: BTreeMap<&String, &String> = config_map.iter().collect();
+    for (key, value) in sorted {
+        let location: Option<Location> = locations.get(key).cloned();
+        let diagnostic: Diagnostic = match schema.get(key) {
+            Some(expected_type) => match matches_type(value, expected_type) {
+                Some(true) => continue,
+                Some(false) => Diagnostic::new(
+                    DiagnosticCode::TypeMismatch,
+                    tr(Msg::TypeMismatch, &[key, value, expected_type]),
+                )
+                .with_expected(expected_type),
+                None => Diagnostic::new(
+                    DiagnosticCode::UnsupportedType,
+                    tr(Msg::UnsupportedType, &[key, expected_type]),
+                )
+                .with_expected(expected_type),
+            },
+            None => Diagnostic::new(DiagnosticCode::UnknownKey, tr(Msg::UnknownKey, &[key])),
+        };
+        diagnostics.push(
+            diagnostic
+                .with_key(key)
+                .with_value(value)
+                .with_location(location),
+        );
     }
+
+    diagnostics
 }
 
-/// ブール値の検証
-fn validate_bool(key: &str, value: &str, expected_type: &str, errors: &mut Vec<String>) {
-    if value != "true" && value != "false" {
-        errors.push(format!(
-            "\x1b[31mError: キー '{}' の値 '{}' の型が一致しません。期待される型は '{}'\x1b[0m",
-            key, value, expected_type
-        ));
+/// 配列の要素に使用できるスキーマ型
+const SCALAR_TYPES: [&str; 4] = ["string", "bool", "int", "float"];
+
+/// 値がスキーマ型に一致するかを判定（サポートされていない型は`None`）
+///
+/// `int[]`のような配列型は、空白区切りの全ての値が要素の型に一致するかを判定する
+fn matches_type(value: &str, expected_type: &str) -> Option<bool> {
+    if let Some(element_type) = expected_type.strip_suffix("[]") {
+        if !SCALAR_TYPES.contains(&element_type) {
+            return None;
+        }
+        let mut elements = value.split_whitespace().peekable();
+        return Some(
+            elements.peek().is_some()
+                && elements.all(|element| matches_type(element, element_type) == Some(true)),
+        );
+    }
+    match expected_type {
+        "string" => Some(is_string(value)),
+        "bool" => Some(is_bool(value)),
+        "int" => Some(is_int(value)),
+        "float" => Some(is_float(value)),
+        _ => None,
     }
 }
 
+/// 文字列の検証
+fn is_string(value: &str) -> bool {
+    !(value.is_empty() || value == "true" || value == "false" || is_numeric(value))
+}
+
+/// ブール値の検証
+fn is_bool(value: &str) -> bool {
+    value == "true" || value == "false"
+}
+
 /// 整数の検証
-fn validate_int(key: &str, value: &str, expected_type: &str, errors: &mut Vec<String>) {
-    if value.contains('.') || value.parse::<i64>().is_err() {
-        errors.push(format!(
-            "\x1b[31mError: キー '{}' の値 '{}' の型が一致しません。期待される型は '{}'\x1b[0m",
-            key, value, expected_type
-        ));
-    }
+fn is_int(value: &str) -> bool {
+    !value.contains('.') && value.parse::<i64>().is_ok()
 }
 
 /// 浮動小数点数の検証
-fn validate_float(key: &str, value: &str, expected_type: &str, errors: &mut Vec<String>) {
-    if value.parse::<f64>().is_err() {
-        errors.push(format!(
-            "\x1b[31mError: キー '{}' の値 '{}' の型が一致しません。期待される型は '{}'\x1b[0m",
-            key, value, expected_type
-        ));
-    }
+fn is_float(value: &str) -> bool {
+    value.parse::<f64>().is_ok()
 }
@@ -0,0 +1,184 @@
+use rustc_hash::FxHashMap;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::procfs::{apply_values, read_runtime_value, ApplyResult};
+use crate::i18n::{tr, Msg};
+
+/// スナップショットファイルの識別子（`format`フィールドの値）
+pub const SNAPSHOT_FORMAT: &str = "linux-conf-parser-snapshot";
+/// スナップショットファイルの形式のバージョン
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+/// 実行中の値のスナップショット
+///
+/// JSONとして保存し、`diff`の比較対象や`restore`で再適用する値として使用する
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// 作成日時（UTC、RFC 3339形式）
+    pub created_at: String,
+    /// 値を読み込んだ`/proc/sys`の形式のディレクトリ
+    pub proc_sys: String,
+    /// 読み込んだ値（キーの順）
+    pub values: BTreeMap<String, String>,
+    /// カーネルに存在しなかったキー
+    pub missing: Vec<String>,
+    /// 読み込めなかったキーとその理由
+    pub unreadable: BTreeMap<String, String>,
+}
+
+impl Snapshot {
+    /// JSONに変換
+    pub fn to_json(&self) -> String {
+        let value: Value = json!({
+            "format": SNAPSHOT_FORMAT,
+            "version": SNAPSHOT_VERSION,
+            "created_at": self.created_at,
+            "proc_sys": self.proc_sys,
+            "values": self.values,
+            "missing": self.missing,
+            "unreadable": self.unreadable,
+        });
+        let mut output: String = serde_json::to_string_pretty(&value).unwrap();
+        output.push('\n');
+        output
+    }
+
+    /// JSONから読み込む（スナップショットでない場合やバージョンが異なる場合はNone）
+    pub fn from_json(value: &Value) -> Option<Snapshot> {
+        let object: &Map<String, Value> = value.as_object()?;
+        if object.get("format")?.as_str()? != SNAPSHOT_FORMAT
+            || object.get("version")?.as_u64()? != SNAPSHOT_VERSION
+        {
+            return None;
+        }
+        let text = |key: &str| -> String {
+            object
+                .get(key)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+        let strings = |key: &str| -> BTreeMap<String, String> {
+            object
+                .get(key)
+                .and_then(Value::as_object)
+                .map(|map| {
+                    map.iter()
+                        .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Some(Snapshot {
+            created_at: text("created_at"),
+            proc_sys: text("proc_sys"),
+            values: strings("values"),
+            missing: object
+                .get("missing")
+                .and_then(Value::as_array)
+                .map(|keys| {
+                    keys.iter()
+                        .filter_map(|key| Some(key.as_str()?.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            unreadable: strings("unreadable"),
+        })
+    }
+
+    /// スナップショットファイルを読み込む
+    pub fn load(path: &Path) -> io::Result<Snapshot> {
+        let content: String = std::fs::read_to_string(path)?;
+        let value: Value = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Snapshot::from_json(&value).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                tr(Msg::SnapshotInvalid, &[&SNAPSHOT_FORMAT, &SNAPSHOT_VERSION]),
+            )
+        })
+    }
+}
+
+/// 指定したキーの実行中の値を`/proc/sys`の形式のディレクトリから読み込む
+pub fn capture_snapshot<'a>(
+    keys: impl IntoIterator<Item = &'a String>,
+    proc_root: &Path,
+) -> Snapshot {
+    let mut snapshot = Snapshot {
+        created_at: current_timestamp(),
+        proc_sys: proc_root.to_string_lossy().to_string(),
+        ..Snapshot::default()
+    };
+    for key in keys {
+        match read_runtime_value(proc_root, key) {
+            Ok(value) => {
+                snapshot.values.insert(key.clone(), value);
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => snapshot.missing.push(key.clone()),
+            Err(e) => {
+                snapshot.unreadable.insert(key.clone(), e.to_string());
+            }
+        }
+    }
+    snapshot.missing.sort();
+    snapshot
+}
+
+/// スナップショットの値を`/proc/sys`の形式のディレクトリに書き戻す
+///
+/// 作成時に存在しなかったキー・読み込めなかったキーは対象外
+pub fn restore_snapshot(snapshot: &Snapshot, proc_root: &Path, dry_run: bool) -> Vec<ApplyResult> {
+    let values: FxHashMap<String, String> = snapshot
+        .values
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    apply_values(&values, &BTreeMap::new(), proc_root, dry_run)
+}
+
+/// 現在の日時（UTC、RFC 3339形式）
+pub fn current_timestamp() -> String {
+    format_timestamp(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+    )
+}
+
+/// UNIX時間（秒）をRFC 3339形式のUTCの日時に変換
+pub fn format_timestamp(seconds: u64) -> String {
+    let days: i64 = (seconds / 86_400) as i64;
+    let time: u64 = seconds % 86_400;
+
+    // 1970-01-01からの日数をグレゴリオ暦の年月日に変換
+    let z: i64 = days + 719_468;
+    let era: i64 = z.div_euclid(146_097);
+    let day_of_era: i64 = z.rem_euclid(146_097);
+    let year_of_era: i64 =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year: i64 = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index: i64 = (5 * day_of_year + 2) / 153;
+    let day: i64 = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month: i64 = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year: i64 = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
@@ -0,0 +1,332 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+
+use super::file_parser::{normalize_key, ConfEntryRef};
+use crate::config::Config;
+use crate::i18n::{tr, Msg};
+
+/// 行末の改行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`
+    Lf,
+    /// `\r\n`
+    CrLf,
+    /// 改行なし（ファイルの最後の行）
+    None,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::None => "",
+        }
+    }
+}
+
+/// 1行の内容（改行を除く）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineKind {
+    /// 空行（空白のみの行を含む）
+    Blank(String),
+    /// `#`・`;`で始まるコメント行（先頭の空白を含む）
+    Comment(String),
+    /// `key = value`の行
+    Entry(EntryLine),
+    /// `=`を含まない行（パース時に警告となる）
+    Invalid(String),
+}
+
+/// `key = value`の行の構成要素
+///
+/// 各フィールドを順に連結すると元の行（改行を除く）と一致する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryLine {
+    /// 行頭の空白
+    pub indent: String,
+    /// 適用に失敗しても無視する指定（`-`と直後の空白）
+    pub ignore_prefix: Option<String>,
+    pub key: String,
+    /// キーと`=`の間の空白
+    pub before_separator: String,
+    /// `=`と値の間の空白
+    pub after_separator: String,
+    pub value: String,
+    /// 値の後ろの空白
+    pub trailing: String,
+}
+
+impl EntryLine {
+    /// キーに`-`が付いている（適用に失敗しても無視する）
+    pub fn ignore_failure(&self) -> bool {
+        self.ignore_prefix.is_some()
+    }
+
+    /// `.`区切り・`/`区切りのどちらで書かれていても同じキーか
+    fn has_key(&self, key: &str) -> bool {
+        normalize_key(&self.key) == normalize_key(key)
+    }
+
+    /// `line`を`=`で分割した行として分解
+    fn parse(line: &str) -> Option<EntryLine> {
+        let (raw_key, raw_value) = line.split_once('=')?;
+        let (indent, key_part, before_separator) = split_spaces(raw_key);
+        let (ignore_prefix, key): (Option<String>, &str) = match key_part.strip_prefix('-') {
+            Some(rest) => {
+                let key: &str = rest.trim_start();
+                let prefix: &str = &key_part[..key_part.len() - key.len()];
+                (Some(prefix.to_string()), key)
+            }
+            None => (None, key_part),
+        };
+        let (after_separator, value, trailing) = split_spaces(raw_value);
+        Some(EntryLine {
+            indent: indent.to_string(),
+            ignore_prefix,
+            key: key.to_string(),
+            before_separator: before_separator.to_string(),
+            after_separator: after_separator.to_string(),
+            value: value.to_string(),
+            trailing: trailing.to_string(),
+        })
+    }
+
+    /// 値の開始列（1始まり、文字単位）
+    fn value_column(&self) -> usize {
+        [
+            &self.indent,
+            self.ignore_prefix.as_deref().unwrap_or(""),
+            &self.key,
+            &self.before_separator,
+            "=",
+            &self.after_separator,
+        ]
+        .iter()
+        .map(|part| part.chars().count())
+        .sum::<usize>()
+            + 1
+    }
+}
+
+impl fmt::Display for EntryLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}={}{}{}",
+            self.indent,
+            self.ignore_prefix.as_deref().unwrap_or(""),
+            self.key,
+            self.before_separator,
+            self.after_separator,
+            self.value,
+            self.trailing
+        )
+    }
+}
+
+/// 1行の内容と改行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfLine {
+    pub kind: LineKind,
+    pub ending: LineEnding,
+}
+
+impl fmt::Display for ConfLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            LineKind::Blank(text) | LineKind::Comment(text) | LineKind::Invalid(text) => {
+                f.write_str(text)?
+            }
+            LineKind::Entry(entry) => entry.fmt(f)?,
+        }
+        f.write_str(self.ending.as_str())
+    }
+}
+
+/// 設定ファイルの具象構文木
+///
+/// コメント・空行・空白・改行（`\n`・`\r\n`・末尾の改行の有無）・`-`の指定・重複したキーを全て保持し、
+/// `to_string()`でパース前と同じ内容に戻せる。行の分類と各エントリのキー・値・位置は`parse_conf_str`と一致する。
+/// 編集は変更した行のみに反映され、他の行の書式はそのまま残る。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfDocument {
+    pub lines: Vec<ConfLine>,
+}
+
+impl ConfDocument {
+    /// 設定内容を行ごとに分解（失敗しない）
+    pub fn parse(input: &str) -> ConfDocument {
+        let lines: Vec<ConfLine> = input
+            .split_inclusive('\n')
+            .map(|line| {
+                let (text, ending): (&str, LineEnding) =
+                    if let Some(text) = line.strip_suffix("\r\n") {
+                        (text, LineEnding::CrLf)
+                    } else if let Some(text) = line.strip_suffix('\n') {
+                        (text, LineEnding::Lf)
+                    } else {
+                        (line, LineEnding::None)
+                    };
+                let trimmed: &str = text.trim();
+                let kind: LineKind = if trimmed.is_empty() {
+                    LineKind::Blank(text.to_string())
+                } else if trimmed.starts_with('#') || trimmed.starts_with(';') {
+                    LineKind::Comment(text.to_string())
+                } else if let Some(entry) = EntryLine::parse(text) {
+                    LineKind::Entry(entry)
+                } else {
+                    LineKind::Invalid(text.to_string())
+                };
+                ConfLine { kind, ending }
+            })
+            .collect();
+        ConfDocument { lines }
+    }
+
+    /// エントリ（出現順、行番号と列は`parse_conf_str`と同じ）
+    pub fn entries(&self) -> impl Iterator<Item = ConfEntryRef<'_>> {
+        self.lines.iter().enumerate().filter_map(|(index, line)| {
+            let LineKind::Entry(entry) = &line.kind else {
+                return None;
+            };
+            Some(ConfEntryRef {
+                key: normalize_key(&entry.key),
+                value: Cow::Borrowed(entry.value.as_str()),
+                line: index + 1,
+                column: entry.value_column(),
+                ignore_failure: entry.ignore_failure(),
+            })
+        })
+    }
+
+    /// キーの有効な値（重複している場合は最後の値）
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entry_lines()
+            .filter(|entry| entry.has_key(key))
+            .last()
+            .map(|entry| entry.value.as_str())
+    }
+
+    /// キーの値を設定
+    ///
+    /// 既存のキーは有効な値を設定している最後の行の値のみを置き換え、空白と`-`の指定はそのまま残す。
+    /// 存在しないキーは末尾に追加し、`=`の前後の空白と改行は既存の行に合わせる。
+    /// 改行を含むなど、パースし直すと同じ内容にならないキー・値はエラーとなる。
+    pub fn set(&mut self, key: &str, value: &str) -> io::Result<()> {
+        validate_key(key)?;
+        validate_value(key, value)?;
+
+        if let Some(entry) = self
+            .lines
+            .iter_mut()
+            .rev()
+            .find_map(|line| match &mut line.kind {
+                LineKind::Entry(entry) if entry.has_key(key) => Some(entry),
+                _ => None,
+            })
+        {
+            entry.value = value.to_string();
+            return Ok(());
+        }
+
+        let (before_separator, after_separator): (String, String) = self
+            .entry_lines()
+            .last()
+            .map(|entry| {
+                (
+                    entry.before_separator.clone(),
+                    entry.after_separator.clone(),
+                )
+            })
+            .unwrap_or_else(|| (" ".to_string(), " ".to_string()));
+        let ending: LineEnding = self.line_ending();
+        if let Some(last) = self.lines.last_mut() {
+            if last.ending == LineEnding::None {
+                last.ending = ending;
+            }
+        }
+        self.lines.push(ConfLine {
+            kind: LineKind::Entry(EntryLine {
+                indent: String::new(),
+                ignore_prefix: None,
+                key: key.to_string(),
+                before_separator,
+                after_separator,
+                value: value.to_string(),
+                trailing: String::new(),
+            }),
+            ending,
+        });
+        Ok(())
+    }
+
+    /// キーを設定している行を全て削除し、削除した行数を返す
+    pub fn remove(&mut self, key: &str) -> usize {
+        let before: usize = self.lines.len();
+        self.lines
+            .retain(|line| !matches!(&line.kind, LineKind::Entry(entry) if entry.has_key(key)));
+        before - self.lines.len()
+    }
+
+    fn entry_lines(&self) -> impl Iterator<Item = &EntryLine> {
+        self.lines.iter().filter_map(|line| match &line.kind {
+            LineKind::Entry(entry) => Some(entry),
+            _ => None,
+        })
+    }
+
+    /// ファイルで使用されている改行（最初の改行、無ければ`\n`）
+    fn line_ending(&self) -> LineEnding {
+        self.lines
+            .iter()
+            .map(|line| line.ending)
+            .find(|ending| *ending != LineEnding::None)
+            .unwrap_or(LineEnding::Lf)
+    }
+}
+
+impl fmt::Display for ConfDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.lines.iter().try_for_each(|line| line.fmt(f))
+    }
+}
+
+/// 文字列を先頭の空白・本体・末尾の空白に分割
+fn split_spaces(text: &str) -> (&str, &str, &str) {
+    let body: &str = text.trim();
+    let start: usize = text.len() - text.trim_start().len();
+    (&text[..start], body, &text[start + body.len()..])
+}
+
+/// 設定ファイルに記述できるキーか（空白・`=`を含まず、コメントや`-`の指定と区別できる）
+fn validate_key(key: &str) -> io::Result<()> {
+    let valid: bool = !key.is_empty()
+        && !key.starts_with(['#', ';', '-'])
+        && !key.contains(|c: char| c == '=' || c.is_whitespace());
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr(Msg::InvalidKeySyntax, &[&key.escape_debug()]),
+        ))
+    }
+}
+
+/// 設定ファイルに記述できる値か（改行・前後の空白を含まず、長さの上限以内）
+fn validate_value(key: &str, value: &str) -> io::Result<()> {
+    let valid: bool = value.trim() == value
+        && !value.contains(['\n', '\r'])
+        && value.len() <= Config::MAX_VALUE_LENGTH;
+    if valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            tr(Msg::InvalidValueSyntax, &[&key, &value.escape_debug()]),
+        ))
+    }
+}
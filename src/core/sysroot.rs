@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::i18n::{tr, Msg};
+
+/// シンボリックリンクを辿る回数の上限（Linuxの`MAXSYMLINKS`と同じ）
+const MAX_SYMLINK_FOLLOWS: usize = 40;
+
+/// ルートディレクトリ内でパスを解決
+///
+/// chrootと同様に、`path`は`root`を`/`とみなして解決される。
+/// 絶対パスを指すシンボリックリンクや`..`も`root`の外に出ることはない。
+/// 存在しない要素はそのまま結合する。
+pub fn resolve_in_root(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    resolve_links(root, path, |host_path| {
+        match fs::symlink_metadata(host_path) {
+            Ok(metadata) if metadata.file_type().is_symlink() => fs::read_link(host_path).map(Some),
+            _ => Ok(None),
+        }
+    })
+}
+
+/// `root`を`/`とみなしてパスを解決
+///
+/// `read_link`は`root`を結合したパスを受け取り、シンボリックリンクであればリンク先を返す。
+/// 実際のファイルシステム以外（メモリ上のファイルなど）のパスの解決にも使用する。
+pub(crate) fn resolve_links(
+    root: &Path,
+    path: &Path,
+    read_link: impl Fn(&Path) -> io::Result<Option<PathBuf>>,
+) -> io::Result<PathBuf> {
+    let mut resolved: PathBuf = PathBuf::new();
+    let mut pending: VecDeque<OsString> = path_parts(path);
+    let mut follows: usize = 0;
+
+    while let Some(part) = pending.pop_front() {
+        if part == ".." {
+            resolved.pop();
+            continue;
+        }
+
+        let candidate: PathBuf = resolved.join(&part);
+        let host_path: PathBuf = root.join(&candidate);
+        match read_link(&host_path)? {
+            Some(target) => {
+                follows += 1;
+                if follows > MAX_SYMLINK_FOLLOWS {
+                    return Err(io::Error::other(tr(
+                        Msg::SymlinkLoop,
+                        &[&host_path.display()],
+                    )));
+                }
+
+                // リンク先を未処理の要素の先頭に展開（絶対パスはルートから辿り直す）
+                if target.is_absolute() {
+                    resolved.clear();
+                }
+                for target_part in path_parts(&target).into_iter().rev() {
+                    pending.push_front(target_part);
+                }
+            }
+            None => resolved = candidate,
+        }
+    }
+
+    Ok(root.join(resolved))
+}
+
+/// パスを通常の要素と`..`に分解（ルートや`.`は取り除く）
+pub(crate) fn path_parts(path: &Path) -> VecDeque<OsString> {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_os_string()),
+            Component::ParentDir => Some(OsString::from("..")),
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => None,
+        })
+        .collect()
+}
@@ -0,0 +1,244 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::PathBuf;
+
+use crate::i18n::{tr, Msg};
+
+/// tarアーカイブのブロックサイズ
+const BLOCK_SIZE: usize = 512;
+
+/// tarアーカイブのエントリの種類
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TarEntryKind {
+    File,
+    Directory,
+    /// シンボリックリンク（リンク先）
+    Symlink(PathBuf),
+    /// ハードリンク（アーカイブ内のリンク先のパス）
+    HardLink(PathBuf),
+    /// デバイスファイル・FIFOなど（設定ファイルの探索では使用しない）
+    Other,
+}
+
+/// tarアーカイブのエントリ（内容はアーカイブの一部を参照する）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarEntry<'a> {
+    /// アーカイブ内のパス（`./`などはそのまま）
+    pub path: PathBuf,
+    pub kind: TarEntryKind,
+    pub data: &'a [u8],
+}
+
+/// エントリのヘッダ（長いパス名を適用したパスと内容の位置）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TarHeader {
+    pub path: PathBuf,
+    pub kind: TarEntryKind,
+    /// 内容の開始位置（アーカイブの先頭からのバイト数）
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// tarアーカイブ（ustar・GNU・PAX形式）のエントリをアーカイブ内の順に読み込む
+///
+/// GNUの長いパス名とPAXの`path`・`linkpath`に対応する。
+/// ヘッダのチェックサムが一致しない場合やアーカイブが途中で終わっている場合はエラーとなる。
+pub fn read_tar(data: &[u8]) -> io::Result<Vec<TarEntry<'_>>> {
+    let headers: Vec<TarHeader> = read_tar_headers(&mut io::Cursor::new(data))?;
+    Ok(headers
+        .into_iter()
+        .map(|header| {
+            // ファイル以外は内容を持たない（範囲は`read_tar_headers`で確認済み）
+            let data: &[u8] = match header.kind {
+                TarEntryKind::File => {
+                    &data[header.offset as usize..(header.offset + header.size) as usize]
+                }
+                _ => &[],
+            };
+            TarEntry {
+                path: header.path,
+                kind: header.kind,
+                data,
+            }
+        })
+        .collect())
+}
+
+/// シーク可能なストリームからtarアーカイブのエントリのヘッダを読み込む
+///
+/// エントリの内容は読み飛ばすため、大きなアーカイブ（コンテナイメージなど）も全体を読み込まずに扱える。
+/// エラーの条件は`read_tar`と同じ。
+pub fn read_tar_headers<R: Read + Seek>(reader: &mut R) -> io::Result<Vec<TarHeader>> {
+    let length: u64 = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut headers: Vec<TarHeader> = Vec::new();
+    let mut offset: u64 = 0;
+    // 次のエントリに適用する長いパス名（GNU形式・PAX形式）
+    let mut long_path: Option<PathBuf> = None;
+    let mut long_link: Option<PathBuf> = None;
+
+    while offset < length {
+        if length - offset < BLOCK_SIZE as u64 {
+            return Err(truncated());
+        }
+        let mut header: [u8; BLOCK_SIZE] = [0; BLOCK_SIZE];
+        reader.read_exact(&mut header)?;
+        // 空のブロックはアーカイブの終端
+        if header.iter().all(|byte| *byte == 0) {
+            break;
+        }
+        if !checksum_matches(&header) {
+            return Err(invalid_header(offset));
+        }
+
+        let size: u64 = parse_number(&header[124..136]).ok_or_else(|| invalid_header(offset))?;
+        let start: u64 = offset + BLOCK_SIZE as u64;
+        if length - start < size {
+            return Err(truncated());
+        }
+        let next: u64 = start + size.div_ceil(BLOCK_SIZE as u64) * BLOCK_SIZE as u64;
+
+        let kind: Option<TarEntryKind> = match header[156] {
+            // 次のエントリのパス名などを表すエントリは内容を読み込む
+            meta @ (b'L' | b'K' | b'x') => {
+                let mut body: Vec<u8> = vec![0; size as usize];
+                reader.read_exact(&mut body)?;
+                match meta {
+                    b'L' => long_path = Some(bytes_to_path(&body)),
+                    b'K' => long_link = Some(bytes_to_path(&body)),
+                    _ => {
+                        let records: Vec<(&str, &str)> =
+                            parse_pax_records(&body).ok_or_else(|| invalid_header(offset))?;
+                        for (key, value) in records {
+                            match key {
+                                "path" => long_path = Some(PathBuf::from(value)),
+                                "linkpath" => long_link = Some(PathBuf::from(value)),
+                                _ => {}
+                            }
+                        }
+                    }
+                }
+                None
+            }
+            // 全体に適用するPAXヘッダは使用しない
+            b'g' => None,
+            b'0' | b'\0' | b'7' => Some(TarEntryKind::File),
+            b'5' => Some(TarEntryKind::Directory),
+            link_type @ (b'1' | b'2') => {
+                let target: PathBuf = long_link
+                    .take()
+                    .unwrap_or_else(|| bytes_to_path(&header[157..257]));
+                if link_type == b'1' {
+                    Some(TarEntryKind::HardLink(target))
+                } else {
+                    Some(TarEntryKind::Symlink(target))
+                }
+            }
+            _ => Some(TarEntryKind::Other),
+        };
+        if let Some(kind) = kind {
+            headers.push(TarHeader {
+                path: long_path.take().unwrap_or_else(|| header_path(&header)),
+                kind,
+                offset: start,
+                size,
+            });
+            long_link = None;
+        }
+
+        reader.seek(SeekFrom::Start(next))?;
+        offset = next;
+    }
+
+    Ok(headers)
+}
+
+/// ヘッダの`name`と、ustar形式の場合は`prefix`を結合したパス
+fn header_path(header: &[u8]) -> PathBuf {
+    let name: PathBuf = bytes_to_path(&header[0..100]);
+    if &header[257..262] != b"ustar" {
+        return name;
+    }
+    let prefix: PathBuf = bytes_to_path(&header[345..500]);
+    if prefix.as_os_str().is_empty() {
+        name
+    } else {
+        prefix.join(name)
+    }
+}
+
+/// NULで終わるフィールドをパスに変換
+fn bytes_to_path(field: &[u8]) -> PathBuf {
+    let end: usize = field
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(field.len());
+    PathBuf::from(String::from_utf8_lossy(&field[..end]).into_owned())
+}
+
+/// 数値フィールドを読み込む（8進数の文字列、または先頭ビットが立っている場合は256進数）
+fn parse_number(field: &[u8]) -> Option<u64> {
+    let (first, rest) = field.split_first()?;
+    if first & 0x80 != 0 {
+        return rest
+            .iter()
+            .try_fold(u64::from(first & 0x7f), |number, byte| {
+                number.checked_mul(256)?.checked_add(u64::from(*byte))
+            });
+    }
+    let text: &str = std::str::from_utf8(field)
+        .ok()?
+        .trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(text, 8).ok()
+}
+
+/// ヘッダのチェックサム（チェックサムのフィールドを空白とみなした各バイトの和）が一致するか
+fn checksum_matches(header: &[u8]) -> bool {
+    let Some(expected) = parse_number(&header[148..156]) else {
+        return false;
+    };
+    let (unsigned, signed) = header
+        .iter()
+        .enumerate()
+        .map(|(index, byte)| {
+            if (148..156).contains(&index) {
+                b' '
+            } else {
+                *byte
+            }
+        })
+        .fold((0u64, 0i64), |(unsigned, signed), byte| {
+            (unsigned + u64::from(byte), signed + i64::from(byte as i8))
+        });
+    // 古い実装には符号付きで計算するものがある
+    expected == unsigned || i64::try_from(expected).is_ok_and(|expected| expected == signed)
+}
+
+/// PAXヘッダの内容（`<長さ> <キー>=<値>\n`の繰り返し）を読み込む
+fn parse_pax_records(body: &[u8]) -> Option<Vec<(&str, &str)>> {
+    let mut records: Vec<(&str, &str)> = Vec::new();
+    let mut rest: &[u8] = body;
+    while !rest.iter().all(|byte| *byte == 0) {
+        let space: usize = rest.iter().position(|byte| *byte == b' ')?;
+        let length: usize = std::str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record: &str = std::str::from_utf8(rest.get(space + 1..length)?).ok()?;
+        let (key, value) = record.strip_suffix('\n')?.split_once('=')?;
+        records.push((key, value));
+        rest = &rest[length..];
+    }
+    Some(records)
+}
+
+fn invalid_header(offset: u64) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        tr(Msg::TarInvalidHeader, &[&offset]),
+    )
+}
+
+fn truncated() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, tr(Msg::TarTruncated, &[]))
+}
@@ -0,0 +1,316 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::cache::FileStamp;
+use super::sysroot::{path_parts, resolve_in_root, resolve_links};
+use super::tar::{read_tar, TarEntry, TarEntryKind};
+
+/// パスの種類（シンボリックリンクは解決済み）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Directory,
+}
+
+/// 設定ファイルの読み込み元
+///
+/// ディレクトリの探索とファイルのパースはこのトレイトを通して行う。
+/// 実際のファイルシステム（`RealFs`）・メモリ上のファイル（`MemoryFs`）・tarアーカイブ（`TarFs`）を実装する。
+/// `resolve`以外のメソッドは`resolve`で解決したパスを受け取る。
+pub trait FileSource: Sync {
+    /// 探索するパスを読み込みに使用するパスに変換（シンボリックリンクを解決する）
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// パスの種類（存在しない・ファイルとディレクトリ以外の場合はNone）
+    fn kind(&self, path: &Path) -> Option<FileKind>;
+
+    /// ディレクトリ内のエントリ名（順不同）
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>>;
+
+    /// ファイルの内容（可能な場合は読み込み元を借用する）
+    fn read(&self, path: &Path) -> io::Result<Cow<'_, [u8]>>;
+
+    /// 変更の検出に使用する情報（キャッシュを使用しない読み込み元はNone）
+    fn stamp(&self, _path: &Path) -> Option<FileStamp> {
+        None
+    }
+
+    /// 解決したパスが`/dev/null`か（systemdのマスクの判定に使用）
+    fn is_dev_null(&self, path: &Path) -> bool {
+        self.resolve(Path::new("/dev/null"))
+            .is_ok_and(|dev_null| dev_null == path)
+    }
+}
+
+/// 実際のファイルシステム
+///
+/// `root`が指定された場合、パスとシンボリックリンクはその配下で解決される（`resolve_in_root`を参照）
+#[derive(Debug, Clone, Default)]
+pub struct RealFs {
+    pub root: Option<PathBuf>,
+}
+
+impl RealFs {
+    pub fn new(root: Option<PathBuf>) -> Self {
+        RealFs { root }
+    }
+}
+
+impl FileSource for RealFs {
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        match &self.root {
+            Some(root) => resolve_in_root(root, path),
+            None => Ok(path.to_path_buf()),
+        }
+    }
+
+    fn kind(&self, path: &Path) -> Option<FileKind> {
+        let metadata: fs::Metadata = fs::metadata(path).ok()?;
+        if metadata.is_file() {
+            Some(FileKind::File)
+        } else if metadata.is_dir() {
+            Some(FileKind::Directory)
+        } else {
+            None
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Cow<'_, [u8]>> {
+        fs::read(path).map(Cow::Owned)
+    }
+
+    fn stamp(&self, path: &Path) -> Option<FileStamp> {
+        FileStamp::of(path)
+    }
+
+    fn is_dev_null(&self, path: &Path) -> bool {
+        match &self.root {
+            Some(root) => {
+                resolve_in_root(root, Path::new("/dev/null")).is_ok_and(|dev_null| dev_null == path)
+            }
+            // ルートを指定しない場合、シンボリックリンクは解決されていない
+            None => fs::canonicalize(path).is_ok_and(|target| target == Path::new("/dev/null")),
+        }
+    }
+}
+
+/// メモリ上のファイルの内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    File(Vec<u8>),
+    Directory,
+    /// シンボリックリンク（リンク先）
+    Symlink(PathBuf),
+}
+
+/// メモリ上のファイルとディレクトリ
+///
+/// パスは`/`を基準とした絶対パスとして扱い、シンボリックリンクは`/`の外に出ることはない。
+/// ファイルを追加すると親ディレクトリも作成される。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryFs {
+    nodes: BTreeMap<PathBuf, Node>,
+}
+
+impl Default for MemoryFs {
+    fn default() -> Self {
+        MemoryFs::new()
+    }
+}
+
+impl MemoryFs {
+    /// ルートディレクトリのみの空の状態を作成
+    pub fn new() -> Self {
+        MemoryFs {
+            nodes: BTreeMap::from([(PathBuf::from("/"), Node::Directory)]),
+        }
+    }
+
+    /// ファイルを追加（既に存在するパスは置き換える）
+    pub fn add_file(&mut self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) {
+        self.insert(path.as_ref(), Node::File(content.into()));
+    }
+
+    /// ディレクトリを追加（既に存在するディレクトリの内容はそのまま）
+    pub fn add_dir(&mut self, path: impl AsRef<Path>) {
+        self.insert(path.as_ref(), Node::Directory);
+    }
+
+    /// シンボリックリンクを追加（既に存在するパスは置き換える）
+    pub fn add_symlink(&mut self, path: impl AsRef<Path>, target: impl AsRef<Path>) {
+        self.insert(path.as_ref(), Node::Symlink(target.as_ref().to_path_buf()));
+    }
+
+    /// パスと、ディレクトリの場合はその配下を削除（シンボリックリンクは辿らない）
+    pub fn remove(&mut self, path: impl AsRef<Path>) {
+        let path: PathBuf = normalize(path.as_ref());
+        if path == Path::new("/") {
+            *self = MemoryFs::new();
+            return;
+        }
+        self.nodes
+            .retain(|node_path, _| !node_path.starts_with(&path));
+    }
+
+    /// ディレクトリ内のエントリ名（シンボリックリンクは辿らない、名前順）
+    pub fn children(&self, path: impl AsRef<Path>) -> Vec<OsString> {
+        let path: PathBuf = normalize(path.as_ref());
+        self.nodes
+            .range(path.clone()..)
+            .skip(1)
+            .take_while(|(node_path, _)| node_path.starts_with(&path))
+            .filter(|(node_path, _)| node_path.parent() == Some(path.as_path()))
+            .filter_map(|(node_path, _)| node_path.file_name().map(|name| name.to_os_string()))
+            .collect()
+    }
+
+    /// tarアーカイブのエントリを順に追加（後のエントリが優先される）
+    pub fn extract_tar(&mut self, data: &[u8]) -> io::Result<()> {
+        for entry in read_tar(data)? {
+            self.add_tar_entry(entry);
+        }
+        Ok(())
+    }
+
+    /// tarアーカイブの1つのエントリを追加
+    ///
+    /// ハードリンクはリンク先のファイルの内容を複製する（リンク先が無い場合は無視する）
+    pub fn add_tar_entry(&mut self, entry: TarEntry) {
+        match entry.kind {
+            TarEntryKind::File => self.add_file(&entry.path, entry.data),
+            TarEntryKind::Directory => self.add_dir(&entry.path),
+            TarEntryKind::Symlink(target) => self.add_symlink(&entry.path, target),
+            TarEntryKind::HardLink(target) => {
+                if let Some(Node::File(content)) = self.nodes.get(&normalize(&target)) {
+                    let content: Vec<u8> = content.clone();
+                    self.add_file(&entry.path, content);
+                }
+            }
+            TarEntryKind::Other => {}
+        }
+    }
+
+    fn insert(&mut self, path: &Path, node: Node) {
+        let path: PathBuf = normalize(path);
+        for parent in path.ancestors().skip(1) {
+            self.nodes
+                .entry(parent.to_path_buf())
+                .or_insert(Node::Directory);
+        }
+        match node {
+            Node::Directory => {
+                if self.nodes.get(&path) != Some(&Node::Directory) {
+                    self.nodes.insert(path, node);
+                }
+            }
+            node => {
+                // ディレクトリを置き換える場合はその配下も削除
+                if self.nodes.get(&path) == Some(&Node::Directory) {
+                    self.remove(&path);
+                }
+                self.nodes.insert(path, node);
+            }
+        }
+    }
+}
+
+impl FileSource for MemoryFs {
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        resolve_links(Path::new("/"), path, |path| match self.nodes.get(path) {
+            Some(Node::Symlink(target)) => Ok(Some(target.clone())),
+            _ => Ok(None),
+        })
+    }
+
+    fn kind(&self, path: &Path) -> Option<FileKind> {
+        match self.nodes.get(path)? {
+            Node::File(_) => Some(FileKind::File),
+            Node::Directory => Some(FileKind::Directory),
+            Node::Symlink(_) => None,
+        }
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        match self.nodes.get(path) {
+            Some(Node::Directory) => Ok(self.children(path)),
+            Some(_) => Err(io::Error::from(io::ErrorKind::NotADirectory)),
+            None => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Cow<'_, [u8]>> {
+        match self.nodes.get(path) {
+            Some(Node::File(content)) => Ok(Cow::Borrowed(content)),
+            Some(_) => Err(io::Error::from(io::ErrorKind::IsADirectory)),
+            None => Err(io::Error::from(io::ErrorKind::NotFound)),
+        }
+    }
+}
+
+/// tarアーカイブ内のファイル（展開せずにメモリ上で参照する）
+///
+/// パスはアーカイブのルートを`/`とみなした絶対パスとなる
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TarFs {
+    files: MemoryFs,
+}
+
+impl TarFs {
+    /// tarアーカイブのファイルを読み込む
+    pub fn open(path: &Path) -> io::Result<TarFs> {
+        TarFs::from_bytes(&fs::read(path)?)
+    }
+
+    /// メモリ上のtarアーカイブを読み込む
+    pub fn from_bytes(data: &[u8]) -> io::Result<TarFs> {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.extract_tar(data)?;
+        Ok(TarFs { files })
+    }
+
+    /// アーカイブの内容
+    pub fn files(&self) -> &MemoryFs {
+        &self.files
+    }
+}
+
+impl FileSource for TarFs {
+    fn resolve(&self, path: &Path) -> io::Result<PathBuf> {
+        self.files.resolve(path)
+    }
+
+    fn kind(&self, path: &Path) -> Option<FileKind> {
+        self.files.kind(path)
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<OsString>> {
+        self.files.read_dir(path)
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Cow<'_, [u8]>> {
+        self.files.read(path)
+    }
+}
+
+/// パスを`/`を基準とした絶対パスに変換（`.`と`..`を取り除く、シンボリックリンクは辿らない）
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized: PathBuf = PathBuf::from("/");
+    for part in path_parts(path) {
+        if part == ".." {
+            normalized.pop();
+        } else {
+            normalized.push(part);
+        }
+    }
+    normalized
+}
@@ -0,0 +1,486 @@
+use rustc_hash::FxHashMap;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::diagnostic::{attach_notes, Diagnostic, DiagnosticCode, Location};
+use super::diff::{diff_configs, KeyChange, ResolvedConfig};
+use super::directory_parser::collect_conf_files;
+use super::file_parser::{parse_conf_entries, ConfEntry};
+use super::schema::{check_against_schema, LoadSchema};
+use super::sysroot::resolve_in_root;
+use super::SchemaLoader;
+use crate::i18n::{tr, Msg};
+
+/// ファイルの変更の検出に使用する情報（更新日時とサイズ）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Fingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn fingerprint(path: &Path) -> Option<Fingerprint> {
+    fs::metadata(path).ok().map(|metadata| Fingerprint {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+/// パース済みのファイル（変更されるまで再パースしない）
+#[derive(Debug, Clone)]
+struct ParsedFile {
+    fingerprint: Option<Fingerprint>,
+    entries: Vec<ConfEntry>,
+    /// ファイル内の警告・読み込みエラー
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ParsedFile {
+    fn parse(path: &Path) -> ParsedFile {
+        let fingerprint: Option<Fingerprint> = fingerprint(path);
+        let (entries, diagnostics) = parse_conf_entries(path).unwrap_or_else(|e| {
+            let message: String = tr(Msg::FileParseFailed, &[&path.display(), &e]);
+            (
+                Vec::new(),
+                vec![Diagnostic::new(DiagnosticCode::ReadError, message)],
+            )
+        });
+        ParsedFile {
+            fingerprint,
+            entries,
+            diagnostics,
+        }
+    }
+}
+
+/// 前回の状態からの変化
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WatchDelta {
+    /// 再パースしたファイル（追加・変更されたファイル）
+    pub parsed: Vec<PathBuf>,
+    /// 削除されたファイル
+    pub removed: Vec<PathBuf>,
+    /// 型定義ファイルを再読み込みしたか
+    pub schema_reloaded: bool,
+    /// 有効な値の変化（キーの順）
+    pub changes: Vec<KeyChange>,
+    /// 新たに見つかった診断
+    pub new_diagnostics: Vec<Diagnostic>,
+    /// 解消された診断
+    pub resolved_diagnostics: Vec<Diagnostic>,
+}
+
+impl WatchDelta {
+    pub fn is_empty(&self) -> bool {
+        self.parsed.is_empty()
+            && self.removed.is_empty()
+            && !self.schema_reloaded
+            && self.changes.is_empty()
+            && self.new_diagnostics.is_empty()
+            && self.resolved_diagnostics.is_empty()
+    }
+}
+
+/// 監視中の設定の状態
+///
+/// `refresh`のたびにディレクトリを探索し、更新日時またはサイズが変わったファイルのみを再パースする。
+/// スキーマ検証も値または定義位置が変わったキーのみを対象とする（型定義ファイルが変わった場合は全て）。
+pub struct WatchState {
+    directories: Vec<String>,
+    root: Option<PathBuf>,
+    schema_path: PathBuf,
+    schema_fingerprint: Option<Fingerprint>,
+    /// 型定義ファイル（読み込みに失敗した場合はそのエラーとなり、検証しない）
+    schema: Result<FxHashMap<String, String>, String>,
+    notes: FxHashMap<String, String>,
+    /// パース済みのファイル（探索順）
+    files: Vec<(PathBuf, ParsedFile)>,
+    config: ResolvedConfig,
+    /// 探索・型定義ファイルの読み込みエラー
+    read_errors: Vec<Diagnostic>,
+    /// キーごとのスキーマ検証の結果（問題が無い場合はNone）
+    schema_diagnostics: BTreeMap<String, Option<Diagnostic>>,
+    /// 前回の`refresh`時点の診断
+    diagnostics: Vec<Diagnostic>,
+    /// 最初の`refresh`を行ったか
+    refreshed: bool,
+}
+
+impl WatchState {
+    /// 監視する設定を指定して作成（最初の`refresh`で全てのファイルをパースする）
+    pub fn new(directories: &[&str], root: Option<&Path>, schema_path: &Path) -> WatchState {
+        WatchState {
+            directories: directories.iter().map(|dir| dir.to_string()).collect(),
+            root: root.map(Path::to_path_buf),
+            schema_path: schema_path.to_path_buf(),
+            schema_fingerprint: None,
+            schema: Ok(FxHashMap::default()),
+            notes: FxHashMap::default(),
+            files: Vec::new(),
+            config: ResolvedConfig::default(),
+            read_errors: Vec::new(),
+            schema_diagnostics: BTreeMap::new(),
+            diagnostics: Vec::new(),
+            refreshed: false,
+        }
+    }
+
+    /// マージ後の設定と各キーの定義位置
+    pub fn config(&self) -> &ResolvedConfig {
+        &self.config
+    }
+
+    /// 監視しているファイル数
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// 現在の診断（探索・ファイルの順、スキーマ検証はキーの順）
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// 変更を監視するディレクトリ（探索ディレクトリ・設定ファイルと型定義ファイルのディレクトリ）
+    pub fn watched_directories(&self) -> Vec<PathBuf> {
+        let mut directories: Vec<PathBuf> = self
+            .directories
+            .iter()
+            .filter_map(|dir| match &self.root {
+                Some(root) => resolve_in_root(root, Path::new(dir)).ok(),
+                None => Some(PathBuf::from(dir)),
+            })
+            .chain(
+                self.files
+                    .iter()
+                    .filter_map(|(path, _)| path.parent().map(Path::to_path_buf)),
+            )
+            .chain(self.schema_path.parent().map(|parent| {
+                if parent.as_os_str().is_empty() {
+                    PathBuf::from(".")
+                } else {
+                    parent.to_path_buf()
+                }
+            }))
+            .filter(|dir| dir.is_dir())
+            .collect();
+        directories.sort();
+        directories.dedup();
+        directories
+    }
+
+    /// ディレクトリを再探索し、変更されたファイルのみを再パース・再検証して変化を返す
+    pub fn refresh(&mut self) -> WatchDelta {
+        let mut delta: WatchDelta = WatchDelta::default();
+        let mut read_errors: Vec<Diagnostic> = Vec::new();
+
+        // 型定義ファイルが変わった場合は読み込み直し、全てのキーを再検証する
+        let schema_fingerprint: Option<Fingerprint> = fingerprint(&self.schema_path);
+        if !self.refreshed || schema_fingerprint != self.schema_fingerprint {
+            delta.schema_reloaded = self.refreshed;
+            self.schema_fingerprint = schema_fingerprint;
+            self.schema = LoadSchema
+                .load_schema(&self.schema_path)
+                .map_err(|e| tr(Msg::SchemaLoadFailed, &[&e]));
+            self.notes = LoadSchema
+                .load_schema_notes(&self.schema_path)
+                .unwrap_or_default();
+            self.schema_diagnostics.clear();
+        }
+        if let Err(message) = &self.schema {
+            read_errors.push(Diagnostic::new(DiagnosticCode::ReadError, message.clone()));
+        }
+
+        // 探索順にファイルを収集し、変更されていないファイルは前回の結果を使用
+        let directories: Vec<&str> = self.directories.iter().map(String::as_str).collect();
+        let mut errors: Vec<String> = Vec::new();
+        let paths: Vec<PathBuf> =
+            collect_conf_files(&directories, self.root.as_deref(), &mut errors);
+        read_errors.extend(
+            errors
+                .into_iter()
+                .map(|message| Diagnostic::new(DiagnosticCode::ReadError, message)),
+        );
+
+        let mut previous: FxHashMap<PathBuf, ParsedFile> = self.files.drain(..).collect();
+        for path in paths {
+            let file: ParsedFile = match previous.remove(&path) {
+                Some(file)
+                    if file.fingerprint.is_some() && file.fingerprint == fingerprint(&path) =>
+                {
+                    file
+                }
+                _ => {
+                    delta.parsed.push(path.clone());
+                    ParsedFile::parse(&path)
+                }
+            };
+            self.files.push((path, file));
+        }
+        delta.removed = previous.into_keys().collect();
+        delta.removed.sort();
+
+        // 探索順にマージし（後のファイルが優先）、前回の設定と比較
+        let mut config: ResolvedConfig = ResolvedConfig::default();
+        for (path, file) in &self.files {
+            for entry in &file.entries {
+                config
+                    .sources
+                    .insert(entry.key.clone(), entry.location(path));
+                config.values.insert(entry.key.clone(), entry.value.clone());
+            }
+        }
+        delta.changes = diff_configs(&self.config, &config);
+
+        // 値または定義位置が変わったキーのみを再検証
+        if let Ok(schema) = &self.schema {
+            let stale: Vec<String> = config
+                .values
+                .keys()
+                .chain(self.config.values.keys())
+                .filter(|key| {
+                    !self.schema_diagnostics.contains_key(*key)
+                        || self.config.values.get(*key) != config.values.get(*key)
+                        || self.config.sources.get(*key) != config.sources.get(*key)
+                })
+                .cloned()
+                .collect();
+            for key in stale {
+                self.schema_diagnostics.remove(&key);
+                let Some(value) = config.values.get(&key) else {
+                    continue;
+                };
+                let values: FxHashMap<String, String> =
+                    FxHashMap::from_iter([(key.clone(), value.clone())]);
+                let locations: FxHashMap<String, Location> = config
+                    .sources
+                    .get(&key)
+                    .map(|location| (key.clone(), location.clone()))
+                    .into_iter()
+                    .collect();
+                let mut diagnostics: Vec<Diagnostic> =
+                    check_against_schema(&values, schema, &locations);
+                attach_notes(&mut diagnostics, &self.notes);
+                self.schema_diagnostics.insert(key, diagnostics.pop());
+            }
+        }
+        self.config = config;
+        self.read_errors = read_errors;
+
+        // 前回の診断と比較
+        let diagnostics: Vec<Diagnostic> = self
+            .read_errors
+            .iter()
+            .chain(self.files.iter().flat_map(|(_, file)| &file.diagnostics))
+            .chain(self.schema_diagnostics.values().flatten())
+            .cloned()
+            .collect();
+        delta.new_diagnostics = diagnostics
+            .iter()
+            .filter(|diagnostic| !self.diagnostics.contains(diagnostic))
+            .cloned()
+            .collect();
+        delta.resolved_diagnostics = self
+            .diagnostics
+            .iter()
+            .filter(|diagnostic| !diagnostics.contains(diagnostic))
+            .cloned()
+            .collect();
+        self.diagnostics = diagnostics;
+        self.refreshed = true;
+
+        delta
+    }
+}
+
+/// ディレクトリの変更を待機する（Linuxではinotify、それ以外は一定時間ごとのポーリング）
+pub struct DirectoryWatcher {
+    #[cfg(target_os = "linux")]
+    inotify: Option<inotify::Inotify>,
+}
+
+impl Default for DirectoryWatcher {
+    fn default() -> Self {
+        DirectoryWatcher::new()
+    }
+}
+
+impl DirectoryWatcher {
+    /// inotifyを使用できない場合はポーリングとなる
+    pub fn new() -> DirectoryWatcher {
+        DirectoryWatcher {
+            #[cfg(target_os = "linux")]
+            inotify: inotify::Inotify::new().ok(),
+        }
+    }
+
+    /// ディレクトリを監視対象に追加（監視済みのディレクトリ・存在しないディレクトリは無視）
+    pub fn watch(&mut self, directories: &[PathBuf]) {
+        #[cfg(target_os = "linux")]
+        if let Some(inotify) = &mut self.inotify {
+            for directory in directories {
+                let _ = inotify.add_watch(directory);
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = directories;
+    }
+
+    /// 変更を最大`timeout`まで待機し、変更があった場合はtrueを返す
+    ///
+    /// ポーリングの場合は常に`timeout`だけ待機してtrueを返す（変更の有無は`WatchState::refresh`で判定）
+    pub fn wait(&mut self, timeout: Duration) -> bool {
+        #[cfg(target_os = "linux")]
+        if let Some(inotify) = &mut self.inotify {
+            return inotify.wait(timeout).unwrap_or(true);
+        }
+        std::thread::sleep(timeout);
+        true
+    }
+}
+
+/// inotifyのシステムコールの最小限のラッパー
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::collections::HashMap;
+    use std::ffi::CString;
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::raw::{c_char, c_int, c_short, c_ulong};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    const IN_NONBLOCK: c_int = 0o4000;
+    const IN_CLOEXEC: c_int = 0o2000000;
+    const IN_MODIFY: u32 = 0x2;
+    const IN_ATTRIB: u32 = 0x4;
+    const IN_CLOSE_WRITE: u32 = 0x8;
+    const IN_MOVED_FROM: u32 = 0x40;
+    const IN_MOVED_TO: u32 = 0x80;
+    const IN_CREATE: u32 = 0x100;
+    const IN_DELETE: u32 = 0x200;
+    const IN_DELETE_SELF: u32 = 0x400;
+    const IN_MOVE_SELF: u32 = 0x800;
+    const IN_IGNORED: u32 = 0x8000;
+    /// `struct inotify_event`の名前を除いた大きさ（wd・mask・cookie・len）
+    const EVENT_HEADER_SIZE: usize = 16;
+    const POLLIN: c_short = 0x1;
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: c_short,
+        revents: c_short,
+    }
+
+    extern "C" {
+        fn inotify_init1(flags: c_int) -> c_int;
+        fn inotify_add_watch(fd: c_int, pathname: *const c_char, mask: u32) -> c_int;
+        fn inotify_rm_watch(fd: c_int, wd: c_int) -> c_int;
+        fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+    }
+
+    pub struct Inotify {
+        file: File,
+        /// 監視済みのディレクトリと監視記述子
+        watched: HashMap<PathBuf, c_int>,
+    }
+
+    impl Inotify {
+        pub fn new() -> io::Result<Inotify> {
+            // SAFETY: 引数はフラグのみで、戻り値のファイルディスクリプタは以降Fileが所有する
+            let fd: c_int = unsafe { inotify_init1(IN_NONBLOCK | IN_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(Inotify {
+                // SAFETY: fdは直前に作成した有効なファイルディスクリプタで、他から参照されない
+                file: unsafe { File::from_raw_fd(fd) },
+                watched: HashMap::new(),
+            })
+        }
+
+        pub fn add_watch(&mut self, directory: &Path) -> io::Result<()> {
+            if self.watched.contains_key(directory) {
+                return Ok(());
+            }
+            let path: CString = CString::new(directory.as_os_str().as_bytes())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let mask: u32 = IN_MODIFY
+                | IN_ATTRIB
+                | IN_CLOSE_WRITE
+                | IN_MOVED_FROM
+                | IN_MOVED_TO
+                | IN_CREATE
+                | IN_DELETE
+                | IN_DELETE_SELF
+                | IN_MOVE_SELF;
+            // SAFETY: pathはNUL終端された文字列で、呼び出し中は有効
+            let wd: c_int =
+                unsafe { inotify_add_watch(self.file.as_raw_fd(), path.as_ptr(), mask) };
+            if wd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            self.watched.insert(directory.to_path_buf(), wd);
+            Ok(())
+        }
+
+        /// イベントを最大`timeout`まで待機し、届いたイベントを全て読み捨てる
+        pub fn wait(&mut self, timeout: Duration) -> io::Result<bool> {
+            let mut fds: PollFd = PollFd {
+                fd: self.file.as_raw_fd(),
+                events: POLLIN,
+                revents: 0,
+            };
+            let timeout: c_int = timeout.as_millis().min(c_int::MAX as u128) as c_int;
+            // SAFETY: fdsは呼び出し中有効な1要素の配列
+            let ready: c_int = unsafe { poll(&mut fds, 1, timeout) };
+            if ready < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            if ready == 0 {
+                return Ok(false);
+            }
+
+            let mut buffer: [u8; 4096] = [0; 4096];
+            loop {
+                match self.file.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(read) => self.handle_events(&buffer[..read]),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(true)
+        }
+
+        /// ディレクトリが削除・移動された場合は監視が外れるため、次の`add_watch`で再登録されるようにする
+        ///
+        /// 同じパスにディレクトリが作り直されても、再登録するまで新しいディレクトリのイベントは届かない
+        fn handle_events(&mut self, mut events: &[u8]) {
+            while events.len() >= EVENT_HEADER_SIZE {
+                let field = |offset: usize| -> [u8; 4] {
+                    events[offset..offset + 4]
+                        .try_into()
+                        .expect("4バイトの範囲")
+                };
+                let wd: c_int = c_int::from_ne_bytes(field(0));
+                let mask: u32 = u32::from_ne_bytes(field(4));
+                let len: usize = u32::from_ne_bytes(field(12)) as usize;
+                events = events.get(EVENT_HEADER_SIZE + len..).unwrap_or_default();
+
+                if mask & (IN_DELETE_SELF | IN_MOVE_SELF | IN_IGNORED) == 0 {
+                    continue;
+                }
+                if mask & IN_MOVE_SELF != 0 {
+                    // 移動先のディレクトリは監視しない
+                    // SAFETY: 引数は整数のみ（既に外れた監視記述子の場合はエラーとなるだけ）
+                    unsafe { inotify_rm_watch(self.file.as_raw_fd(), wd) };
+                }
+                self.watched.retain(|_, watched| *watched != wd);
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use std::io;
+use std::process::ExitCode;
+
+use crate::core::ParseReport;
+
+/// プロセスの終了コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// 成功（警告のみの場合も、`--fail-on-warnings`未指定なら成功）
+    Success = 0,
+    /// スキーマ検証に失敗
+    ValidationFailed = 1,
+    /// コマンドライン引数が不正
+    Usage = 2,
+    /// 警告のみ（`--fail-on-warnings`指定時）
+    Warnings = 3,
+    /// ファイルやディレクトリの読み書きに失敗
+    Io = 4,
+}
+
+impl ExitStatus {
+    /// パース結果から終了コードを決定
+    ///
+    /// 読み込みエラーは検証エラーより優先する
+    pub fn from_result(result: &io::Result<ParseReport>, fail_on_warnings: bool) -> ExitStatus {
+        match result {
+            Err(_) => ExitStatus::Io,
+            Ok(report) if report.has_read_errors() => ExitStatus::Io,
+            Ok(report) if report.has_errors() => ExitStatus::ValidationFailed,
+            Ok(report) if fail_on_warnings && report.warning_count() > 0 => ExitStatus::Warnings,
+            Ok(_) => ExitStatus::Success,
+        }
+    }
+}
+
+impl From<ExitStatus> for ExitCode {
+    fn from(status: ExitStatus) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
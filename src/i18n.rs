@@ -0,0 +1,522 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// メッセージの言語
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Ja,
+    En,
+}
+
+/// 現在の言語（ライブラリとしてのデフォルトは日本語）
+static CURRENT_LANG: AtomicU8 = AtomicU8::new(Lang::Ja as u8);
+
+impl Lang {
+    /// `ja`・`en`や`ja_JP.UTF-8`・`en_US.UTF-8`のようなロケール名から言語を判定
+    pub fn parse(value: &str) -> Option<Lang> {
+        let language: &str = value
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or("")
+            .trim();
+        match language.to_ascii_lowercase().as_str() {
+            "ja" => Some(Lang::Ja),
+            "en" | "c" | "posix" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Lang::Ja => "ja",
+            Lang::En => "en",
+        }
+    }
+
+    /// 環境変数（`LC_ALL` → `LC_MESSAGES` → `LANG`）から言語を判定
+    ///
+    /// 最初に設定されている変数を使い、日本語以外や未設定の場合は英語とする
+    pub fn from_env() -> Lang {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value))
+            .unwrap_or(Lang::En)
+    }
+}
+
+/// メッセージの言語を設定
+pub fn set_lang(lang: Lang) {
+    CURRENT_LANG.store(lang as u8, Ordering::Relaxed);
+}
+
+/// 現在のメッセージの言語
+pub fn lang() -> Lang {
+    if CURRENT_LANG.load(Ordering::Relaxed) == Lang::En as u8 {
+        Lang::En
+    } else {
+        Lang::Ja
+    }
+}
+
+/// メッセージID（診断コードと異なり、文言は言語ごとに変わる）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // コマンドライン
+    Usage,
+    OptionRequiresValue,
+    UnsupportedFormat,
+    UnsupportedLang,
+    UnknownOption,
+    InvalidJobs,
+    ImageNotAvailable,
+    ImageRootConflict,
+    FormatNotAvailable,
+    ExplainRequiresKey,
+    GetRequiresKey,
+    DiffRequiresPaths,
+    RestoreRequiresPath,
+    SetRequiresKeyValue,
+    UnsetRequiresKey,
+    FileOptionNotAvailable,
+    UnknownCommand,
+    // パース・検証
+    ConfigHasErrors,
+    SchemaLoadFailed,
+    AllFilesValid,
+    ParseFailed,
+    FileOpenFailed,
+    FileReadFailed,
+    InvalidUtf8,
+    ValueTooLong,
+    EnvNameCollision,
+    DuplicateKey,
+    MissingSeparator,
+    SchemaOpenFailed,
+    SchemaReadFailed,
+    TypeMismatch,
+    UnsupportedType,
+    UnknownKey,
+    FileParseFailed,
+    DirectoryParseFailed,
+    DirectoryNotFound,
+    DirectoryReadFailed,
+    SymlinkLoop,
+    TarInvalidHeader,
+    TarTruncated,
+    GzipInvalid,
+    GzipTooLarge,
+    ImageLoadFailed,
+    ImageManifestMissing,
+    ImageManifestInvalid,
+    ImageBlobMissing,
+    ImageLayerUnsupported,
+    // 設定ファイルの編集
+    InvalidKeySyntax,
+    InvalidValueSyntax,
+    CacheSaveFailed,
+    // 診断コードの説明
+    DescTypeMismatch,
+    DescUnsupportedType,
+    DescUnknownKey,
+    DescReadError,
+    DescMissingSeparator,
+    DescDuplicateKey,
+    DescValueTooLong,
+    DescEnvNameCollision,
+    // 出力
+    ExpectedType,
+    WritingSkeleton,
+    SkeletonCreated,
+    FileCreateFailed,
+    MergedWritten,
+    DriftMismatch,
+    DriftMissing,
+    DriftUnreadable,
+    ApplyApplied,
+    ApplyDryRun,
+    ApplyIgnored,
+    ApplyFailed,
+    SnapshotWritten,
+    ValueSet,
+    ValueAdded,
+    ValueUnchanged,
+    ValueUnset,
+    ValueNotSet,
+    ConfigWriteFailed,
+    ValueShadowed,
+    EditTargetNotSearched,
+    VendorFileNotEditable,
+    ValueStillSet,
+    QueryNoMatch,
+    QueryType,
+    QueryTypeUndefined,
+    SnapshotLoadFailed,
+    SnapshotInvalid,
+    WatchStarted,
+    WatchParsed,
+    WatchRemoved,
+    WatchSchemaReloaded,
+    WatchResolved,
+    ExplainKey,
+    ExplainValue,
+    ExplainValueUnset,
+    ExplainType,
+    ExplainTypeUndefined,
+    ExplainFailed,
+}
+
+/// メッセージを現在の言語で取得し、`{0}`・`{1}`…を引数で置き換える
+pub fn tr(msg: Msg, args: &[&dyn Display]) -> String {
+    let template: &str = match lang() {
+        Lang::Ja => ja(msg),
+        Lang::En => en(msg),
+    };
+
+    let mut output: String = String::with_capacity(template.len());
+    let mut rest: &str = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after: &str = &rest[start + 1..];
+        let index: Option<usize> = after
+            .find('}')
+            .and_then(|end| after[..end].parse::<usize>().ok().map(|i| (i, end)))
+            .and_then(|(i, end)| args.get(i).map(|arg| (arg, end)))
+            .map(|(arg, end)| {
+                output.push_str(&arg.to_string());
+                end
+            });
+        match index {
+            Some(end) => rest = &after[end + 1..],
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// 日本語のメッセージカタログ
+fn ja(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Usage => "\
+使い方: linux-conf-parser [オプション] [コマンド]
+
+コマンド:
+  check                  .confファイルをパースし、スキーマに基づいて検証（デフォルト）
+  dump                   マージ後の設定を出力
+  merge [出力先]          マージ後の設定を正規化したsysctl.confとして出力（デフォルト: 標準出力）
+  schema init [出力先]    型定義ファイルの雛形を作成（デフォルト: output.txt）
+  explain <キー>          キーを設定しているファイル・有効な値・スキーマ型を表示
+  get <キー>              キーまたはその配下のキー（例: net.ipv4）の有効な値を表示（別名: query）
+  drift                  マージ後の設定と実行中の値（/proc/sys）を比較
+  apply                  検証に成功した場合、マージ後の設定を /proc/sys に書き込む
+  snapshot [出力先]       設定されたキーの実行中の値をスナップショット（JSON）として保存（デフォルト: 標準出力）
+  restore <スナップショット> スナップショットの値を /proc/sys に書き戻す
+  watch                  ディレクトリを監視し、変更されたファイルを再検証して診断と値の変化を表示
+  set <キー> <値>         値をスキーマで検証し、有効な値を設定しているファイル（無ければ 99-local.conf）に書き込む
+  unset <キー>            キーを設定している行を全てのファイル（--file 指定時はそのファイル）から削除
+  diff <旧> <新>          2つのルート（ディレクトリ）または出力結果（JSON・key = value形式）の設定を比較
+  help                   この使い方を表示
+
+オプション:
+  --schema <パス>         型定義ファイル（デフォルト: schema.txt）
+  --root <パス>           探索ディレクトリをこのディレクトリ配下で解決（chroot・展開済みイメージなど）
+  --image <パス>          コンテナイメージ（docker save のtarball・OCIイメージレイアウト）のレイヤーを適用した
+                         ファイルシステムで探索（check・dump・merge・schema init）
+  --dir <パス>            探索するディレクトリ（複数指定可、先に指定したものが優先、デフォルト: 本番想定のsysctl.d）
+  --file <パス>           set・unset で編集するファイル（--root 指定時はその配下で解決）
+  --format <形式>         出力形式
+                           check:   json（診断をJSON Lines形式で出力）, sarif（SARIF 2.1.0）
+                           dump:    json（デフォルト）, flat, yaml, toml, env
+                           explain, get: json
+                           diff:    json, unified
+                           drift:   json
+                           apply, restore: json
+                           watch:   json（変化を1行1オブジェクトで出力）
+  --proc-sys <パス>       実行中の値を読み書きするディレクトリ（デフォルト: /proc/sys）
+  --dry-run              apply・restore で書き込まずに結果のみを表示
+  --cache <パス>          ファイルごとのパース結果をキャッシュし、変更されていないファイルのパースを省略
+  -j, --jobs <数>         ファイルの探索・パースを並列に行うスレッド数（デフォルト: 0 = CPU数）
+  --lang <言語>           メッセージの言語: ja, en（デフォルト: LC_ALL / LC_MESSAGES / LANG から判定）
+  -q, --quiet            ファイルごとのパース結果や進捗メッセージを表示しない
+  --fail-on-warnings     警告のみの場合も終了コード3で終了する
+  --annotate             merge で各キーの定義元のファイルと行番号をコメントとして出力
+  --show-source          get で有効な値を設定したファイルと行番号を表示
+  --show-type            get でスキーマの型を表示
+  -h, --help             この使い方を表示
+
+終了コード:
+  0  成功
+  1  スキーマ検証エラー（diff・drift では差分あり）
+  2  コマンドライン引数の誤り
+  3  警告のみ（--fail-on-warnings 指定時）
+  4  ファイル・ディレクトリの読み書きエラー（apply・restore では書き込みの失敗）",
+        Msg::OptionRequiresValue => "オプション '{0}' には値が必要です。",
+        Msg::UnsupportedFormat => "出力形式 '{0}' はサポートされていません。",
+        Msg::UnsupportedLang => "言語 '{0}' はサポートされていません（ja または en を指定して下さい）。",
+        Msg::UnknownOption => "不明なオプション '{0}' が指定されました。",
+        Msg::InvalidJobs => "スレッド数 '{0}' は0以上の整数で指定して下さい。",
+        Msg::ImageNotAvailable => "--image はこのコマンドでは使用できません。",
+        Msg::ImageRootConflict => "--image と --root は同時に指定できません。",
+        Msg::FormatNotAvailable => "出力形式 '{0}' はこのコマンドでは使用できません。",
+        Msg::ExplainRequiresKey => "explain にはキーを指定して下さい。",
+        Msg::GetRequiresKey => "get にはキーを指定して下さい。",
+        Msg::DiffRequiresPaths => "diff には比較する2つのパスを指定して下さい。",
+        Msg::RestoreRequiresPath => "restore にはスナップショットファイルを指定して下さい。",
+        Msg::SetRequiresKeyValue => "set にはキーと値を指定して下さい。",
+        Msg::UnsetRequiresKey => "unset にはキーを指定して下さい。",
+        Msg::FileOptionNotAvailable => "--file は set・unset でのみ使用できます。",
+        Msg::UnknownCommand => "不明なコマンドまたは余分な引数 '{0}' が指定されました。",
+        Msg::ConfigHasErrors => "設定ファイルにエラーがあります。",
+        Msg::SchemaLoadFailed => "スキーマファイルの読み込みに失敗しました: {0}",
+        Msg::AllFilesValid => "全てのファイルが正常にパースされ、スキーマに従っています。",
+        Msg::ParseFailed => "設定ファイルのパース中にエラーが発生しました: {0}",
+        Msg::FileOpenFailed => "Error: ファイル '{0}' を開く際にエラーが発生しました: {1}",
+        Msg::FileReadFailed => "Error: ファイル '{0}' の読み込み中にエラーが発生しました: {1}",
+        Msg::InvalidUtf8 => "'{0}' の {1} 行目がUTF-8として正しくありません",
+        Msg::ValueTooLong => "ファイル '{0}' の {1} 行目でキー '{2}' の値が{3}文字を超えています。この行は無視されます。👀",
+        Msg::EnvNameCollision => "キー '{1}' はキー '{2}' と同じ環境変数名 {0} になるため出力されません。",
+        Msg::DuplicateKey => "ファイル '{0}' の {1} 行目でキー '{2}' が重複しています（最初の定義は {3} 行目）。後の値が使用されます。",
+        Msg::MissingSeparator => "ファイル '{0}' の {1} 行目は '=' を含まないため無視されました: {2}",
+        Msg::SchemaOpenFailed => "Error: スキーマファイル '{0}' を開く際にエラーが発生しました: {1}",
+        Msg::SchemaReadFailed => "Error: スキーマファイル '{0}' の読み込み中にエラーが発生しました: {1}",
+        Msg::TypeMismatch => "キー '{0}' の値 '{1}' の型が一致しません。期待される型は '{2}'",
+        Msg::UnsupportedType => "キー '{0}' のスキーマ型 '{1}' はサポートされていません。",
+        Msg::UnknownKey => "キー '{0}' はスキーマに存在しません。",
+        Msg::FileParseFailed => "ファイル '{0}' のパースに失敗しました: {1}",
+        Msg::DirectoryParseFailed => "ディレクトリ '{0}' のパースに失敗しました: {1}",
+        Msg::DirectoryNotFound => "Error: 指定されたディレクトリ '{0}' が存在しません。",
+        Msg::DirectoryReadFailed => "Error: ディレクトリ '{0}' の読み込みに失敗しました: {1}",
+        Msg::SymlinkLoop => "シンボリックリンクの階層が深すぎます: {0}",
+        Msg::TarInvalidHeader => "tarアーカイブの {0} バイト目のヘッダが不正です",
+        Msg::TarTruncated => "tarアーカイブが途中で終わっています",
+        Msg::GzipInvalid => "gzip形式のデータが不正です",
+        Msg::GzipTooLarge => "gzip形式のデータを展開した大きさが上限（{0}バイト）を超えています",
+        Msg::ImageLoadFailed => "Error: イメージ {0} を読み込めません: {1}",
+        Msg::ImageManifestMissing => "イメージのマニフェスト（manifest.json・index.json）が見つかりません",
+        Msg::ImageManifestInvalid => "イメージのマニフェスト {0} が不正です",
+        Msg::ImageBlobMissing => "イメージ内に {0} が見つかりません",
+        Msg::ImageLayerUnsupported => "レイヤー {0} の圧縮形式（zstd）には対応していません",
+        Msg::InvalidKeySyntax => "キー \"{0}\" は設定ファイルに記述できません（空白・`=`を含むか、`#`・`;`・`-`で始まっています）",
+        Msg::InvalidValueSyntax => "キー {0} の値 \"{1}\" は設定ファイルに記述できません（改行・前後の空白を含むか、長すぎます）",
+        Msg::CacheSaveFailed => "Warning: キャッシュファイル {0} を保存できません: {1}",
+        Msg::DescTypeMismatch => "値の型がスキーマと一致しません",
+        Msg::DescUnsupportedType => "スキーマ型がサポートされていません",
+        Msg::DescUnknownKey => "キーがスキーマに存在しません",
+        Msg::DescReadError => "ファイルまたはディレクトリの読み込みに失敗しました",
+        Msg::DescMissingSeparator => "'=' を含まない行は無視されます",
+        Msg::DescDuplicateKey => "同じファイル内でキーが重複しています",
+        Msg::DescValueTooLong => "値が4096文字を超えています",
+        Msg::DescEnvNameCollision => "別のキーと同じ環境変数名になります",
+        Msg::ExpectedType => "期待される型は '{0}'",
+        Msg::WritingSkeleton => "パース結果をファイルに出力します。",
+        Msg::SkeletonCreated => "
+型定義ファイル {0} を作成しました。🖋️✨（既存の定義 {1} 件をマージ）
+1.推論された型を確認し、必要に応じて修正して下さい。
+2.schema.txtに名前を変更して型定義ファイルとして使用して下さい。
+3.cargo runで.conf ファイルの設定をJSON 形式で出力し、型の検証結果も表示。
+",
+        Msg::FileCreateFailed => "ファイル {0} の作成に失敗しました: {1}",
+        Msg::MergedWritten => "{1} 件のキーを統合した設定ファイル {0} を作成しました。",
+        Msg::DriftMismatch => "キー '{0}' の実行中の値 '{1}' が設定値 '{2}' と異なります。",
+        Msg::DriftMissing => "キー '{0}' はカーネルに存在しません（設定値 '{1}'）。",
+        Msg::DriftUnreadable => "キー '{0}' の実行中の値を読み込めません: {1}",
+        Msg::ApplyApplied => "設定しました: {0} = {1}",
+        Msg::ApplyDryRun => "設定します（ドライラン）: {0} = {1}",
+        Msg::ApplyIgnored => "設定に失敗しましたが無視します: {0} = {1}: {2}",
+        Msg::ApplyFailed => "設定に失敗しました: {0} = {1}: {2}",
+        Msg::SnapshotWritten => "{1} 件のキーの実行中の値をスナップショット {0} に保存しました。",
+        Msg::ValueSet => "{0}: {1} = {2} を設定しました（以前の値: {3}）",
+        Msg::ValueAdded => "{0}: {1} = {2} を追加しました",
+        Msg::ValueUnchanged => "{0}: {1} は既に {2} のため、変更しませんでした",
+        Msg::ValueUnset => "{0}: {1} を削除しました（以前の値: {2}）",
+        Msg::ValueNotSet => "{0} を設定しているファイルはありません",
+        Msg::ConfigWriteFailed => "Error: ファイル {0} を更新できません: {1}",
+        Msg::ValueShadowed => "Warning: {0} の {1} = {2} が優先されるため、設定した値は有効になりません",
+        Msg::EditTargetNotSearched => "Warning: {0} は探索ディレクトリに含まれないため、設定した値は有効になりません",
+        Msg::VendorFileNotEditable => "{0} はベンダーのファイルのため編集できません（/etc/sysctl.d のファイルを指定してください）",
+        Msg::ValueStillSet => "Warning: {1} は引き続き {0} で {2} に設定されています",
+        Msg::QueryNoMatch => "Error: {0} に一致するキーは設定されていません",
+        Msg::QueryType => "型: {0}",
+        Msg::QueryTypeUndefined => "型: 未定義",
+        Msg::SnapshotLoadFailed => "スナップショット {0} を読み込めません: {1}",
+        Msg::SnapshotInvalid => "{0} のバージョン {1} の形式のファイルではありません",
+        Msg::WatchStarted => "{0} 件のファイル・{1} 件のキーを監視しています（Ctrl+C で終了）。",
+        Msg::WatchParsed => "[{0}] 再読み込み: {1}",
+        Msg::WatchRemoved => "[{0}] 削除: {1}",
+        Msg::WatchSchemaReloaded => "[{0}] 型定義ファイルを再読み込みしました。",
+        Msg::WatchResolved => "解消: {0}",
+        Msg::ExplainKey => "キー: {0}",
+        Msg::ExplainValue => "有効な値: {0}",
+        Msg::ExplainValueUnset => "有効な値: (未設定)",
+        Msg::ExplainType => "スキーマ型: {0}",
+        Msg::ExplainTypeUndefined => "スキーマ型: (未定義)",
+        Msg::ExplainFailed => "Error: キー {0} の設定状況を調べられません: {1}",
+    }
+}
+
+/// 英語のメッセージカタログ
+fn en(msg: Msg) -> &'static str {
+    match msg {
+        Msg::Usage => "\
+Usage: linux-conf-parser [OPTIONS] [COMMAND]
+
+Commands:
+  check                  Parse .conf files and validate them against the schema (default)
+  dump                   Print the merged configuration
+  merge [OUTPUT]         Write the merged configuration as a canonical sysctl.conf (default: stdout)
+  schema init [OUTPUT]   Create a schema skeleton (default: output.txt)
+  explain <KEY>          Show which files set a key, its effective value and schema type
+  get <KEY>              Show the effective values of a key or the keys under it (e.g. net.ipv4, alias: query)
+  drift                  Compare the merged configuration with the running values (/proc/sys)
+  apply                  Write the merged configuration to /proc/sys if validation succeeds
+  snapshot [OUTPUT]      Save the running values of the configured keys as a JSON snapshot (default: stdout)
+  restore <SNAPSHOT>     Write the values of a snapshot back to /proc/sys
+  watch                  Watch the directories, re-validate changed files and print changes in diagnostics and values
+  set <KEY> <VALUE>      Validate the value against the schema and write it to the file providing the
+                         effective value (or 99-local.conf in the first directory)
+  unset <KEY>            Remove the lines setting the key from every file (or from --file)
+  diff <OLD> <NEW>       Compare the configuration of two roots (directories) or outputs (JSON or key = value)
+  help                   Print this help
+
+Options:
+  --schema <PATH>        Schema file (default: schema.txt)
+  --root <PATH>          Resolve the search directories inside this directory (chroot, extracted image, ...)
+  --image <PATH>         Search the filesystem built from the layers of a container image
+                         (docker save tarball or OCI image layout; check, dump, merge, schema init)
+  --dir <PATH>           Directory to search (repeatable, earlier ones take precedence, default: the production sysctl.d path)
+  --file <PATH>          File to edit with set and unset (resolved under --root if given)
+  --format <FORMAT>      Output format
+                           check:   json (diagnostics as JSON Lines), sarif (SARIF 2.1.0)
+                           dump:    json (default), flat, yaml, toml, env
+                           explain, get: json
+                           diff:    json, unified
+                           drift:   json
+                           apply, restore: json
+                           watch:   json (one object per line for each change)
+  --proc-sys <PATH>      Directory to read and write running values (default: /proc/sys)
+  --dry-run              With apply and restore, only report what would be written
+  --cache <PATH>         Cache parse results per file and skip parsing unchanged files
+  -j, --jobs <N>         Number of threads for discovering and parsing files (default: 0 = number of CPUs)
+  --lang <LANG>          Message language: ja, en (default: from LC_ALL / LC_MESSAGES / LANG)
+  -q, --quiet            Do not print per-file results or progress messages
+  --fail-on-warnings     Exit with code 3 when there are only warnings
+  --annotate             With merge, add a comment with the source file and line of each key
+  --show-source          With get, show the file and line providing each effective value
+  --show-type            With get, show the schema type of each key
+  -h, --help             Print this help
+
+Exit codes:
+  0  Success
+  1  Schema validation errors (for diff and drift: differences found)
+  2  Invalid command-line arguments
+  3  Warnings only (with --fail-on-warnings)
+  4  Failed to read or write files or directories (for apply and restore: failed writes)",
+        Msg::OptionRequiresValue => "Option '{0}' requires a value.",
+        Msg::UnsupportedFormat => "Output format '{0}' is not supported.",
+        Msg::UnsupportedLang => "Language '{0}' is not supported (use ja or en).",
+        Msg::UnknownOption => "Unknown option '{0}'.",
+        Msg::InvalidJobs => "The number of threads '{0}' must be a non-negative integer.",
+        Msg::ImageNotAvailable => "--image is not available for this command.",
+        Msg::ImageRootConflict => "--image and --root cannot be used together.",
+        Msg::FormatNotAvailable => "Output format '{0}' is not available for this command.",
+        Msg::ExplainRequiresKey => "explain requires a key.",
+        Msg::GetRequiresKey => "get requires a key.",
+        Msg::DiffRequiresPaths => "diff requires two paths to compare.",
+        Msg::RestoreRequiresPath => "restore requires a snapshot file.",
+        Msg::SetRequiresKeyValue => "set requires a key and a value.",
+        Msg::UnsetRequiresKey => "unset requires a key.",
+        Msg::FileOptionNotAvailable => "--file is only available for set and unset.",
+        Msg::UnknownCommand => "Unknown command or unexpected arguments '{0}'.",
+        Msg::ConfigHasErrors => "The configuration files contain errors.",
+        Msg::SchemaLoadFailed => "Failed to load the schema file: {0}",
+        Msg::AllFilesValid => "All files were parsed successfully and match the schema.",
+        Msg::ParseFailed => "An error occurred while parsing the configuration files: {0}",
+        Msg::FileOpenFailed => "Error: failed to open file '{0}': {1}",
+        Msg::FileReadFailed => "Error: failed to read file '{0}': {1}",
+        Msg::InvalidUtf8 => "line {1} of '{0}' is not valid UTF-8",
+        Msg::ValueTooLong => "The value of key '{2}' in file '{0}' at line {1} exceeds {3} characters. The line is ignored.",
+        Msg::EnvNameCollision => "The key '{1}' is not output because it maps to the same environment variable name {0} as the key '{2}'.",
+        Msg::DuplicateKey => "Key '{2}' is set more than once in file '{0}' at line {1} (first set at line {3}). The later value is used.",
+        Msg::MissingSeparator => "Line {1} of file '{0}' does not contain '=' and was ignored: {2}",
+        Msg::SchemaOpenFailed => "Error: failed to open schema file '{0}': {1}",
+        Msg::SchemaReadFailed => "Error: failed to read schema file '{0}': {1}",
+        Msg::TypeMismatch => "The value '{1}' of key '{0}' has the wrong type. Expected type: '{2}'",
+        Msg::UnsupportedType => "The schema type '{1}' of key '{0}' is not supported.",
+        Msg::UnknownKey => "Key '{0}' is not defined in the schema.",
+        Msg::FileParseFailed => "Failed to parse file '{0}': {1}",
+        Msg::DirectoryParseFailed => "Failed to parse directory '{0}': {1}",
+        Msg::DirectoryNotFound => "Error: directory '{0}' does not exist.",
+        Msg::DirectoryReadFailed => "Error: failed to read directory '{0}': {1}",
+        Msg::SymlinkLoop => "Too many levels of symbolic links: {0}",
+        Msg::TarInvalidHeader => "Invalid tar header at byte offset {0}",
+        Msg::TarTruncated => "The tar archive is truncated",
+        Msg::GzipInvalid => "Invalid gzip data",
+        Msg::GzipTooLarge => "Decompressed gzip data exceeds the limit of {0} bytes",
+        Msg::ImageLoadFailed => "Error: failed to load the image {0}: {1}",
+        Msg::ImageManifestMissing => "The image manifest (manifest.json or index.json) was not found",
+        Msg::ImageManifestInvalid => "Invalid image manifest {0}",
+        Msg::ImageBlobMissing => "{0} was not found in the image",
+        Msg::ImageLayerUnsupported => "The compression of layer {0} (zstd) is not supported",
+        Msg::InvalidKeySyntax => "The key \"{0}\" cannot be written to a configuration file (it contains whitespace or `=`, or starts with `#`, `;` or `-`)",
+        Msg::InvalidValueSyntax => "The value \"{1}\" of {0} cannot be written to a configuration file (it contains line breaks or surrounding whitespace, or is too long)",
+        Msg::CacheSaveFailed => "Warning: failed to save the cache file {0}: {1}",
+        Msg::DescTypeMismatch => "The value does not match the schema type",
+        Msg::DescUnsupportedType => "The schema type is not supported",
+        Msg::DescUnknownKey => "The key is not defined in the schema",
+        Msg::DescReadError => "Failed to read a file or directory",
+        Msg::DescMissingSeparator => "Lines without '=' are ignored",
+        Msg::DescDuplicateKey => "The key is set more than once in the same file",
+        Msg::DescValueTooLong => "The value exceeds 4096 characters",
+        Msg::DescEnvNameCollision => "The key maps to the same environment variable name as another key",
+        Msg::ExpectedType => "expected type '{0}'",
+        Msg::WritingSkeleton => "Writing the parse results to a file.",
+        Msg::SkeletonCreated => "
+Created schema file {0}. ({1} existing definitions merged)
+1. Review the inferred types and adjust them if needed.
+2. Rename it to schema.txt to use it as the schema file.
+3. Run cargo run to print the .conf settings as JSON and validate their types.
+",
+        Msg::FileCreateFailed => "Failed to create file {0}: {1}",
+        Msg::MergedWritten => "Wrote the merged configuration with {1} keys to {0}.",
+        Msg::DriftMismatch => "The running value '{1}' of key '{0}' differs from the configured value '{2}'.",
+        Msg::DriftMissing => "Key '{0}' does not exist in the kernel (configured value '{1}').",
+        Msg::DriftUnreadable => "Cannot read the running value of key '{0}': {1}",
+        Msg::ApplyApplied => "Applied: {0} = {1}",
+        Msg::ApplyDryRun => "Would apply (dry run): {0} = {1}",
+        Msg::ApplyIgnored => "Failed to apply, ignored: {0} = {1}: {2}",
+        Msg::ApplyFailed => "Failed to apply: {0} = {1}: {2}",
+        Msg::SnapshotWritten => "Saved the running values of {1} keys to the snapshot {0}.",
+        Msg::ValueSet => "{0}: set {1} = {2} (previous value: {3})",
+        Msg::ValueAdded => "{0}: added {1} = {2}",
+        Msg::ValueUnchanged => "{0}: {1} is already {2}, nothing changed",
+        Msg::ValueUnset => "{0}: removed {1} (previous value: {2})",
+        Msg::ValueNotSet => "No file sets {0}",
+        Msg::ConfigWriteFailed => "Error: failed to update the file {0}: {1}",
+        Msg::ValueShadowed => "Warning: {1} = {2} in {0} takes precedence, so the new value is not effective",
+        Msg::EditTargetNotSearched => "Warning: {0} is not in the searched directories, so the new value is not effective",
+        Msg::VendorFileNotEditable => "{0} is a vendor file and is not edited (specify a file in /etc/sysctl.d)",
+        Msg::ValueStillSet => "Warning: {1} is still set to {2} in {0}",
+        Msg::QueryNoMatch => "Error: no key matching {0} is set",
+        Msg::QueryType => "type: {0}",
+        Msg::QueryTypeUndefined => "type: undefined",
+        Msg::SnapshotLoadFailed => "Cannot load the snapshot {0}: {1}",
+        Msg::SnapshotInvalid => "not a {0} version {1} file",
+        Msg::WatchStarted => "Watching {0} files with {1} keys (press Ctrl+C to stop).",
+        Msg::WatchParsed => "[{0}] Reloaded: {1}",
+        Msg::WatchRemoved => "[{0}] Removed: {1}",
+        Msg::WatchSchemaReloaded => "[{0}] Reloaded the schema file.",
+        Msg::WatchResolved => "Resolved: {0}",
+        Msg::ExplainKey => "Key: {0}",
+        Msg::ExplainValue => "Effective value: {0}",
+        Msg::ExplainValueUnset => "Effective value: (not set)",
+        Msg::ExplainType => "Schema type: {0}",
+        Msg::ExplainTypeUndefined => "Schema type: (not defined)",
+        Msg::ExplainFailed => "Error: failed to explain the key {0}: {1}",
+    }
+}
@@ -1,3 +1,6 @@
+pub mod cli;
 pub mod config;
 pub mod core;
+pub mod exit_code;
+pub mod i18n;
 pub mod utils;
@@ -1,50 +1,494 @@
-mod config;
-mod core;
-mod utils;
-
-use core::{directory_parser::DirectoryParser, schema::LoadSchema};
+use linux_conf_parser::cli::{usage, Cli, Command, OutputFormat};
+use linux_conf_parser::config::Config;
+use linux_conf_parser::core::diagnostic::Diagnostic;
+use linux_conf_parser::core::diff::{
+    diff_configs, load_resolved_config, KeyChange, ResolvedConfig,
+};
+use linux_conf_parser::core::edit::{
+    edit_target, is_same_file, is_vendor_file, set_in_file, setting_files, unset_in_file,
+    EditOutcome,
+};
+use linux_conf_parser::core::procfs::{
+    apply_values, check_drift, ApplyResult, ApplyStatus, KeyDrift,
+};
+use linux_conf_parser::core::query::{select_keys, QueryMatch};
+use linux_conf_parser::core::snapshot::{
+    capture_snapshot, current_timestamp, restore_snapshot, Snapshot,
+};
+use linux_conf_parser::core::watch::{DirectoryWatcher, WatchDelta, WatchState};
+use linux_conf_parser::core::{
+    self,
+    directory_parser::{DirectoryParser, SourceParser},
+    image::load_image,
+    schema::{check_against_schema, LoadSchema},
+    ParseFiles, ParseOptions, ParseReport, SchemaLoader,
+};
+use linux_conf_parser::exit_code::ExitStatus;
+use linux_conf_parser::i18n::{self, tr, Lang, Msg};
+use linux_conf_parser::utils::display::{display_explanation, display_explanation_json};
+use linux_conf_parser::utils::format::{
+    render_apply_human, render_apply_json, render_diff_human, render_diff_json,
+    render_diff_unified, render_drift_human, render_drift_json, render_query_human,
+    render_query_json, render_watch_human, render_watch_json,
+};
+use linux_conf_parser::utils::output::{dump_renderer, handle_output};
+use linux_conf_parser::utils::report::{render_human, stderr_color};
 use rustc_hash::FxHashMap;
-use std::io;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, process::ExitCode, thread};
 
-use utils::output::handle_output;
+fn main() -> ExitCode {
+    // メッセージの言語を環境変数から決定（--lang が指定された場合はそちらを優先）
+    i18n::set_lang(Lang::from_env());
 
-fn main() -> io::Result<()> {
-    let directories = [
-        "test_config/etc/sysctl.d",
-        "test_config/run/sysctl.d",
-        "test_config/usr/local/lib/sysctl.d",
-        "test_config/usr/lib/sysctl.d",
-        "test_config/lib/sysctl.d",
-        "test_config/etc",
-        "test_config",
-    ];
+    // コマンドライン引数を解析
+    let cli: Cli = match Cli::parse(env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(message) => {
+            eprintln!("Error: {}\n\n{}", message, usage());
+            return ExitStatus::Usage.into();
+        }
+    };
+    if let Some(lang) = cli.lang {
+        i18n::set_lang(lang);
+    }
 
-    // パース結果を格納するマップ
-    let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+    // 探索するディレクトリ（--dir / 本番想定のデフォルト）
+    let directories: Vec<String> = cli.directories();
+    let directories: Vec<&str> = directories.iter().map(String::as_str).collect();
+    let schema_file: String = cli.schema.to_string_lossy().to_string();
 
-    let parser = DirectoryParser;
     let schema = LoadSchema;
 
-    // スキーマ検証と.confファイルのパースを実行
-    let result: Result<(), io::Error> = core::validate_schema_and_parse_files(
-        config::Config::SCHEMA_FILE_PATH,
-        &directories,
-        &parser,
-        &schema,
-        &mut result_map,
-    );
-
-    // コマンドライン引数に応じて出力方法を分岐
-    // cargo run .confファイルの設定を出力
-    // cargo run output .confファイルの空の型定義ファイルを出力
-    handle_output(result, &result_map)
+    match &cli.command {
+        Command::Help => {
+            println!("{}", usage());
+            ExitStatus::Success.into()
+        }
+        Command::Explain { key } => {
+            // スキーマを読み込めない場合も検証せずに設定状況は表示する
+            let schema: Option<FxHashMap<String, String>> = schema.load_schema(&cli.schema).ok();
+            let explanation = match core::explain::explain_key(
+                key,
+                &directories,
+                cli.root.as_deref(),
+                schema.as_ref(),
+            ) {
+                Ok(explanation) => explanation,
+                Err(e) => {
+                    eprintln!("{}", tr(Msg::ExplainFailed, &[&key, &e]));
+                    return ExitStatus::Io.into();
+                }
+            };
+            match cli.format {
+                Some(OutputFormat::Json) => display_explanation_json(&explanation),
+                _ => display_explanation(&explanation),
+            }
+            // 読み込めなかったファイルは標準エラー出力に表示（読み込みエラーは検証エラーより優先）
+            eprint!("{}", render_human(&explanation.diagnostics, stderr_color()));
+            match explanation.validation {
+                _ if !explanation.diagnostics.is_empty() => ExitStatus::Io.into(),
+                Ok(()) => ExitStatus::Success.into(),
+                Err(_) => ExitStatus::ValidationFailed.into(),
+            }
+        }
+        Command::Watch => run_watch(&cli, &directories),
+        Command::Set { key, value } => run_set(&cli, &directories, key, value),
+        Command::Unset { key } => run_unset(&cli, &directories, key),
+        Command::Restore { snapshot } => {
+            let snapshot: Snapshot = match Snapshot::load(snapshot) {
+                Ok(snapshot) => snapshot,
+                Err(e) => {
+                    eprintln!(
+                        "Error: {}",
+                        tr(Msg::SnapshotLoadFailed, &[&snapshot.display(), &e])
+                    );
+                    return ExitStatus::Io.into();
+                }
+            };
+            let results: Vec<ApplyResult> = restore_snapshot(&snapshot, &cli.proc_sys, cli.dry_run);
+            print_apply_results(&cli, &results)
+        }
+        Command::Diff { old, new } => {
+            // 両方を読み込み、読み込み時の診断は標準エラー出力に表示
+            let mut configs: Vec<ResolvedConfig> = Vec::new();
+            for path in [old, new] {
+                match load_resolved_config(path, &directories) {
+                    Ok((config, diagnostics)) => {
+                        eprint!("{}", render_human(&diagnostics, stderr_color()));
+                        if diagnostics.iter().any(Diagnostic::is_error) {
+                            return ExitStatus::Io.into();
+                        }
+                        configs.push(config);
+                    }
+                    Err(e) => {
+                        eprintln!("Error: {}: {}", path.display(), e);
+                        return ExitStatus::Io.into();
+                    }
+                }
+            }
+
+            let changes: Vec<KeyChange> = diff_configs(&configs[0], &configs[1]);
+            let output: String = match cli.format {
+                Some(OutputFormat::Json) => render_diff_json(&changes),
+                Some(OutputFormat::Unified) => {
+                    render_diff_unified(&changes, &old.to_string_lossy(), &new.to_string_lossy())
+                }
+                _ => render_diff_human(&changes),
+            };
+            print!("{}", output);
+
+            // diff(1) と同様に、差分がある場合は 1 で終了
+            if changes.is_empty() {
+                ExitStatus::Success.into()
+            } else {
+                ExitStatus::ValidationFailed.into()
+            }
+        }
+        _ => {
+            // イメージが指定された場合はレイヤーを適用したファイルシステムで探索
+            let parser: Box<dyn ParseFiles> = match &cli.image {
+                Some(path) => match load_image(path) {
+                    Ok(files) => Box::new(SourceParser::new(files)),
+                    Err(e) => {
+                        eprintln!("{}", tr(Msg::ImageLoadFailed, &[&path.display(), &e]));
+                        return ExitStatus::Io.into();
+                    }
+                },
+                None => Box::new(DirectoryParser),
+            };
+
+            // パース結果を格納するマップ
+            let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+
+            // check ではファイルごとの結果も表示し、それ以外はマージ結果のみを扱う
+            let options = ParseOptions {
+                verbose: !cli.quiet && cli.command == Command::Check && cli.format.is_none(),
+                validate: !matches!(
+                    cli.command,
+                    Command::SchemaInit { .. }
+                        | Command::Drift
+                        | Command::Snapshot { .. }
+                        | Command::Get { .. }
+                ),
+                root: cli.root.clone(),
+                cache: cli.cache.clone(),
+                jobs: cli.jobs,
+            };
+
+            // スキーマ検証と.confファイルのパースを実行
+            let mut result: io::Result<ParseReport> = core::validate_schema_and_parse_files(
+                &schema_file,
+                &directories,
+                parser.as_ref(),
+                &schema,
+                &mut result_map,
+                &options,
+            );
+
+            // dump の出力形式で表せない設定を警告に加える（診断の出力と終了コードに反映）
+            if let (Command::Dump, Ok(report)) = (&cli.command, &mut result) {
+                let warnings: Vec<Diagnostic> =
+                    dump_renderer(cli.format).diagnostics(&result_map, &report.sources);
+                report.diagnostics.extend(warnings);
+            }
+
+            // コマンドに応じて出力方法を分岐
+            if handle_output(&cli, &result, &result_map, parser.file_source()).is_err() {
+                return ExitStatus::Io.into();
+            }
+
+            let status: ExitStatus = ExitStatus::from_result(&result, cli.fail_on_warnings);
+            if let (Command::Drift, Ok(report), ExitStatus::Success) =
+                (&cli.command, &result, status)
+            {
+                // 実行中の値と比較し、違いがある場合は 1 で終了
+                let drifts: Vec<KeyDrift> =
+                    check_drift(&result_map, &report.sources, &cli.proc_sys);
+                match cli.format {
+                    Some(OutputFormat::Json) => print!("{}", render_drift_json(&drifts)),
+                    _ => print!("{}", render_drift_human(&drifts)),
+                }
+                if !drifts.is_empty() {
+                    return ExitStatus::ValidationFailed.into();
+                }
+            }
+            if let (Command::Get { key }, Ok(report), ExitStatus::Success) =
+                (&cli.command, &result, status)
+            {
+                // 優先順位を適用した後の値を表示（スキーマは型の表示にのみ使用）
+                let types: FxHashMap<String, String> =
+                    if cli.show_type || cli.format == Some(OutputFormat::Json) {
+                        schema.load_schema(&cli.schema).unwrap_or_default()
+                    } else {
+                        FxHashMap::default()
+                    };
+                let matches: Vec<QueryMatch> =
+                    select_keys(key, &result_map, &report.sources, &types);
+                if matches.is_empty() {
+                    eprintln!("{}", tr(Msg::QueryNoMatch, &[key]));
+                    return ExitStatus::ValidationFailed.into();
+                }
+                match cli.format {
+                    Some(OutputFormat::Json) => print!("{}", render_query_json(&matches)),
+                    _ => print!(
+                        "{}",
+                        render_query_human(&matches, cli.show_source, cli.show_type)
+                    ),
+                }
+            }
+            if let (Command::Apply, Ok(report), ExitStatus::Success) =
+                (&cli.command, &result, status)
+            {
+                // 検証に成功した場合のみ書き込む
+                let results: Vec<ApplyResult> =
+                    apply_values(&result_map, &report.sources, &cli.proc_sys, cli.dry_run);
+                return print_apply_results(&cli, &results);
+            }
+            if let (Command::Snapshot { output }, Ok(_), ExitStatus::Success) =
+                (&cli.command, &result, status)
+            {
+                // 設定されたキーの実行中の値を保存
+                let snapshot: Snapshot = capture_snapshot(result_map.keys(), &cli.proc_sys);
+                match output {
+                    Some(path) => {
+                        if let Err(e) = fs::write(path, snapshot.to_json()) {
+                            eprintln!("{}", tr(Msg::FileCreateFailed, &[&path.display(), &e]));
+                            return ExitStatus::Io.into();
+                        }
+                        if !cli.quiet {
+                            println!(
+                                "{}",
+                                tr(
+                                    Msg::SnapshotWritten,
+                                    &[&path.display(), &snapshot.values.len()]
+                                )
+                            );
+                        }
+                    }
+                    None => print!("{}", snapshot.to_json()),
+                }
+            }
+            status.into()
+        }
+    }
 }
 
-// 本番想定ディレクトリ
-// let directories = [
-//     "/etc/sysctl.d",
-//     "/run/sysctl.d",
-//     "/usr/local/lib/sysctl.d",
-//     "/usr/lib/sysctl.d",
-//     "/lib/sysctl.d",
-// ];
+/// ディレクトリを監視し、変化を表示し続ける（Ctrl+C で終了）
+fn run_watch(cli: &Cli, directories: &[&str]) -> ExitCode {
+    let mut state: WatchState = WatchState::new(directories, cli.root.as_deref(), &cli.schema);
+    let mut watcher: DirectoryWatcher = DirectoryWatcher::new();
+
+    // 最初は全ての診断と監視対象の規模を表示
+    let delta: WatchDelta = state.refresh();
+    match cli.format {
+        Some(OutputFormat::Json) => print!("{}", render_watch_json(&delta, &current_timestamp())),
+        _ => {
+            eprint!("{}", render_human(&delta.new_diagnostics, stderr_color()));
+            if !cli.quiet {
+                println!(
+                    "{}",
+                    tr(
+                        Msg::WatchStarted,
+                        &[&state.file_count(), &state.config().values.len()]
+                    )
+                );
+            }
+        }
+    }
+
+    loop {
+        watcher.watch(&state.watched_directories());
+        if watcher.wait(Duration::from_millis(Config::WATCH_INTERVAL_MS)) {
+            // 連続した書き込みをまとめてから再読み込み
+            thread::sleep(Duration::from_millis(Config::WATCH_DEBOUNCE_MS));
+            watcher.wait(Duration::ZERO);
+        }
+        let delta: WatchDelta = state.refresh();
+        if delta.is_empty() {
+            continue;
+        }
+        match cli.format {
+            Some(OutputFormat::Json) => {
+                print!("{}", render_watch_json(&delta, &current_timestamp()))
+            }
+            _ => {
+                print!("{}", render_watch_human(&delta, &current_timestamp()));
+                eprint!("{}", render_human(&delta.new_diagnostics, stderr_color()));
+            }
+        }
+        let _ = io::stdout().flush();
+    }
+}
+
+/// 値をスキーマで検証し、設定ファイルに書き込む
+fn run_set(cli: &Cli, directories: &[&str], key: &str, value: &str) -> ExitCode {
+    // 書き込む前にスキーマで検証（未定義のキーもエラーとする）
+    let schema: FxHashMap<String, String> = match LoadSchema.load_schema(&cli.schema) {
+        Ok(schema) => schema,
+        Err(_) => return ExitStatus::Io.into(),
+    };
+    let config_map: FxHashMap<String, String> =
+        FxHashMap::from_iter([(key.to_string(), value.to_string())]);
+    let diagnostics: Vec<Diagnostic> =
+        check_against_schema(&config_map, &schema, &FxHashMap::default());
+    if !diagnostics.is_empty() {
+        eprint!("{}", render_human(&diagnostics, stderr_color()));
+        return ExitStatus::ValidationFailed.into();
+    }
+
+    let root: Option<&Path> = cli.root.as_deref();
+    let target: PathBuf = match edit_target(key, directories, root, cli.file.as_deref()) {
+        Ok(target) => target,
+        // ベンダーのファイルが指定された
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+            eprintln!("Error: {}", e);
+            return ExitStatus::Usage.into();
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitStatus::Io.into();
+        }
+    };
+    let outcome: EditOutcome = match set_in_file(&target, key, value) {
+        Ok(outcome) => outcome,
+        // 改行を含むなど、設定ファイルに記述できない値
+        Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+            eprintln!("Error: {}", e);
+            return ExitStatus::ValidationFailed.into();
+        }
+        Err(e) => {
+            eprintln!("{}", tr(Msg::ConfigWriteFailed, &[&target.display(), &e]));
+            return ExitStatus::Io.into();
+        }
+    };
+    if !cli.quiet {
+        let file = outcome.file.display();
+        let message: String = match (&outcome.previous, outcome.changed) {
+            (Some(previous), true) => tr(Msg::ValueSet, &[&file, &key, &value, previous]),
+            (None, _) => tr(Msg::ValueAdded, &[&file, &key, &value]),
+            (Some(_), false) => tr(Msg::ValueUnchanged, &[&file, &key, &value]),
+        };
+        println!("{}", message);
+    }
+
+    // 書き込んだファイルの値が有効になるかを確認
+    let warning: Option<String> = match setting_files(key, directories, root) {
+        Ok(occurrences) => match occurrences.last() {
+            Some((path, _)) if is_same_file(path, &target) => None,
+            Some((path, effective))
+                if occurrences.iter().any(|(p, _)| is_same_file(p, &target)) =>
+            {
+                Some(tr(Msg::ValueShadowed, &[&path.display(), &key, effective]))
+            }
+            _ => Some(tr(Msg::EditTargetNotSearched, &[&target.display()])),
+        },
+        Err(e) => Some(format!("Error: {}", e)),
+    };
+    match warning {
+        Some(warning) => {
+            eprintln!("{}", warning);
+            if cli.fail_on_warnings {
+                ExitStatus::Warnings.into()
+            } else {
+                ExitStatus::Success.into()
+            }
+        }
+        None => ExitStatus::Success.into(),
+    }
+}
+
+/// キーを設定している行を設定ファイルから削除
+fn run_unset(cli: &Cli, directories: &[&str], key: &str) -> ExitCode {
+    let root: Option<&Path> = cli.root.as_deref();
+    let occurrences: Vec<(PathBuf, String)> = match setting_files(key, directories, root) {
+        Ok(occurrences) => occurrences,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            return ExitStatus::Io.into();
+        }
+    };
+
+    // --file 指定時はそのファイルのみ、それ以外はキーを設定している全てのファイル（ベンダーのファイルを除く）から削除
+    let targets: Vec<PathBuf> = match &cli.file {
+        Some(_) => match edit_target(key, directories, root, cli.file.as_deref()) {
+            Ok(target) => vec![target],
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                eprintln!("Error: {}", e);
+                return ExitStatus::Usage.into();
+            }
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return ExitStatus::Io.into();
+            }
+        },
+        None => occurrences
+            .iter()
+            .filter(|(path, _)| !is_vendor_file(path, root))
+            .map(|(path, _)| path.clone())
+            .collect(),
+    };
+
+    let mut removed: Vec<PathBuf> = Vec::new();
+    for target in &targets {
+        match unset_in_file(target, key) {
+            Ok(outcome) => {
+                if let (true, Some(previous)) = (outcome.changed, &outcome.previous) {
+                    if !cli.quiet {
+                        println!(
+                            "{}",
+                            tr(Msg::ValueUnset, &[&outcome.file.display(), &key, previous])
+                        );
+                    }
+                    removed.push(outcome.file);
+                }
+            }
+            Err(e) => {
+                eprintln!("{}", tr(Msg::ConfigWriteFailed, &[&target.display(), &e]));
+                return ExitStatus::Io.into();
+            }
+        }
+    }
+    if removed.is_empty() && !cli.quiet {
+        println!("{}", tr(Msg::ValueNotSet, &[&key]));
+    }
+
+    // 削除しなかったファイルで引き続き設定されている場合は警告
+    let remaining: Vec<&(PathBuf, String)> = occurrences
+        .iter()
+        .filter(|(path, _)| !removed.iter().any(|file| is_same_file(file, path)))
+        .collect();
+    match remaining.last() {
+        Some((path, value)) => {
+            eprintln!(
+                "{}",
+                tr(Msg::ValueStillSet, &[&path.display(), &key, value])
+            );
+            if cli.fail_on_warnings {
+                ExitStatus::Warnings.into()
+            } else {
+                ExitStatus::Success.into()
+            }
+        }
+        None => ExitStatus::Success.into(),
+    }
+}
+
+/// apply・restore の結果を表示し、失敗したキーがある場合は 4 で終了
+fn print_apply_results(cli: &Cli, results: &[ApplyResult]) -> ExitCode {
+    match cli.format {
+        Some(OutputFormat::Json) => print!("{}", render_apply_json(results)),
+        _ if cli.quiet => {}
+        _ => print!("{}", render_apply_human(results)),
+    }
+    if results
+        .iter()
+        .any(|result| matches!(result.status, ApplyStatus::Failed { .. }))
+    {
+        ExitStatus::Io.into()
+    } else {
+        ExitStatus::Success.into()
+    }
+}
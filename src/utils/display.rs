@@ -1,37 +1,177 @@
 use rustc_hash::FxHashMap;
 use serde_json::{json, Value};
+use std::collections::BTreeMap;
 
-/// FxHashMapの内容をフラットに出力
-pub fn _display_flat_map(map: &FxHashMap<String, String>) {
-    for (key, value) in map {
-        println!("{} {}", key, value);
-    }
+use crate::core::explain::KeyExplanation;
+use crate::i18n::{tr, Msg};
+use crate::utils::format::{FlatRenderer, JsonRenderer, Renderer};
+
+/// FxHashMapの内容をフラットに出力（キーでソート）
+pub fn display_flat_map(map: &FxHashMap<String, String>) {
+    print!("{}", FlatRenderer.render(map, &FxHashMap::default()));
 }
 
 /// FxHashMapの内容をJSON形式出力（ネスト対応、整形出力）
-pub fn display_json_map(map: &FxHashMap<String, String>) {
+///
+/// スキーマで型が定義されているキーは、その型のJSON値として出力する
+pub fn display_json_map(map: &FxHashMap<String, String>, schema: &FxHashMap<String, String>) {
+    // JSON形式に変換してインデント付きで出力
+    print!("{}", JsonRenderer.render(map, schema));
+}
+
+/// キーが値とネストしたキーの両方を持つ場合に、値を格納するフィールド名
+pub const LEAF_VALUE_KEY: &str = "_value";
+
+/// FxHashMapの内容を`.`区切りでネストしたJSONに変換
+///
+/// `log = x`と`log.file = y`のようにキーが値とネストしたキーの両方を持つ場合は、
+/// `{"log": {"_value": "x", "file": "y"}}`のように値を`_value`に格納する。
+/// 予約フィールドと衝突しないよう、`_value`・`__value`のような名前の部分には`_`を1つ追加する
+/// （`log._value = x`は`{"log": {"__value": "x"}}`となる）。
+/// オブジェクトのキーは常にソートされ、結果は挿入順に依存しない。
+pub fn json_map_to_value(
+    map: &FxHashMap<String, String>,
+    schema: &FxHashMap<String, String>,
+) -> Value {
     let mut json_map: serde_json::Map<String, Value> = serde_json::Map::new();
 
-    // 再帰的にネストしたマップを構築する関数
-    fn insert_nested(map: &mut serde_json::Map<String, Value>, key_parts: &[&str], value: &str) {
-        if key_parts.len() == 1 {
-            map.insert(key_parts[0].to_string(), json!(value));
-        } else {
-            let entry: &mut Value = map
-                .entry(key_parts[0].to_string())
-                .or_insert_with(|| json!(serde_json::Map::new()));
-            if let Some(sub_map) = entry.as_object_mut() {
-                insert_nested(sub_map, &key_parts[1..], value);
+    let sorted: BTreeMap<&String, &String> = map.iter().collect();
+    for (key, value) in sorted {
+        let key_parts: Vec<String> = key.split('.').map(escape_key_part).collect();
+        let key_parts: Vec<&str> = key_parts.iter().map(String::as_str).collect();
+        let value: Value = typed_value(value, schema.get(key).map(String::as_str));
+        insert_nested(&mut json_map, &key_parts, value);
+    }
+
+    json!(json_map)
+}
+
+/// `key_parts`の順にネストしたオブジェクトを作成して値を設定
+///
+/// 値とネストしたキーのどちらを先に設定しても、値は`_value`に格納される
+pub fn insert_nested(map: &mut serde_json::Map<String, Value>, key_parts: &[&str], value: Value) {
+    let entry: &mut Value = map.entry(key_parts[0].to_string()).or_insert(Value::Null);
+    if key_parts.len() == 1 {
+        // 既にネストしたキーがある場合は`_value`に格納
+        match entry.as_object_mut() {
+            Some(sub_map) => {
+                sub_map.insert(LEAF_VALUE_KEY.to_string(), value);
+            }
+            None => *entry = value,
+        }
+    } else {
+        // 既に値がある場合はオブジェクトに変換し、値を`_value`に移す
+        if !entry.is_object() {
+            let mut sub_map: serde_json::Map<String, Value> = serde_json::Map::new();
+            if !entry.is_null() {
+                sub_map.insert(LEAF_VALUE_KEY.to_string(), entry.take());
             }
+            *entry = Value::Object(sub_map);
+        }
+        if let Some(sub_map) = entry.as_object_mut() {
+            insert_nested(sub_map, &key_parts[1..], value);
         }
     }
+}
 
-    for (key, value) in map {
-        let key_parts: Vec<&str> = key.split('.').collect();
-        insert_nested(&mut json_map, &key_parts, value);
+/// キーの部分が`_value`の前に`_`を付けた名前の場合は`_`を1つ追加（それ以外はそのまま）
+fn escape_key_part(part: &str) -> String {
+    if is_reserved_like(part) {
+        format!("_{}", part)
+    } else {
+        part.to_string()
     }
+}
 
-    // JSON形式に変換してインデント付きで出力
-    let json_output: String = serde_json::to_string_pretty(&json!(json_map)).unwrap();
-    println!("{}", json_output);
+/// `json_map_to_value`で`_`を追加したオブジェクトのキーを元のキーの部分に戻す
+///
+/// 予約フィールドの`_value`自体は値を表すため、呼び出し側で扱う
+pub fn unescape_key_part(key: &str) -> &str {
+    if key != LEAF_VALUE_KEY && is_reserved_like(key) {
+        &key[1..]
+    } else {
+        key
+    }
+}
+
+/// `_value`・`__value`のように、`_value`の前に`_`を付けた名前か
+fn is_reserved_like(part: &str) -> bool {
+    let reserved: &str = LEAF_VALUE_KEY.trim_start_matches('_');
+    part.starts_with('_') && part.trim_start_matches('_') == reserved
+}
+
+/// 値をスキーマ型に応じたJSON値に変換
+///
+/// `int[]`のような配列型は空白区切りの値を配列にする。
+/// 型が未定義の場合や値が型に一致しない場合は文字列のまま出力する。
+pub fn typed_value(value: &str, value_type: Option<&str>) -> Value {
+    if let Some(element_type) = value_type.and_then(|t| t.strip_suffix("[]")) {
+        let elements: Option<Vec<Value>> = value
+            .split_whitespace()
+            .map(|element| scalar_value(element, element_type))
+            .collect();
+        return match elements {
+            Some(elements) => Value::Array(elements),
+            None => json!(value),
+        };
+    }
+    value_type
+        .and_then(|value_type| scalar_value(value, value_type))
+        .unwrap_or_else(|| json!(value))
+}
+
+/// 単一の値をスキーマ型のJSON値に変換（変換できない場合は`None`）
+fn scalar_value(value: &str, value_type: &str) -> Option<Value> {
+    match value_type {
+        "bool" => value.parse::<bool>().ok().map(Value::Bool),
+        "int" if !value.contains('.') => value.parse::<i64>().ok().map(|n| json!(n)),
+        "float" => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number),
+        "string" => Some(json!(value)),
+        _ => None,
+    }
+}
+
+/// キーの設定状況を表示
+pub fn display_explanation(explanation: &KeyExplanation) {
+    println!("{}", tr(Msg::ExplainKey, &[&explanation.key]));
+    match explanation.effective_value() {
+        Some(value) => println!("{}", tr(Msg::ExplainValue, &[&value])),
+        None => println!("{}", tr(Msg::ExplainValueUnset, &[])),
+    }
+    match &explanation.expected_type {
+        Some(expected_type) => println!("{}", tr(Msg::ExplainType, &[expected_type])),
+        None => println!("{}", tr(Msg::ExplainTypeUndefined, &[])),
+    }
+
+    // 設定しているファイルを探索順に表示（最後のものが有効）
+    let last: usize = explanation.occurrences.len().saturating_sub(1);
+    for (i, (path, value)) in explanation.occurrences.iter().enumerate() {
+        let marker: &str = if i == last { "*" } else { " " };
+        println!("  {} {} = {}", marker, path.display(), value);
+    }
+
+    if let Err(errors) = &explanation.validation {
+        println!("{}", errors);
+    }
+}
+
+/// キーの設定状況をJSON形式で出力
+pub fn display_explanation_json(explanation: &KeyExplanation) {
+    let occurrences: Vec<Value> = explanation
+        .occurrences
+        .iter()
+        .map(|(path, value)| json!({ "file": path.to_string_lossy(), "value": value }))
+        .collect();
+    let output: Value = json!({
+        "key": explanation.key,
+        "value": explanation.effective_value(),
+        "type": explanation.expected_type,
+        "occurrences": occurrences,
+        "valid": explanation.validation.is_ok(),
+    });
+    println!("{}", serde_json::to_string_pretty(&output).unwrap());
 }
@@ -0,0 +1,620 @@
+use regex::Regex;
+use rustc_hash::FxHashMap;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::LazyLock;
+
+use crate::cli::OutputFormat;
+use crate::core::diagnostic::{Diagnostic, DiagnosticCode, Location};
+use crate::core::diff::{ChangeKind, KeyChange};
+use crate::core::procfs::{ApplyResult, ApplyStatus, DriftKind, KeyDrift};
+use crate::core::query::QueryMatch;
+use crate::core::watch::WatchDelta;
+use crate::core::EntrySource;
+use crate::i18n::{tr, Msg};
+use crate::utils::display::json_map_to_value;
+use crate::utils::report::diagnostic_to_json;
+
+/// マージ後の設定を文字列に変換する出力形式
+///
+/// どの形式もキーをソートして出力するため、結果は入力の順序に依存しない
+pub trait Renderer {
+    fn render(&self, map: &FxHashMap<String, String>, schema: &FxHashMap<String, String>)
+        -> String;
+
+    /// 出力形式で表せず、出力から除かれる設定の警告（定義位置は`sources`から取得）
+    fn diagnostics(
+        &self,
+        _map: &FxHashMap<String, String>,
+        _sources: &BTreeMap<String, EntrySource>,
+    ) -> Vec<Diagnostic> {
+        Vec::new()
+    }
+}
+
+/// ネストしたJSON（スキーマの型で出力）
+pub struct JsonRenderer;
+
+/// `key = value`形式（sysctl.conf）
+pub struct FlatRenderer;
+
+/// ネストしたYAML（スキーマの型で出力）
+pub struct YamlRenderer;
+
+/// ネストしたTOML（スキーマの型で出力）
+pub struct TomlRenderer;
+
+/// シェルの環境変数形式（`NET_IPV4_IP_FORWARD=1`）
+pub struct EnvRenderer;
+
+impl OutputFormat {
+    /// 設定の出力に使用する形式（診断専用の形式は`None`）
+    pub fn renderer(self) -> Option<&'static dyn Renderer> {
+        match self {
+            OutputFormat::Json => Some(&JsonRenderer),
+            OutputFormat::Flat => Some(&FlatRenderer),
+            OutputFormat::Yaml => Some(&YamlRenderer),
+            OutputFormat::Toml => Some(&TomlRenderer),
+            OutputFormat::Env => Some(&EnvRenderer),
+            OutputFormat::Sarif | OutputFormat::Unified => None,
+        }
+    }
+}
+
+impl Renderer for JsonRenderer {
+    fn render(
+        &self,
+        map: &FxHashMap<String, String>,
+        schema: &FxHashMap<String, String>,
+    ) -> String {
+        let mut output: String =
+            serde_json::to_string_pretty(&json_map_to_value(map, schema)).unwrap();
+        output.push('\n');
+        output
+    }
+}
+
+impl Renderer for FlatRenderer {
+    fn render(
+        &self,
+        map: &FxHashMap<String, String>,
+        _schema: &FxHashMap<String, String>,
+    ) -> String {
+        let sorted: BTreeMap<&String, &String> = map.iter().collect();
+        let mut output: String = String::new();
+        for (key, value) in sorted {
+            let _ = writeln!(output, "{} = {}", key, value);
+        }
+        output
+    }
+}
+
+impl Renderer for YamlRenderer {
+    fn render(
+        &self,
+        map: &FxHashMap<String, String>,
+        schema: &FxHashMap<String, String>,
+    ) -> String {
+        let mut output: String = String::new();
+        match json_map_to_value(map, schema) {
+            Value::Object(object) if !object.is_empty() => {
+                write_yaml_object(&mut output, &object, 0)
+            }
+            _ => output.push_str("{}\n"),
+        }
+        output
+    }
+}
+
+/// YAMLのマッピングを出力（ネストはインデント2つ）
+fn write_yaml_object(output: &mut String, object: &serde_json::Map<String, Value>, depth: usize) {
+    let indent: String = "  ".repeat(depth);
+    for (key, value) in object {
+        match value {
+            Value::Object(sub_object) if !sub_object.is_empty() => {
+                let _ = writeln!(output, "{}{}:", indent, yaml_scalar(key));
+                write_yaml_object(output, sub_object, depth + 1);
+            }
+            _ => {
+                let _ = writeln!(
+                    output,
+                    "{}{}: {}",
+                    indent,
+                    yaml_scalar(key),
+                    yaml_value(value)
+                );
+            }
+        }
+    }
+}
+
+/// YAMLの値（配列はフロー形式）
+fn yaml_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => yaml_scalar(text),
+        Value::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(yaml_value).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        Value::Object(_) => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// YAML 1.1で数値として解釈される形式（`0x10`・`0b101`・`1_000`・`1.2.3`・`.inf`・`.nan`など）
+static YAML11_NUMBER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?:[-+]?0b[01_]+|[-+]?0x[0-9a-f_]+|[-+]?[0-9][0-9_]*|[-+]?(?:[0-9][0-9_]*)?\.[0-9_.]*(?:e[-+]?[0-9]+)?|[-+]?\.inf|\.nan)$",
+    )
+    .unwrap()
+});
+
+/// YAMLの文字列（別の型として解釈される可能性がある場合は引用符で囲む）
+fn yaml_scalar(text: &str) -> String {
+    let plain: bool = !text.is_empty()
+        && text.trim() == text
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ' '))
+        && !text.starts_with('-')
+        && text.parse::<f64>().is_err()
+        && !YAML11_NUMBER.is_match(text)
+        && !matches!(
+            text.to_ascii_lowercase().as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "y" | "n" | "null" | "nan" | "inf"
+        );
+    if plain {
+        text.to_string()
+    } else {
+        // JSONの文字列はYAMLのダブルクォート文字列としても有効
+        Value::String(text.to_string()).to_string()
+    }
+}
+
+impl Renderer for TomlRenderer {
+    fn render(
+        &self,
+        map: &FxHashMap<String, String>,
+        schema: &FxHashMap<String, String>,
+    ) -> String {
+        let mut output: String = String::new();
+        if let Value::Object(object) = json_map_to_value(map, schema) {
+            write_toml_table(&mut output, &object, &[]);
+        }
+        output
+    }
+}
+
+/// TOMLのテーブルを出力（値を先に、サブテーブルを後に出力）
+fn write_toml_table(output: &mut String, table: &serde_json::Map<String, Value>, path: &[String]) {
+    let values: Vec<(&String, &Value)> = table.iter().filter(|(_, v)| !v.is_object()).collect();
+    if !values.is_empty() && !path.is_empty() {
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        let _ = writeln!(output, "[{}]", path.join("."));
+    }
+    for (key, value) in values {
+        let _ = writeln!(output, "{} = {}", toml_key(key), toml_value(value));
+    }
+
+    for (key, value) in table {
+        if let Value::Object(sub_table) = value {
+            let mut sub_path: Vec<String> = path.to_vec();
+            sub_path.push(toml_key(key));
+            write_toml_table(output, sub_table, &sub_path);
+        }
+    }
+}
+
+/// TOMLのキー（ベアキーに使用できない文字を含む場合は引用符で囲む）
+fn toml_key(key: &str) -> String {
+    let bare: bool = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// TOMLの値（文字列はJSONと同じエスケープで基本文字列として出力）
+fn toml_value(value: &Value) -> String {
+    match value {
+        Value::Array(elements) => {
+            let elements: Vec<String> = elements.iter().map(toml_value).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+impl Renderer for EnvRenderer {
+    fn render(
+        &self,
+        map: &FxHashMap<String, String>,
+        _schema: &FxHashMap<String, String>,
+    ) -> String {
+        let mut output: String = String::new();
+        for (name, keys) in env_names(map) {
+            let _ = writeln!(output, "{}={}", name, shell_quote(&map[keys[0]]));
+        }
+        output
+    }
+
+    /// 同じ環境変数名になるキーのうち、出力しなかったキー（キーの順で最初のキーを出力する）
+    fn diagnostics(
+        &self,
+        map: &FxHashMap<String, String>,
+        sources: &BTreeMap<String, EntrySource>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = Vec::new();
+        for (name, keys) in env_names(map) {
+            for key in &keys[1..] {
+                diagnostics.push(
+                    Diagnostic::new(
+                        DiagnosticCode::EnvNameCollision,
+                        tr(Msg::EnvNameCollision, &[&name, key, &keys[0]]),
+                    )
+                    .with_key(key)
+                    .with_value(&map[*key])
+                    .with_location(sources.get(*key).map(|source| source.location.clone())),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+/// 環境変数名ごとのキー（名前とキーの順）
+fn env_names(map: &FxHashMap<String, String>) -> BTreeMap<String, Vec<&String>> {
+    let mut names: BTreeMap<String, Vec<&String>> = BTreeMap::new();
+    for key in map.keys() {
+        names.entry(env_name(key)).or_default().push(key);
+    }
+    for keys in names.values_mut() {
+        keys.sort();
+    }
+    names
+}
+
+/// キーを環境変数名に変換（英数字以外は`_`、大文字化）
+pub fn env_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    // 数字で始まる名前は環境変数として使用できない
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+/// シェルで安全に扱えるよう、必要な場合のみシングルクォートで囲む
+fn shell_quote(value: &str) -> String {
+    let safe: bool = !value.is_empty()
+        && value.chars().all(|c| {
+            c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ':' | ',' | '+')
+        });
+    if safe {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// マージ後の設定を正規化したsysctl.confに変換
+///
+/// キーをソートして`key = value`形式で出力し、定義元で`-`が付いていたキーには`-`を付ける。
+/// `annotate`が有効な場合は、各キーの前に定義元のファイルと行番号をコメントで出力する。
+pub fn render_sysctl_conf(
+    map: &FxHashMap<String, String>,
+    sources: &BTreeMap<String, EntrySource>,
+    annotate: bool,
+) -> String {
+    let sorted: BTreeMap<&String, &String> = map.iter().collect();
+    let mut output: String = String::new();
+    for (key, value) in sorted {
+        let source: Option<&EntrySource> = sources.get(key);
+        if annotate {
+            if let Some(source) = source {
+                let _ = writeln!(
+                    output,
+                    "# {}:{}",
+                    source.location.file.display(),
+                    source.location.line
+                );
+            }
+        }
+        let prefix: &str = if source.is_some_and(|source| source.ignore_failure) {
+            "-"
+        } else {
+            ""
+        };
+        let _ = writeln!(output, "{}{} = {}", prefix, key, value);
+    }
+    output
+}
+
+/// 差分を人間向けの文字列に変換（`+`追加・`-`削除・`~`変更、定義位置を添える）
+pub fn render_diff_human(changes: &[KeyChange]) -> String {
+    let mut output: String = String::new();
+    for change in changes {
+        let old_value: &str = change.old_value.as_deref().unwrap_or("");
+        let new_value: &str = change.new_value.as_deref().unwrap_or("");
+        let _ = match change.kind {
+            ChangeKind::Added => writeln!(output, "+ {} = {}", change.key, new_value),
+            ChangeKind::Removed => writeln!(output, "- {} = {}", change.key, old_value),
+            ChangeKind::Changed => {
+                writeln!(output, "~ {}: {} -> {}", change.key, old_value, new_value)
+            }
+        };
+        for (label, source) in [("old", &change.old_source), ("new", &change.new_source)] {
+            if let Some(location) = source {
+                let _ = writeln!(
+                    output,
+                    "    {}: {}:{}",
+                    label,
+                    location.file.display(),
+                    location.line
+                );
+            }
+        }
+    }
+    output
+}
+
+/// 差分をJSONに変換
+pub fn render_diff_json(changes: &[KeyChange]) -> String {
+    let changes: Vec<Value> = changes.iter().map(change_to_json).collect();
+    let mut output: String = serde_json::to_string_pretty(&json!(changes)).unwrap();
+    output.push('\n');
+    output
+}
+
+/// 1つのキーの差分をJSONオブジェクトに変換
+fn change_to_json(change: &KeyChange) -> Value {
+    let source = |location: &Option<Location>| -> Value {
+        match location {
+            Some(location) => json!({
+                "file": location.file.to_string_lossy(),
+                "line": location.line,
+            }),
+            None => Value::Null,
+        }
+    };
+    json!({
+        "key": change.key,
+        "change": change.kind.as_str(),
+        "old": change.old_value,
+        "new": change.new_value,
+        "old_source": source(&change.old_source),
+        "new_source": source(&change.new_source),
+    })
+}
+
+/// 差分をunified diff風の文字列に変換（`key = value`の行単位）
+pub fn render_diff_unified(changes: &[KeyChange], old_label: &str, new_label: &str) -> String {
+    let mut output: String = String::new();
+    if changes.is_empty() {
+        return output;
+    }
+    let _ = writeln!(output, "--- {}", old_label);
+    let _ = writeln!(output, "+++ {}", new_label);
+    for change in changes {
+        if let Some(old_value) = &change.old_value {
+            let _ = writeln!(output, "-{} = {}", change.key, old_value);
+        }
+        if let Some(new_value) = &change.new_value {
+            let _ = writeln!(output, "+{} = {}", change.key, new_value);
+        }
+    }
+    output
+}
+
+/// 実行中の値との違いを人間向けの文字列に変換
+pub fn render_drift_human(drifts: &[KeyDrift]) -> String {
+    let mut output: String = String::new();
+    for drift in drifts {
+        let message: String = match &drift.kind {
+            DriftKind::Mismatch { actual } => {
+                tr(Msg::DriftMismatch, &[&drift.key, actual, &drift.expected])
+            }
+            DriftKind::Missing => tr(Msg::DriftMissing, &[&drift.key, &drift.expected]),
+            DriftKind::Unreadable { error } => tr(Msg::DriftUnreadable, &[&drift.key, error]),
+        };
+        let _ = writeln!(output, "{}", message);
+        if let Some(location) = &drift.source {
+            let _ = writeln!(
+                output,
+                "  --> {}:{}",
+                location.file.display(),
+                location.line
+            );
+        }
+    }
+    output
+}
+
+/// 実行中の値との違いをJSONに変換
+pub fn render_drift_json(drifts: &[KeyDrift]) -> String {
+    let drifts: Vec<Value> = drifts
+        .iter()
+        .map(|drift| {
+            let (actual, error): (Option<&str>, Option<&str>) = match &drift.kind {
+                DriftKind::Mismatch { actual } => (Some(actual), None),
+                DriftKind::Missing => (None, None),
+                DriftKind::Unreadable { error } => (None, Some(error)),
+            };
+            json!({
+                "key": drift.key,
+                "status": drift.kind.as_str(),
+                "expected": drift.expected,
+                "actual": actual,
+                "error": error,
+                "file": drift.source.as_ref().map(|l| l.file.to_string_lossy().to_string()),
+                "line": drift.source.as_ref().map(|l| l.line),
+            })
+        })
+        .collect();
+    let mut output: String = serde_json::to_string_pretty(&json!(drifts)).unwrap();
+    output.push('\n');
+    output
+}
+
+/// 問い合わせの結果を`key = value`形式に変換
+///
+/// `show_source`・`show_type`が有効な場合は、定義位置とスキーマの型を行末のコメントとして追加する
+pub fn render_query_human(matches: &[QueryMatch], show_source: bool, show_type: bool) -> String {
+    let mut output: String = String::new();
+    for query_match in matches {
+        let mut notes: Vec<String> = Vec::new();
+        if let (true, Some(source)) = (show_source, &query_match.source) {
+            notes.push(format!(
+                "{}:{}",
+                source.location.file.display(),
+                source.location.line
+            ));
+        }
+        if show_type {
+            notes.push(match &query_match.expected_type {
+                Some(expected_type) => tr(Msg::QueryType, &[expected_type]),
+                None => tr(Msg::QueryTypeUndefined, &[]),
+            });
+        }
+        let _ = write!(output, "{} = {}", query_match.key, query_match.value);
+        if !notes.is_empty() {
+            let _ = write!(output, "  # {}", notes.join(", "));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// 問い合わせの結果をJSONに変換
+pub fn render_query_json(matches: &[QueryMatch]) -> String {
+    let matches: Vec<Value> = matches
+        .iter()
+        .map(|query_match| {
+            let location = query_match.source.as_ref().map(|source| &source.location);
+            json!({
+                "key": query_match.key,
+                "value": query_match.value,
+                "type": query_match.expected_type,
+                "file": location.map(|l| l.file.to_string_lossy().to_string()),
+                "line": location.map(|l| l.line),
+                "column": location.map(|l| l.column),
+                "ignore_failure": query_match.source.as_ref().map(|s| s.ignore_failure),
+            })
+        })
+        .collect();
+    let mut output: String = serde_json::to_string_pretty(&json!(matches)).unwrap();
+    output.push('\n');
+    output
+}
+
+/// 適用結果を人間向けの文字列に変換
+pub fn render_apply_human(results: &[ApplyResult]) -> String {
+    let mut output: String = String::new();
+    for result in results {
+        let message: String = match &result.status {
+            ApplyStatus::Applied => tr(Msg::ApplyApplied, &[&result.key, &result.value]),
+            ApplyStatus::DryRun => tr(Msg::ApplyDryRun, &[&result.key, &result.value]),
+            ApplyStatus::Ignored { error } => {
+                tr(Msg::ApplyIgnored, &[&result.key, &result.value, error])
+            }
+            ApplyStatus::Failed { error } => {
+                tr(Msg::ApplyFailed, &[&result.key, &result.value, error])
+            }
+        };
+        let _ = writeln!(output, "{}", message);
+    }
+    output
+}
+
+/// 適用結果をJSONに変換
+pub fn render_apply_json(results: &[ApplyResult]) -> String {
+    let results: Vec<Value> = results
+        .iter()
+        .map(|result| {
+            let error: Option<&str> = match &result.status {
+                ApplyStatus::Ignored { error } | ApplyStatus::Failed { error } => Some(error),
+                _ => None,
+            };
+            json!({
+                "key": result.key,
+                "value": result.value,
+                "path": result.path.to_string_lossy(),
+                "status": result.status.as_str(),
+                "error": error,
+            })
+        })
+        .collect();
+    let mut output: String = serde_json::to_string_pretty(&json!(results)).unwrap();
+    output.push('\n');
+    output
+}
+
+/// 監視中の変化を人間向けの文字列に変換（新たな診断は含まない）
+///
+/// `time`は変化を検出した日時
+pub fn render_watch_human(delta: &WatchDelta, time: &str) -> String {
+    let mut output: String = String::new();
+    if delta.schema_reloaded {
+        let _ = writeln!(output, "{}", tr(Msg::WatchSchemaReloaded, &[&time]));
+    }
+    for path in &delta.parsed {
+        let _ = writeln!(
+            output,
+            "{}",
+            tr(Msg::WatchParsed, &[&time, &path.display()])
+        );
+    }
+    for path in &delta.removed {
+        let _ = writeln!(
+            output,
+            "{}",
+            tr(Msg::WatchRemoved, &[&time, &path.display()])
+        );
+    }
+    output.push_str(&render_diff_human(&delta.changes));
+    for diagnostic in &delta.resolved_diagnostics {
+        let _ = writeln!(output, "{}", tr(Msg::WatchResolved, &[&diagnostic.message]));
+    }
+    output
+}
+
+/// 監視中の変化を1行のJSONに変換
+pub fn render_watch_json(delta: &WatchDelta, time: &str) -> String {
+    let paths = |paths: &[std::path::PathBuf]| -> Vec<String> {
+        paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect()
+    };
+    let value: Value = json!({
+        "time": time,
+        "parsed": paths(&delta.parsed),
+        "removed": paths(&delta.removed),
+        "schema_reloaded": delta.schema_reloaded,
+        "changes": delta.changes.iter().map(change_to_json).collect::<Vec<Value>>(),
+        "new_diagnostics": delta.new_diagnostics.iter().map(diagnostic_to_json).collect::<Vec<Value>>(),
+        "resolved_diagnostics": delta
+            .resolved_diagnostics
+            .iter()
+            .map(diagnostic_to_json)
+            .collect::<Vec<Value>>(),
+    });
+    format!("{}\n", value)
+}
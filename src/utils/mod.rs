@@ -1,2 +1,4 @@
 pub mod display;
+pub mod format;
 pub mod output;
+pub mod report;
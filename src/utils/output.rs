@@ -1,70 +1,196 @@
 use rustc_hash::FxHashMap;
-use std::{
-    env,
-    fs::File,
-    io::{self, Error, Write},
-};
+use std::{collections::BTreeMap, fmt::Write as _, io, path::Path};
 
-/// コマンドライン引数に応じて出力方法を分岐
+use crate::cli::{Cli, Command, OutputFormat};
+use crate::core::edit::write_atomic;
+use crate::core::schema::{infer_type, LoadSchema};
+use crate::core::vfs::FileSource;
+use crate::core::{EntrySource, ParseReport, SchemaLoader};
+use crate::i18n::{tr, Msg};
+use crate::utils::format::{render_sysctl_conf, JsonRenderer, Renderer};
+use crate::utils::report::{render_human_from, render_json_lines, render_sarif, stderr_color};
+
+/// コマンドに応じて出力方法を分岐
+///
+/// `source`は診断の該当行の読み込みに使用する（パースした読み込み元）
 pub fn handle_output(
-    result: Result<(), io::Error>,
+    cli: &Cli,
+    result: &io::Result<ParseReport>,
     result_map: &FxHashMap<String, String>,
+    source: &dyn FileSource,
 ) -> io::Result<()> {
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 1 && args[1] == "output" {
-        // 出力ファイルパスの指定
-        let output_file_path = "output.txt";
+    // 診断を指定された形式で出力
+    if let Ok(report) = result {
+        output_diagnostics(cli, report, source);
+    }
 
-        // パースの成否にかかわらず情報をファイルに出力
-        if result.is_ok() {
-            println!("パース結果をファイルに出力します。");
-        } else {
-            // println!("エラーが発生しましたが、空の型定義を含むファイルを出力します。");
+    match &cli.command {
+        Command::SchemaInit { output } => {
+            // パースの成否にかかわらず情報をファイルに出力
+            if result.is_ok() && !cli.quiet {
+                println!("{}", tr(Msg::WritingSkeleton, &[]));
+            }
+            // パース結果を使ってファイルに出力
+            output_schema_skeleton(result_map, output, cli.quiet)
         }
-        // パース結果を使ってファイルに出力
-        output_empty_values_to_file(result_map, output_file_path)
-    } else {
-        // 標準出力
-        if result.is_ok() {
-            // デバッグ用
-            // println!("パース結果:");
-            // for (key, value) in result_map {
-            //     println!("{}: {}", key, value);
-            // }
-            Ok(())
-        } else {
-            // println!("パースに失敗しました");
+        Command::Dump => {
+            // マージ後の設定を指定された形式で出力（JSON・YAML・TOMLはスキーマの型で出力）
+            let renderer: &dyn Renderer = dump_renderer(cli.format);
+            let schema: FxHashMap<String, String> =
+                LoadSchema.load_schema(&cli.schema).unwrap_or_default();
+            print!("{}", renderer.render(result_map, &schema));
             Ok(())
         }
+        Command::Merge { output } => {
+            // パースや検証に失敗した場合は出力しない（既存の出力先ファイルはそのまま残す）
+            let sources: &BTreeMap<String, EntrySource> = match result {
+                Ok(report) if !report.has_errors() => &report.sources,
+                _ => return Ok(()),
+            };
+            let merged: String = render_sysctl_conf(result_map, sources, cli.annotate);
+            match output {
+                Some(path) => output_merged_conf(&merged, result_map.len(), path, cli.quiet),
+                None => {
+                    print!("{}", merged);
+                    Ok(())
+                }
+            }
+        }
+        // 検証結果は既に表示済み（終了コードは呼び出し側で決定）
+        _ => Ok(()),
+    }
+}
+
+/// dump で使用する出力形式（指定が無い場合はJSON）
+pub fn dump_renderer(format: Option<OutputFormat>) -> &'static dyn Renderer {
+    format
+        .and_then(OutputFormat::renderer)
+        .unwrap_or(&JsonRenderer)
+}
+
+/// 診断をコマンドと出力形式に応じて出力
+///
+/// check の json / sarif は標準出力へ、それ以外は人間向けの形式で標準エラー出力へ出力する
+fn output_diagnostics(cli: &Cli, report: &ParseReport, source: &dyn FileSource) {
+    match (&cli.command, cli.format) {
+        (Command::Check, Some(OutputFormat::Json)) => {
+            print!("{}", render_json_lines(&report.diagnostics))
+        }
+        (Command::Check, Some(OutputFormat::Sarif)) => {
+            println!("{}", render_sarif(&report.diagnostics))
+        }
+        _ => {
+            eprint!(
+                "{}",
+                render_human_from(&report.diagnostics, source, stderr_color())
+            );
+            if report.has_errors() {
+                eprintln!(
+                    "{}",
+                    tr(Msg::ParseFailed, &[&tr(Msg::ConfigHasErrors, &[])])
+                );
+            }
+        }
+    }
+}
+
+/// 統合した設定ファイルを出力（書き込み途中の内容が残らないよう置き換える）
+fn output_merged_conf(merged: &str, keys: usize, path: &Path, quiet: bool) -> io::Result<()> {
+    write_atomic(path, merged.as_bytes()).inspect_err(|e| {
+        eprintln!("{}", tr(Msg::FileCreateFailed, &[&path.display(), e]));
+    })?;
+    if !quiet {
+        println!("{}", tr(Msg::MergedWritten, &[&path.display(), &keys]));
     }
+    Ok(())
 }
 
-/// 指定されたキーを空の値としてファイルに出力
-pub fn output_empty_values_to_file(
+/// パース結果から型定義ファイルの雛形を生成してファイルに出力
+///
+/// 出力先に既存の型定義ファイルがある場合は、その定義を優先してマージする。
+/// 書き込みに失敗しても既存の型定義ファイルが壊れないよう、ファイル全体を置き換える。
+pub fn output_schema_skeleton(
     result_map: &FxHashMap<String, String>,
-    output_file_path: &str,
+    output_file_path: &Path,
+    quiet: bool,
 ) -> io::Result<()> {
-    // 出力先ファイルを開く
-    let output_file: Result<File, Error> = File::create(output_file_path);
-    match output_file {
-        Ok(mut file) => {
-            println!(
-                "\n空の型定義ファイル {} を作成しました。🖋️✨
-1.schema.txtに名前を変更して型定義ファイルを作成して下さい。
-2.cargo runで.conf ファイルの設定をJSON 形式で出力し、型の検証結果も表示。
-",
-                output_file_path
-            );
-            // パース結果のキーを空の値として出力
-            for key in result_map.keys() {
-                writeln!(file, "{} ->", key)?;
+    // 既存の型定義と補足説明を読み込む（存在しない場合は空）
+    let (existing, notes): (FxHashMap<String, String>, FxHashMap<String, String>) =
+        if output_file_path.is_file() {
+            (
+                LoadSchema.load_schema(output_file_path)?,
+                LoadSchema.load_schema_notes(output_file_path)?,
+            )
+        } else {
+            (FxHashMap::default(), FxHashMap::default())
+        };
+    let merged: usize = existing.len();
+
+    let skeleton: String = build_schema_skeleton(result_map, &existing, &notes);
+
+    write_atomic(output_file_path, skeleton.as_bytes()).inspect_err(|e| {
+        eprintln!(
+            "{}",
+            tr(Msg::FileCreateFailed, &[&output_file_path.display(), e])
+        );
+    })?;
+    if !quiet {
+        println!(
+            "{}",
+            tr(
+                Msg::SkeletonCreated,
+                &[&output_file_path.display(), &merged]
+            )
+        );
+    }
+    Ok(())
+}
+
+/// 型定義ファイルの雛形を文字列として生成
+///
+/// キーはソートされ、トップレベルの名前空間ごとにセクションコメントでまとめられる。
+/// `existing`に含まれるキーはその型を優先し、パース結果に無いキーも保持する。
+/// `notes`の補足説明は行末の`#`以降に出力する。
+pub fn build_schema_skeleton(
+    result_map: &FxHashMap<String, String>,
+    existing: &FxHashMap<String, String>,
+    notes: &FxHashMap<String, String>,
+) -> String {
+    // キーをソートしつつ型を決定（既存の定義 → 値からの推論の順）
+    let mut entries: BTreeMap<&str, &str> = BTreeMap::new();
+    for (key, value) in result_map {
+        entries.insert(key, infer_type(value).unwrap_or(""));
+    }
+    for (key, value_type) in existing {
+        // 型が空の既存定義（`key ->`）は推論した型を上書きしない
+        let entry: &mut &str = entries.entry(key).or_insert("");
+        if !value_type.is_empty() {
+            *entry = value_type;
+        }
+    }
+
+    let mut output: String = String::new();
+    let mut current_section: Option<&str> = None;
+    for (key, value_type) in entries {
+        // トップレベルの名前空間が変わったらセクションコメントを挿入
+        let section: &str = key.split('.').next().unwrap_or(key);
+        if current_section != Some(section) {
+            if current_section.is_some() {
+                output.push('\n');
             }
-            // println!("ファイルに書き込みが完了しました: {}", output_file_path);
+            let _ = writeln!(output, "# {}", section);
+            current_section = Some(section);
         }
-        Err(e) => {
-            eprintln!("ファイル {} の作成に失敗しました: {}", output_file_path, e);
-            return Err(e);
+
+        let mut line: String = if value_type.is_empty() {
+            format!("{} ->", key)
+        } else {
+            format!("{} -> {}", key, value_type)
+        };
+        if let Some(note) = notes.get(key) {
+            let _ = write!(line, "  # {}", note);
         }
+        let _ = writeln!(output, "{}", line);
     }
-    Ok(())
+    output
 }
@@ -0,0 +1,281 @@
+use rustc_hash::FxHashMap;
+use serde_json::{json, Value};
+use std::env;
+use std::fmt::Write as _;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use crate::core::diagnostic::{Diagnostic, DiagnosticCode, Severity};
+use crate::core::vfs::{FileSource, RealFs};
+use crate::i18n::{tr, Msg};
+
+/// SARIFのスキーマURI
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// ANSIエスケープシーケンス
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+/// 色付きで出力するかを判定
+///
+/// `NO_COLOR`が設定されている場合や、出力先が端末でない場合は色を付けない
+pub fn use_color(is_terminal: bool) -> bool {
+    let no_color: bool = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    is_terminal && !no_color
+}
+
+/// 標準エラー出力に色付きで出力するかを判定
+pub fn stderr_color() -> bool {
+    use_color(io::stderr().is_terminal())
+}
+
+/// 色付きの場合のみエスケープシーケンスで囲む
+fn paint(text: &str, style: &str, color: bool) -> String {
+    if color {
+        format!("{}{}{}", style, text, RESET)
+    } else {
+        text.to_string()
+    }
+}
+
+/// 診断をコンパイラ風の人間向けの文字列に変換
+///
+/// 位置が分かる場合はファイルパス・行番号・該当行を表示し、値の範囲に`^`で下線を引く。
+/// スキーマの補足説明があればヘルプとして表示する。
+/// 該当行は実際のファイルシステムから読み込む（イメージなどの場合は`render_human_from`を使用）。
+pub fn render_human(diagnostics: &[Diagnostic], color: bool) -> String {
+    render_human_from(diagnostics, &RealFs::default(), color)
+}
+
+/// `render_human`と同じ形式で、該当行を読み込み元`source`から読み込んで変換
+pub fn render_human_from(
+    diagnostics: &[Diagnostic],
+    source: &dyn FileSource,
+    color: bool,
+) -> String {
+    let mut sources: FxHashMap<PathBuf, Option<Vec<String>>> = FxHashMap::default();
+    let mut output: String = String::new();
+
+    for diagnostic in diagnostics {
+        let style: &str = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+        let header: String = format!(
+            "{}[{}]",
+            diagnostic.severity.as_str(),
+            diagnostic.code.as_str()
+        );
+        let _ = writeln!(
+            output,
+            "{}{}",
+            paint(&header, style, color),
+            paint(&format!(": {}", diagnostic.message), BOLD, color)
+        );
+
+        let Some(location) = &diagnostic.location else {
+            if let Some(help) = &diagnostic.help {
+                let _ = writeln!(output, "  = help: {}", help);
+            }
+            output.push('\n');
+            continue;
+        };
+
+        // 該当行を取得（ファイルごとに一度だけ読み込む）
+        let lines: &Option<Vec<String>> = sources
+            .entry(location.file.clone())
+            .or_insert_with(|| read_lines(source, &location.file));
+        let source_line: Option<&String> = lines
+            .as_ref()
+            .and_then(|lines| lines.get(location.line.wrapping_sub(1)));
+
+        let line_number: String = location.line.to_string();
+        let gutter: String = " ".repeat(line_number.len());
+        let bar: String = paint("|", BLUE, color);
+        let _ = writeln!(
+            output,
+            "{}{} {}:{}:{}",
+            gutter,
+            paint("-->", BLUE, color),
+            location.file.display(),
+            location.line,
+            location.column
+        );
+
+        if let Some(source_line) = source_line {
+            // タブを含む行でも下線の位置がずれないよう、値の前の空白はそのまま使う
+            let padding: String = source_line
+                .chars()
+                .take(location.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect();
+            let width: usize = location.end_column.saturating_sub(location.column).max(1);
+            let mut underline: String = "^".repeat(width);
+            if let Some(expected) = &diagnostic.expected {
+                underline.push(' ');
+                underline.push_str(&tr(Msg::ExpectedType, &[expected]));
+            }
+
+            let _ = writeln!(output, "{} {}", gutter, bar);
+            let _ = writeln!(
+                output,
+                "{} {} {}",
+                paint(&line_number, BLUE, color),
+                bar,
+                source_line
+            );
+            let _ = writeln!(
+                output,
+                "{} {} {}{}",
+                gutter,
+                bar,
+                padding,
+                paint(&underline, style, color)
+            );
+        }
+
+        if let Some(help) = &diagnostic.help {
+            let _ = writeln!(output, "{} {}", gutter, bar);
+            let _ = writeln!(
+                output,
+                "{} {} {}",
+                gutter,
+                paint("=", BLUE, color),
+                paint(&format!("help: {}", help), BOLD, color)
+            );
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// ファイルを行ごとに読み込む（読み込めない場合は`None`）
+fn read_lines(source: &dyn FileSource, path: &Path) -> Option<Vec<String>> {
+    let content = source.read(path).ok()?;
+    Some(
+        String::from_utf8_lossy(&content)
+            .lines()
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// 診断をJSON Lines形式に変換（1件1オブジェクト・1行）
+pub fn render_json_lines(diagnostics: &[Diagnostic]) -> String {
+    let mut output: String = String::new();
+    for diagnostic in diagnostics {
+        let _ = writeln!(output, "{}", diagnostic_to_json(diagnostic));
+    }
+    output
+}
+
+/// 診断をJSONオブジェクトに変換
+pub fn diagnostic_to_json(diagnostic: &Diagnostic) -> Value {
+    let location = diagnostic.location.as_ref();
+    json!({
+        "code": diagnostic.code.as_str(),
+        "severity": diagnostic.severity.as_str(),
+        "message": diagnostic.message,
+        "key": diagnostic.key,
+        "value": diagnostic.value,
+        "expected": diagnostic.expected,
+        "file": location.map(|l| l.file.to_string_lossy().to_string()),
+        "line": location.map(|l| l.line),
+        "column": location.map(|l| l.column),
+        "help": diagnostic.help,
+    })
+}
+
+/// 診断をSARIF 2.1.0形式に変換
+pub fn render_sarif(diagnostics: &[Diagnostic]) -> String {
+    let rules: Vec<Value> = DiagnosticCode::ALL
+        .iter()
+        .map(|code| {
+            json!({
+                "id": code.as_str(),
+                "shortDescription": { "text": code.description() },
+                "defaultConfiguration": { "level": code.severity().as_str() },
+            })
+        })
+        .collect();
+
+    let results: Vec<Value> = diagnostics.iter().map(sarif_result).collect();
+
+    let sarif: Value = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    });
+    serde_json::to_string_pretty(&sarif).unwrap()
+}
+
+/// 診断1件をSARIFのresultに変換
+fn sarif_result(diagnostic: &Diagnostic) -> Value {
+    let mut result: Value = json!({
+        "ruleId": diagnostic.code.as_str(),
+        "level": diagnostic.severity.as_str(),
+        "message": { "text": diagnostic.message },
+        "properties": {
+            "key": diagnostic.key,
+            "value": diagnostic.value,
+            "expected": diagnostic.expected,
+        },
+    });
+
+    if let Some(location) = &diagnostic.location {
+        result["locations"] = json!([{
+            "physicalLocation": {
+                "artifactLocation": { "uri": path_to_uri(&location.file) },
+                "region": {
+                    "startLine": location.line,
+                    "startColumn": location.column,
+                    "endColumn": location.end_column.max(location.column + 1),
+                }
+            }
+        }]);
+    }
+    result
+}
+
+/// ファイルパスをSARIFのURIに変換（相対パスは相対参照、絶対パスはfile URI）
+///
+/// 区切り文字以外はRFC 3986の非予約文字を除いてパーセントエンコードする
+fn path_to_uri(path: &Path) -> String {
+    let path: String = path.to_string_lossy().replace('\\', "/");
+    let encoded: String = path
+        .split('/')
+        .map(percent_encode)
+        .collect::<Vec<String>>()
+        .join("/");
+    if path.starts_with('/') {
+        format!("file://{}", encoded)
+    } else {
+        encoded
+    }
+}
+
+/// パスの部分をUTF-8のバイト単位でパーセントエンコード
+fn percent_encode(segment: &str) -> String {
+    let mut encoded: String = String::new();
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::config::Config;
+    use linux_conf_parser::core::diagnostic::{Diagnostic, DiagnosticCode, Location};
+    use linux_conf_parser::core::file_parser::{
+        parse_conf_bytes, parse_conf_entries, parse_conf_str, ConfEntry, ConfEntryRef,
+    };
+    use linux_conf_parser::i18n::{tr, Msg};
+    use std::borrow::Cow;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    const CONTENT: &str =
+        "# comment\nvm.swappiness = 10\n-kernel.panic=5\nbroken line\n  vm.swappiness =  60 \n";
+
+    /// メモリ上の内容をパースし、キーと値が入力を借用するテスト
+    #[test]
+    fn test_parse_conf_str_borrows_input() {
+        let origin: &Path = Path::new("image.tar:etc/sysctl.d/99.conf");
+        let (entries, warnings) = parse_conf_str(CONTENT, origin);
+
+        assert_eq!(entries.len(), 3);
+        for entry in &entries {
+            assert!(matches!(entry.key, Cow::Borrowed(_)));
+            assert!(matches!(entry.value, Cow::Borrowed(_)));
+        }
+        assert_eq!(
+            entries[1],
+            ConfEntryRef {
+                key: Cow::Borrowed("kernel.panic"),
+                value: Cow::Borrowed("5"),
+                line: 3,
+                column: 15,
+                ignore_failure: true,
+            }
+        );
+        assert_eq!((entries[2].line, entries[2].column), (5, 20));
+
+        // 警告の位置には`origin`を使用する
+        let codes: Vec<DiagnosticCode> = warnings.iter().map(|w| w.code).collect();
+        assert_eq!(
+            codes,
+            vec![
+                DiagnosticCode::MissingSeparator,
+                DiagnosticCode::DuplicateKey
+            ]
+        );
+        assert!(warnings
+            .iter()
+            .all(|w| w.location.as_ref().map(|l| l.file.as_path()) == Some(origin)));
+    }
+
+    /// ファイルのパースと同じエントリ・警告になるテスト
+    #[test]
+    fn test_parse_conf_str_matches_file() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_buffer");
+        fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("99.conf");
+        fs::write(&path, CONTENT.replace('\n', "\r\n")).unwrap();
+
+        let (file_entries, file_warnings) = parse_conf_entries(&path).unwrap();
+        let (entries, warnings) = parse_conf_str(CONTENT, &path);
+        let entries: Vec<ConfEntry> = entries.into_iter().map(ConfEntryRef::into_owned).collect();
+        assert_eq!(entries, file_entries);
+        assert_eq!(warnings, file_warnings);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// バイト列は入力を借用し、不正なUTF-8はファイルと同じく読み込みエラーとなるテスト
+    #[test]
+    fn test_parse_conf_bytes() {
+        let origin: &Path = Path::new("blob");
+        let (entries, _) = parse_conf_bytes(CONTENT.as_bytes(), origin).unwrap();
+        assert!(entries
+            .iter()
+            .all(|entry| matches!(entry.value, Cow::Borrowed(_))));
+
+        let input: &[u8] = b"net.ipv4.ip_forward = 1\nkernel.hostname = a\xffb\n";
+        let error: io::Error = parse_conf_bytes(input, origin).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            tr(Msg::InvalidUtf8, &[&origin.display(), &2])
+        );
+
+        // ファイルのパースも同じ方針
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_buffer_utf8");
+        fs::create_dir_all(&dir).unwrap();
+        let path: PathBuf = dir.join("invalid.conf");
+        fs::write(&path, input).unwrap();
+        assert_eq!(
+            parse_conf_entries(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 区切りの無い行の位置が、先頭のマルチバイトの空白も1文字として数えられるテスト
+    #[test]
+    fn test_missing_separator_column() {
+        let (_, warnings): (Vec<ConfEntryRef>, Vec<Diagnostic>) =
+            parse_conf_str("\u{3000}\u{3000}novalue\n", Path::new("blob"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DiagnosticCode::MissingSeparator);
+        let location = warnings[0].location.as_ref().unwrap();
+        assert_eq!((location.column, location.end_column), (3, 10));
+    }
+
+    /// 長すぎる値は処理を中断せず、位置付きのエラーの診断となるテスト
+    #[test]
+    fn test_parse_conf_str_value_too_long() {
+        let origin: &Path = Path::new("image.tar:etc/sysctl.d/99.conf");
+        let input: String = format!(
+            "vm.swappiness = 10\n  -kernel.x =  {}\n",
+            "A".repeat(Config::MAX_VALUE_LENGTH + 1)
+        );
+        let (entries, diagnostics) = parse_conf_str(&input, origin);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::ValueTooLong);
+        assert_eq!(diagnostics[0].code.as_str(), "E005");
+        let location: &Location = diagnostics[0].location.as_ref().unwrap();
+        assert_eq!(location.file, origin);
+        assert_eq!((location.line, location.column), (2, 16));
+
+        // 上限ちょうどの値は使用する
+        let input: String = format!("kernel.x = {}", "A".repeat(Config::MAX_VALUE_LENGTH));
+        let (entries, diagnostics) = parse_conf_str(&input, origin);
+        assert_eq!(entries.len(), 1);
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::Diagnostic;
+    use linux_conf_parser::core::directory_parser::DirectoryParser;
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use rustc_hash::FxHashMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// キャッシュを使用してディレクトリをパースする関数
+    fn parse_cached(
+        dir: &Path,
+        schema: &[(&str, &str)],
+    ) -> (ParseReport, FxHashMap<String, String>) {
+        let schema: FxHashMap<String, String> = schema
+            .iter()
+            .map(|(key, value_type)| (key.to_string(), value_type.to_string()))
+            .collect();
+        let options = ParseOptions {
+            verbose: false,
+            cache: Some(dir.join("cache/parse.json")),
+            ..ParseOptions::default()
+        };
+        let sysctl_d: String = dir.join("sysctl.d").to_string_lossy().to_string();
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        let report: ParseReport = DirectoryParser
+            .parse_all_conf_files_with_options(&[&sysctl_d], &schema, &mut result_map, &options)
+            .unwrap();
+        (report, result_map)
+    }
+
+    /// 変更されていないファイルはキャッシュを使用し、変更や型定義の変更で再パースするテスト
+    #[test]
+    fn test_parse_cache() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_cache");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        fs::write(
+            dir.join("sysctl.d/10-base.conf"),
+            "vm.swappiness = 60\nvm.swappiness = 30\n",
+        )
+        .unwrap();
+        fs::write(dir.join("sysctl.d/20-extra.conf"), "kernel.panic = 5\n").unwrap();
+        let schema: [(&str, &str); 2] = [("vm.swappiness", "int"), ("kernel.panic", "int")];
+
+        let (first, _) = parse_cached(&dir, &schema);
+        assert_eq!((first.files, first.cached_files), (2, 0));
+        assert!(dir.join("cache/parse.json").is_file());
+
+        // 2回目は全てキャッシュから読み込み、警告も同じ内容になる
+        let (second, result_map) = parse_cached(&dir, &schema);
+        assert_eq!((second.files, second.cached_files), (2, 2));
+        assert_eq!(second.diagnostics, first.diagnostics);
+        assert_eq!(second.sources, first.sources);
+        assert_eq!(
+            result_map.get("vm.swappiness").map(String::as_str),
+            Some("30")
+        );
+
+        // 変更したファイルのみ再パースする
+        fs::write(dir.join("sysctl.d/20-extra.conf"), "kernel.panic = 10\n").unwrap();
+        let (third, result_map) = parse_cached(&dir, &schema);
+        assert_eq!(third.cached_files, 1);
+        assert_eq!(
+            result_map.get("kernel.panic").map(String::as_str),
+            Some("10")
+        );
+
+        // 型定義が変わった場合はキャッシュを破棄する
+        let (fourth, _) = parse_cached(&dir, &[("vm.swappiness", "int")]);
+        assert_eq!(fourth.cached_files, 0);
+        assert!(fourth.diagnostics.iter().any(Diagnostic::is_error));
+
+        // 壊れたキャッシュファイルは無視する
+        fs::write(dir.join("cache/parse.json"), "{").unwrap();
+        let (fifth, _) = parse_cached(&dir, &schema);
+        assert_eq!((fifth.files, fifth.cached_files), (2, 0));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,184 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::cli::{Cli, Command, OutputFormat};
+    use std::path::PathBuf;
+
+    fn parse(args: &[&str]) -> Result<Cli, String> {
+        Cli::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    /// 引数なしの場合は本番想定のディレクトリで check を実行するテスト
+    #[test]
+    fn test_default_command() {
+        let cli: Cli = parse(&[]).unwrap();
+        assert_eq!(cli.command, Command::Check);
+        assert_eq!(cli.schema, PathBuf::from("schema.txt"));
+        assert_eq!(
+            cli.directories(),
+            vec![
+                "/etc/sysctl.d",
+                "/run/sysctl.d",
+                "/usr/local/lib/sysctl.d",
+                "/usr/lib/sysctl.d",
+                "/lib/sysctl.d",
+            ]
+        );
+    }
+
+    /// サブコマンドとオプションの解析テスト
+    #[test]
+    fn test_subcommands_and_flags() {
+        let cli: Cli = parse(&[
+            "dump",
+            "--dir",
+            "a",
+            "--dir=b",
+            "--format",
+            "flat",
+            "--schema",
+            "my_schema.txt",
+            "-q",
+        ])
+        .unwrap();
+        assert_eq!(cli.command, Command::Dump);
+        assert_eq!(cli.dirs, vec!["a", "b"]);
+        assert_eq!(cli.format, Some(OutputFormat::Flat));
+        assert_eq!(cli.schema, PathBuf::from("my_schema.txt"));
+        assert!(cli.quiet);
+
+        let cli: Cli = parse(&["schema", "init", "out.txt"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::SchemaInit {
+                output: PathBuf::from("out.txt")
+            }
+        );
+
+        // 旧来の `output` コマンドも使用可能
+        let cli: Cli = parse(&["output"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::SchemaInit {
+                output: PathBuf::from("output.txt")
+            }
+        );
+
+        let cli: Cli = parse(&["merge", "merged.conf", "--annotate"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Merge {
+                output: Some(PathBuf::from("merged.conf"))
+            }
+        );
+        assert!(cli.annotate);
+
+        let cli: Cli = parse(&["restore", "before.json", "--dry-run"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Restore {
+                snapshot: PathBuf::from("before.json")
+            }
+        );
+        assert!(cli.dry_run);
+
+        let cli: Cli = parse(&["-j", "4", "--cache", "cache.json"]).unwrap();
+        assert_eq!(cli.jobs, 4);
+        assert_eq!(cli.cache, Some(PathBuf::from("cache.json")));
+
+        let cli: Cli = parse(&["explain", "vm.swappiness"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Explain {
+                key: "vm.swappiness".to_string()
+            }
+        );
+    }
+
+    /// --root 指定時もディレクトリはルート内のパスとして保持されるテスト
+    #[test]
+    fn test_root_option() {
+        let cli: Cli = parse(&["--root", "/mnt/image", "--dir", "/etc/sysctl.d"]).unwrap();
+        assert_eq!(cli.root, Some(PathBuf::from("/mnt/image")));
+        assert_eq!(cli.directories(), vec!["/etc/sysctl.d"]);
+    }
+
+    /// --image は設定の読み込みのみを行うコマンドで --root と同時に指定しない場合のみ使用できるテスト
+    #[test]
+    fn test_image_option() {
+        let cli: Cli = parse(&["dump", "--image", "image.tar"]).unwrap();
+        assert_eq!(cli.image, Some(PathBuf::from("image.tar")));
+        assert!(parse(&["merge", "--image=image.tar"]).is_ok());
+        assert!(parse(&["--image", "image.tar", "--root", "/mnt/image"]).is_err());
+        assert!(parse(&["apply", "--image", "image.tar"]).is_err());
+        assert!(parse(&["explain", "vm.swappiness", "--image", "image.tar"]).is_err());
+    }
+
+    /// set・unset と --file、`-`で始まる値の解析テスト
+    #[test]
+    fn test_set_unset_commands() {
+        let cli: Cli = parse(&["set", "kernel.panic", "-1", "--file", "99-local.conf"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Set {
+                key: "kernel.panic".to_string(),
+                value: "-1".to_string()
+            }
+        );
+        assert_eq!(cli.file, Some(PathBuf::from("99-local.conf")));
+        let cli: Cli = parse(&["set", "--", "kernel.domainname", "-x"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Set {
+                key: "kernel.domainname".to_string(),
+                value: "-x".to_string()
+            }
+        );
+        let cli: Cli = parse(&["unset", "vm.swappiness"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Unset {
+                key: "vm.swappiness".to_string()
+            }
+        );
+
+        assert!(parse(&["set", "vm.swappiness"]).is_err());
+        assert!(parse(&["unset"]).is_err());
+        assert!(parse(&["set", "kernel.domainname", "-x"]).is_err());
+        assert!(parse(&["dump", "--file", "99-local.conf"]).is_err());
+        assert!(parse(&["set", "vm.swappiness", "10", "--image", "image.tar"]).is_err());
+    }
+
+    /// get（別名 query）と表示オプションの解析テスト
+    #[test]
+    fn test_get_command() {
+        let cli: Cli = parse(&["get", "net.ipv4", "--show-source", "--show-type"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Get {
+                key: "net.ipv4".to_string()
+            }
+        );
+        assert!(cli.show_source && cli.show_type);
+        let cli: Cli = parse(&["query", "vm", "--format", "json", "--image", "image.tar"]).unwrap();
+        assert_eq!(
+            cli.command,
+            Command::Get {
+                key: "vm".to_string()
+            }
+        );
+        assert!(parse(&["get"]).is_err());
+        assert!(parse(&["get", "vm", "--format", "yaml"]).is_err());
+    }
+
+    /// 不正な引数のエラーテスト
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["--dir"]).is_err());
+        assert!(parse(&["--format", "xml"]).is_err());
+        assert!(parse(&["explain"]).is_err());
+        assert!(parse(&["restore"]).is_err());
+        assert!(parse(&["--jobs", "many"]).is_err());
+        assert!(parse(&["check", "extra"]).is_err());
+    }
+}
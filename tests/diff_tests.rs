@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diff::{
+        diff_configs, load_resolved_config, ChangeKind, KeyChange, ResolvedConfig,
+    };
+    use linux_conf_parser::utils::format::{render_diff_human, render_diff_unified};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// テスト用のルート（etc/sysctl.d に設定ファイル）を作成する関数
+    fn setup_root(dir: &Path, name: &str, conf: &str) -> PathBuf {
+        let root: PathBuf = dir.join(name);
+        fs::create_dir_all(root.join("etc/sysctl.d")).unwrap();
+        fs::write(root.join("etc/sysctl.d/99-test.conf"), conf).unwrap();
+        root
+    }
+
+    fn load(path: &Path) -> ResolvedConfig {
+        let (config, diagnostics) = load_resolved_config(path, &["/etc/sysctl.d"]).unwrap();
+        assert!(diagnostics.is_empty());
+        config
+    }
+
+    /// 2つのルートの追加・削除・変更されたキーを検出するテスト
+    #[test]
+    fn test_diff_roots() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_diff_roots");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        let old: PathBuf = setup_root(
+            &dir,
+            "old",
+            "vm.swappiness = 10\nkernel.panic = 5\nfs.file-max = 100\n",
+        );
+        let new: PathBuf = setup_root(
+            &dir,
+            "new",
+            "vm.swappiness = 60\nfs.file-max = 100\nnet.ipv4.ip_forward = 1\n",
+        );
+
+        let changes: Vec<KeyChange> = diff_configs(&load(&old), &load(&new));
+        let summary: Vec<(&str, ChangeKind)> = changes
+            .iter()
+            .map(|change| (change.key.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("kernel.panic", ChangeKind::Removed),
+                ("net.ipv4.ip_forward", ChangeKind::Added),
+                ("vm.swappiness", ChangeKind::Changed),
+            ]
+        );
+
+        // 定義位置はルート内のファイルを指す
+        let swappiness: &KeyChange = &changes[2];
+        assert_eq!(swappiness.old_value.as_deref(), Some("10"));
+        assert_eq!(swappiness.new_value.as_deref(), Some("60"));
+        assert_eq!(
+            swappiness.new_source.as_ref().unwrap().file,
+            new.join("etc/sysctl.d/99-test.conf")
+        );
+
+        assert_eq!(
+            render_diff_unified(&changes, "old", "new"),
+            "--- old\n+++ new\n-kernel.panic = 5\n+net.ipv4.ip_forward = 1\n\
+             -vm.swappiness = 10\n+vm.swappiness = 60\n"
+        );
+        assert!(render_diff_human(&changes).starts_with(&format!(
+            "- kernel.panic = 5\n    old: {}:2\n",
+            old.join("etc/sysctl.d/99-test.conf").display()
+        )));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// JSON・key = value 形式の出力結果と比較できるテスト
+    #[test]
+    fn test_diff_snapshots() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_diff_snapshots");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("old.json"),
+            r#"{"log": {"_value": "x", "__value": "a", "file": "y"}, "net": {"ipv4": {"ip_local_port_range": [32768, 60999]}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("new.conf"),
+            "log = x\nlog._value = a\nlog.file = z\nnet.ipv4.ip_local_port_range = 32768 60999\n",
+        )
+        .unwrap();
+
+        let old: ResolvedConfig = load(&dir.join("old.json"));
+        assert_eq!(old.values.get("log").map(String::as_str), Some("x"));
+        // `_`を追加して出力したキーは元のキーに戻す
+        assert_eq!(old.values.get("log._value").map(String::as_str), Some("a"));
+        assert!(old.sources.is_empty());
+
+        let changes: Vec<KeyChange> = diff_configs(&old, &load(&dir.join("new.conf")));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key, "log.file");
+        assert_eq!(changes[0].new_source.as_ref().unwrap().line, 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::cli::OutputFormat;
+    use linux_conf_parser::core::diagnostic::{DiagnosticCode, Severity};
+    use linux_conf_parser::core::schema::validate_against_schema;
+    use linux_conf_parser::utils::display::{insert_nested, json_map_to_value};
+    use linux_conf_parser::utils::format::{
+        EnvRenderer, FlatRenderer, Renderer, TomlRenderer, YamlRenderer,
+    };
+    use rustc_hash::FxHashMap;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn map(entries: &[(&str, &str)]) -> FxHashMap<String, String> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    /// スキーマの型に応じてJSONの数値・真偽値・配列として出力されるテスト
+    #[test]
+    fn test_typed_json_values() {
+        let config = map(&[
+            ("vm.swappiness", "10"),
+            ("vm.dirty_ratio", "0.5"),
+            ("net.ipv4.tcp_syncookies", "true"),
+            ("net.ipv4.ip_local_port_range", "32768 60999"),
+            ("kernel.hostname", "server"),
+            ("kernel.untyped", "42"),
+            ("kernel.mismatch", "abc"),
+        ]);
+        let schema = map(&[
+            ("vm.swappiness", "int"),
+            ("vm.dirty_ratio", "float"),
+            ("net.ipv4.tcp_syncookies", "bool"),
+            ("net.ipv4.ip_local_port_range", "int[]"),
+            ("kernel.hostname", "string"),
+            ("kernel.mismatch", "int"),
+        ]);
+
+        assert_eq!(
+            json_map_to_value(&config, &schema),
+            json!({
+                "vm": { "swappiness": 10, "dirty_ratio": 0.5 },
+                "net": { "ipv4": {
+                    "tcp_syncookies": true,
+                    "ip_local_port_range": [32768, 60999],
+                } },
+                // スキーマに無いキーや型に一致しない値は文字列のまま
+                "kernel": { "hostname": "server", "untyped": "42", "mismatch": "abc" },
+            })
+        );
+    }
+
+    /// 配列型のスキーマ検証テスト
+    #[test]
+    fn test_validate_array_type() {
+        let schema = map(&[("net.ipv4.ip_local_port_range", "int[]")]);
+
+        let valid = map(&[("net.ipv4.ip_local_port_range", "32768\t60999")]);
+        assert!(validate_against_schema(&valid, &schema).is_ok());
+
+        let invalid = map(&[("net.ipv4.ip_local_port_range", "32768 high")]);
+        assert!(validate_against_schema(&invalid, &schema).is_err());
+
+        let empty = map(&[("net.ipv4.ip_local_port_range", "")]);
+        assert!(validate_against_schema(&empty, &schema).is_err());
+
+        // 要素の型がサポートされていない場合はエラー
+        let unsupported = map(&[("net.ipv4.ip_local_port_range", "1 2")]);
+        let schema = map(&[("net.ipv4.ip_local_port_range", "port[]")]);
+        let errors: String = validate_against_schema(&unsupported, &schema).unwrap_err();
+        assert!(errors.contains("port[]"));
+    }
+
+    /// キーが値とネストしたキーの両方を持つ場合に、入力の順序によらず値が失われないテスト
+    ///
+    /// 挿入順ごとの動作は`test_insert_nested_order`で確認する
+    #[test]
+    fn test_leaf_prefix_collision() {
+        let expected = json!({
+            "log": {
+                "_value": "x",
+                "file": { "_value": "y", "mode": "z" },
+            },
+        });
+        let no_schema: FxHashMap<String, String> = FxHashMap::default();
+
+        // 値 → ネストしたキーの順
+        let leaf_first = map(&[("log", "x"), ("log.file", "y"), ("log.file.mode", "z")]);
+        assert_eq!(json_map_to_value(&leaf_first, &no_schema), expected);
+
+        // ネストしたキー → 値の順
+        let prefix_first = map(&[("log.file.mode", "z"), ("log.file", "y"), ("log", "x")]);
+        assert_eq!(json_map_to_value(&prefix_first, &no_schema), expected);
+
+        // オブジェクトのキーはソートされて出力される
+        let output: String =
+            serde_json::to_string(&json_map_to_value(&prefix_first, &no_schema)).unwrap();
+        assert_eq!(
+            output,
+            r#"{"log":{"_value":"x","file":{"_value":"y","mode":"z"}}}"#
+        );
+    }
+
+    /// ネストしたキーを先に設定した場合も値が`_value`に格納されるテスト（挿入順を直接指定）
+    #[test]
+    fn test_insert_nested_order() {
+        let expected = json!({ "log": { "_value": "x", "file": "y" } });
+
+        // 値 → ネストしたキーの順（値をオブジェクトに移す）
+        let mut leaf_first = serde_json::Map::new();
+        insert_nested(&mut leaf_first, &["log"], json!("x"));
+        insert_nested(&mut leaf_first, &["log", "file"], json!("y"));
+        assert_eq!(serde_json::Value::Object(leaf_first), expected);
+
+        // ネストしたキー → 値の順（既存のオブジェクトに値を追加）
+        let mut prefix_first = serde_json::Map::new();
+        insert_nested(&mut prefix_first, &["log", "file"], json!("y"));
+        insert_nested(&mut prefix_first, &["log"], json!("x"));
+        assert_eq!(serde_json::Value::Object(prefix_first), expected);
+    }
+
+    /// `_value`で終わるキーが値を格納する予約フィールドと衝突しないテスト
+    #[test]
+    fn test_reserved_value_key() {
+        let config = map(&[
+            ("log", "x"),
+            ("log._value", "a"),
+            ("log.__value", "b"),
+            ("log.file", "y"),
+            ("log.my_value", "c"),
+        ]);
+        assert_eq!(
+            json_map_to_value(&config, &FxHashMap::default()),
+            json!({
+                "log": {
+                    "_value": "x",
+                    "__value": "a",
+                    "___value": "b",
+                    "file": "y",
+                    "my_value": "c",
+                },
+            })
+        );
+    }
+
+    /// 各出力形式がキーをソートして出力するテスト
+    #[test]
+    fn test_renderers() {
+        let config = map(&[
+            ("net.ipv4.ip_forward", "1"),
+            ("net.ipv4.ip_local_port_range", "32768 60999"),
+            ("kernel.core_pattern", "|/usr/lib/core %p"),
+            ("kernel.hostname", "web-01"),
+        ]);
+        let schema = map(&[
+            ("net.ipv4.ip_forward", "int"),
+            ("net.ipv4.ip_local_port_range", "int[]"),
+        ]);
+
+        assert_eq!(
+            FlatRenderer.render(&config, &schema),
+            "kernel.core_pattern = |/usr/lib/core %p\n\
+             kernel.hostname = web-01\n\
+             net.ipv4.ip_forward = 1\n\
+             net.ipv4.ip_local_port_range = 32768 60999\n"
+        );
+        assert_eq!(
+            YamlRenderer.render(&config, &schema),
+            "kernel:\n  core_pattern: \"|/usr/lib/core %p\"\n  hostname: web-01\n\
+             net:\n  ipv4:\n    ip_forward: 1\n    ip_local_port_range: [32768, 60999]\n"
+        );
+        assert_eq!(
+            TomlRenderer.render(&config, &schema),
+            "[kernel]\ncore_pattern = \"|/usr/lib/core %p\"\nhostname = \"web-01\"\n\n\
+             [net.ipv4]\nip_forward = 1\nip_local_port_range = [32768, 60999]\n"
+        );
+        assert_eq!(
+            EnvRenderer.render(&config, &schema),
+            "KERNEL_CORE_PATTERN='|/usr/lib/core %p'\n\
+             KERNEL_HOSTNAME=web-01\n\
+             NET_IPV4_IP_FORWARD=1\n\
+             NET_IPV4_IP_LOCAL_PORT_RANGE='32768 60999'\n"
+        );
+
+        // CLIの出力形式から同じ出力形式を取得できる
+        let renderer = OutputFormat::Env.renderer().unwrap();
+        assert_eq!(
+            renderer.render(&config, &schema),
+            EnvRenderer.render(&config, &schema)
+        );
+        assert!(OutputFormat::Sarif.renderer().is_none());
+    }
+
+    /// YAML 1.1で数値として解釈される文字列を引用符で囲むテスト
+    #[test]
+    fn test_yaml_numeric_like_strings() {
+        let config = map(&[
+            ("a.hex", "0x10"),
+            ("a.binary", "0b101"),
+            ("a.underscore", "1_000"),
+            ("a.version", "1.2.3"),
+            ("a.positive_inf", ".inf"),
+            ("a.negative_inf", "-.Inf"),
+            ("a.not_a_number", ".NaN"),
+            ("a.name", "eth0"),
+        ]);
+        assert_eq!(
+            YamlRenderer.render(&config, &FxHashMap::default()),
+            "a:\n  binary: \"0b101\"\n  hex: \"0x10\"\n  name: eth0\n  negative_inf: \"-.Inf\"\n  \
+             not_a_number: \".NaN\"\n  positive_inf: \".inf\"\n  underscore: \"1_000\"\n  version: \"1.2.3\"\n"
+        );
+    }
+
+    /// 同じ環境変数名になるキーを警告とし、キーの順で最初のキーのみを出力するテスト
+    #[test]
+    fn test_env_name_collision() {
+        let config = map(&[("a_b", "2"), ("a-b", "1"), ("a.c", "3")]);
+        let schema = FxHashMap::default();
+        assert_eq!(EnvRenderer.render(&config, &schema), "A_B=1\nA_C=3\n");
+
+        let diagnostics = EnvRenderer.diagnostics(&config, &BTreeMap::new());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::EnvNameCollision);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].key.as_deref(), Some("a_b"));
+        assert_eq!(diagnostics[0].value.as_deref(), Some("2"));
+
+        // 他の形式では警告とならない
+        assert!(FlatRenderer
+            .diagnostics(&config, &BTreeMap::new())
+            .is_empty());
+    }
+}
@@ -0,0 +1,364 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::DiagnosticCode;
+    use linux_conf_parser::core::edit::{
+        edit_target, is_vendor_file, set_in_file, setting_files, unset_in_file, write_atomic,
+        EditOutcome,
+    };
+    use linux_conf_parser::core::explain::{explain_key, KeyExplanation};
+    use rustc_hash::FxHashMap;
+    use serde_json::Value;
+    use std::fs;
+    use std::io;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Output};
+
+    /// テストごとの作業ディレクトリを作成する関数
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_edit_{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// スキーマを読み込めない場合は検証せずに設定状況を表示するテスト
+    #[test]
+    fn test_explain_without_schema() {
+        let dir: PathBuf = temp_dir("explain_without_schema");
+        fs::write(dir.join("10-a.conf"), "vm.swappiness = 10\n").unwrap();
+        let directory: String = dir.to_string_lossy().to_string();
+
+        let schema: FxHashMap<String, String> = FxHashMap::default();
+        let explanation: KeyExplanation =
+            explain_key("vm.swappiness", &[directory.as_str()], None, Some(&schema)).unwrap();
+        assert!(explanation.validation.is_err());
+        let explanation: KeyExplanation =
+            explain_key("vm.swappiness", &[directory.as_str()], None, None).unwrap();
+        assert_eq!(explanation.validation, Ok(()));
+        assert_eq!(explanation.expected_type, None);
+
+        let output: Output = Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+            .args(["--lang", "en", "--dir", &directory, "--schema"])
+            .arg(dir.join("missing.txt"))
+            .args(["explain", "vm.swappiness", "--format", "json"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value["value"], "10");
+        assert_eq!(value["valid"], true);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 読み込めないファイルがあっても残りのファイルを調べ、読み込みエラーを記録するテスト
+    #[test]
+    fn test_explain_skips_unreadable_file() {
+        let dir: PathBuf = temp_dir("explain_unreadable");
+        fs::write(dir.join("10-a.conf"), "vm.swappiness = 10\n").unwrap();
+        // UTF-8として正しくないファイルは読み込みエラーとなる
+        fs::write(dir.join("20-bad.conf"), b"vm.swappiness = \xff\n").unwrap();
+        fs::write(dir.join("30-c.conf"), "vm.swappiness = 30\n").unwrap();
+        let directory: String = dir.to_string_lossy().to_string();
+
+        let explanation: KeyExplanation =
+            explain_key("vm.swappiness", &[directory.as_str()], None, None).unwrap();
+        assert_eq!(explanation.occurrences.len(), 2);
+        assert_eq!(explanation.effective_value(), Some("30"));
+        assert_eq!(explanation.diagnostics.len(), 1);
+        assert_eq!(explanation.diagnostics[0].code, DiagnosticCode::ReadError);
+        assert!(explanation.diagnostics[0].message.contains("20-bad.conf"));
+
+        // 編集先は決定できないためエラーとなる
+        assert!(setting_files("vm.swappiness", &[directory.as_str()], None).is_err());
+
+        // 結果を表示し、読み込みエラーとして 4 で終了
+        let output: Output = Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+            .args([
+                "--lang",
+                "en",
+                "--dir",
+                &directory,
+                "explain",
+                "vm.swappiness",
+            ])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(4));
+        assert!(String::from_utf8_lossy(&output.stdout).contains("30-c.conf = 30"));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("20-bad.conf"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// コメントと書式を保持したまま値を設定・削除するテスト
+    #[test]
+    fn test_set_and_unset_in_file() {
+        let dir: PathBuf = temp_dir("file");
+        let path: PathBuf = dir.join("99-custom.conf");
+        let content: &str = "# tuning\r\nvm.swappiness=60 \r\n\r\n-kernel.panic = 5\r\n";
+        fs::write(&path, content).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+
+        let outcome: EditOutcome = set_in_file(&path, "vm.swappiness", "10").unwrap();
+        assert_eq!(outcome.previous.as_deref(), Some("60"));
+        assert!(outcome.changed);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            content.replace("=60", "=10")
+        );
+        // 権限は引き継がれ、一時ファイルは残らない
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o640
+        );
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+        // 値が変わらない場合は書き込まない
+        assert!(!set_in_file(&path, "vm.swappiness", "10").unwrap().changed);
+
+        set_in_file(&path, "net.ipv4.ip_forward", "1").unwrap();
+        let outcome: EditOutcome = unset_in_file(&path, "kernel.panic").unwrap();
+        assert_eq!(outcome.previous.as_deref(), Some("5"));
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "# tuning\r\nvm.swappiness=10 \r\n\r\nnet.ipv4.ip_forward = 1\r\n"
+        );
+        assert!(!unset_in_file(&path, "kernel.panic").unwrap().changed);
+
+        // 記述できない値は書き込まない
+        let error: io::Error = set_in_file(&path, "vm.swappiness", "1\n2").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+
+        // 存在しないファイルは作成し、シンボリックリンクはリンク先を更新する
+        let created: PathBuf = dir.join("sub/new.conf");
+        let outcome: EditOutcome = set_in_file(&created, "a", "1").unwrap();
+        assert_eq!(outcome.previous, None);
+        assert_eq!(fs::read_to_string(&created).unwrap(), "a = 1\n");
+        let link: PathBuf = dir.join("link.conf");
+        symlink(&created, &link).unwrap();
+        write_atomic(&link, b"a = 2\n").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&created).unwrap(), "a = 2\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 編集するファイルの決定（有効な値のファイル・ドロップイン・指定されたファイル）のテスト
+    #[test]
+    fn test_edit_target() {
+        let dir: PathBuf = temp_dir("target");
+        fs::create_dir_all(dir.join("etc/sysctl.d")).unwrap();
+        fs::create_dir_all(dir.join("usr/lib/sysctl.d")).unwrap();
+        fs::write(dir.join("etc/sysctl.d/60-a.conf"), "vm.dirty_ratio = 10\n").unwrap();
+        fs::write(
+            dir.join("usr/lib/sysctl.d/50-b.conf"),
+            "vm.swappiness = 60\nvm.dirty_ratio = 20\n",
+        )
+        .unwrap();
+        let directories: [&str; 2] = ["/usr/lib/sysctl.d", "/etc/sysctl.d"];
+        let root: Option<&Path> = Some(&dir);
+
+        // 有効な値を設定しているファイルを編集する
+        assert_eq!(
+            edit_target("vm.dirty_ratio", &directories, root, None).unwrap(),
+            dir.join("etc/sysctl.d/60-a.conf")
+        );
+        // ベンダーのファイルは編集せず、ベンダー以外の最初のディレクトリのドロップインに設定する
+        assert_eq!(
+            edit_target("vm.swappiness", &directories, root, None).unwrap(),
+            dir.join("etc/sysctl.d/99-local.conf")
+        );
+        assert!(is_vendor_file(
+            &dir.join("usr/lib/sysctl.d/50-b.conf"),
+            root
+        ));
+        // 設定されていないキーも同じドロップイン
+        assert_eq!(
+            edit_target("kernel.panic", &directories, root, None).unwrap(),
+            dir.join("etc/sysctl.d/99-local.conf")
+        );
+        // ベンダーのファイルを指定した場合はエラー
+        let error: io::Error = edit_target(
+            "vm.swappiness",
+            &directories,
+            root,
+            Some(Path::new("/usr/lib/sysctl.d/50-b.conf")),
+        )
+        .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        // 指定されたファイルはルート配下で解決
+        assert_eq!(
+            edit_target(
+                "vm.swappiness",
+                &directories,
+                root,
+                Some(Path::new("/etc/sysctl.d/../sysctl.d/20-x.conf"))
+            )
+            .unwrap(),
+            dir.join("etc/sysctl.d/20-x.conf")
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// set・unset コマンドのスキーマ検証・書き込み・警告のテスト
+    #[test]
+    fn test_set_unset_commands() {
+        let dir: PathBuf = temp_dir("cli");
+        fs::create_dir_all(dir.join("a")).unwrap();
+        fs::create_dir_all(dir.join("b")).unwrap();
+        fs::write(dir.join("a/10-a.conf"), "# local\nvm.swappiness = 10\n").unwrap();
+        fs::write(dir.join("b/50-b.conf"), "kernel.panic=5\n").unwrap();
+        fs::write(
+            dir.join("schema.txt"),
+            "vm.swappiness -> int\nkernel.panic -> int\nnet.ipv4.ip_forward -> bool\n",
+        )
+        .unwrap();
+
+        let run = |args: &[&str]| -> Output {
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["--lang", "en", "--schema"])
+                .arg(dir.join("schema.txt"))
+                .arg("--dir")
+                .arg(dir.join("a"))
+                .arg("--dir")
+                .arg(dir.join("b"))
+                .args(args)
+                .output()
+                .expect("バイナリの実行に失敗しました")
+        };
+        let stdout = |output: &Output| String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = |output: &Output| String::from_utf8_lossy(&output.stderr).to_string();
+
+        // 有効な値を設定しているファイルを編集する
+        let output: Output = run(&["set", "vm.swappiness", "30"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        assert!(stdout(&output).contains("previous value: 10"));
+        assert_eq!(
+            fs::read_to_string(dir.join("a/10-a.conf")).unwrap(),
+            "# local\nvm.swappiness = 30\n"
+        );
+
+        // スキーマに一致しない値・未定義のキーは書き込まない
+        let output: Output = run(&["set", "vm.swappiness", "high"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(stderr(&output).contains("vm.swappiness"));
+        assert_eq!(run(&["set", "vm.unknown", "1"]).status.code(), Some(1));
+        assert_eq!(
+            fs::read_to_string(dir.join("a/10-a.conf")).unwrap(),
+            "# local\nvm.swappiness = 30\n"
+        );
+
+        // 設定されていないキーは最初のディレクトリのドロップインに追加
+        let output: Output = run(&["set", "net.ipv4.ip_forward", "true"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        assert_eq!(
+            fs::read_to_string(dir.join("a/99-local.conf")).unwrap(),
+            "net.ipv4.ip_forward = true\n"
+        );
+
+        // 優先されるファイルで上書きされる場合は警告
+        let a_file: String = dir.join("a/10-a.conf").to_string_lossy().to_string();
+        let output: Output = run(&["set", "kernel.panic", "-1", "--file", &a_file]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(
+            stderr(&output).contains("takes precedence"),
+            "{}",
+            stderr(&output)
+        );
+        let output: Output = run(&[
+            "set",
+            "kernel.panic",
+            "10",
+            "--file",
+            &a_file,
+            "--fail-on-warnings",
+        ]);
+        assert_eq!(output.status.code(), Some(3));
+
+        // 全てのファイルから削除
+        let output: Output = run(&["unset", "kernel.panic"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        assert_eq!(fs::read_to_string(dir.join("b/50-b.conf")).unwrap(), "");
+        assert_eq!(
+            fs::read_to_string(dir.join("a/99-local.conf")).unwrap(),
+            "net.ipv4.ip_forward = true\n"
+        );
+        let output: Output = run(&["unset", "kernel.panic"]);
+        assert!(stdout(&output).contains("No file sets kernel.panic"));
+
+        // --file 指定時はそのファイルのみ、他のファイルに残る場合は警告
+        fs::write(dir.join("b/60-c.conf"), "vm.swappiness = 40\n").unwrap();
+        let output: Output = run(&["unset", "vm.swappiness", "--file", &a_file]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(stderr(&output).contains("is still set to 40"));
+        assert_eq!(
+            fs::read_to_string(dir.join("a/10-a.conf")).unwrap(),
+            "# local\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// --root 配下のベンダーのファイルを set・unset で書き換えないテスト
+    #[test]
+    fn test_set_unset_keep_vendor_files() {
+        let dir: PathBuf = temp_dir("vendor");
+        let root: PathBuf = dir.join("root");
+        fs::create_dir_all(root.join("usr/lib/sysctl.d")).unwrap();
+        fs::write(root.join("usr/lib/sysctl.d/x.conf"), "vm.swappiness = 60\n").unwrap();
+        fs::write(dir.join("schema.txt"), "vm.swappiness -> int\n").unwrap();
+
+        let run = |args: &[&str]| -> Output {
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["--lang", "en", "--schema"])
+                .arg(dir.join("schema.txt"))
+                .arg("--root")
+                .arg(&root)
+                .args(args)
+                .output()
+                .expect("バイナリの実行に失敗しました")
+        };
+        let stderr = |output: &Output| String::from_utf8_lossy(&output.stderr).to_string();
+
+        // ベンダーのファイルより後に読み込まれる /etc/sysctl.d のドロップインで上書きする
+        let output: Output = run(&["set", "vm.swappiness", "9"]);
+        assert_eq!(output.status.code(), Some(0), "{}", stderr(&output));
+        assert_eq!(stderr(&output), "");
+        assert_eq!(
+            fs::read_to_string(root.join("etc/sysctl.d/x.local.conf")).unwrap(),
+            "vm.swappiness = 9\n"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("usr/lib/sysctl.d/x.conf")).unwrap(),
+            "vm.swappiness = 60\n"
+        );
+
+        // ベンダーのファイルは指定しても編集しない
+        let output: Output = run(&[
+            "set",
+            "vm.swappiness",
+            "5",
+            "--file",
+            "/usr/lib/sysctl.d/x.conf",
+        ]);
+        assert_eq!(output.status.code(), Some(2));
+        assert!(stderr(&output).contains("vendor file"));
+
+        // ドロップインからのみ削除し、ベンダーの値が残ることを警告
+        let output: Output = run(&["unset", "vm.swappiness"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert!(stderr(&output).contains("is still set to 60"));
+        assert_eq!(
+            fs::read_to_string(root.join("etc/sysctl.d/x.local.conf")).unwrap(),
+            ""
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("usr/lib/sysctl.d/x.conf")).unwrap(),
+            "vm.swappiness = 60\n"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// テスト用のディレクトリ（設定ファイルと型定義ファイル）を作成する関数
+    fn setup_test_dir(name: &str, conf: &str, schema: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_exit_{}", name));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        fs::write(dir.join("sysctl.d/99-test.conf"), conf).unwrap();
+        fs::write(dir.join("schema.txt"), schema).unwrap();
+        dir
+    }
+
+    /// バイナリを実行して終了コードを返す関数
+    fn run(dir: &Path, extra_args: &[&str]) -> i32 {
+        let output = Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+            .arg("--quiet")
+            .arg("--dir")
+            .arg(dir.join("sysctl.d"))
+            .arg("--schema")
+            .arg(dir.join("schema.txt"))
+            .args(extra_args)
+            .output()
+            .expect("バイナリの実行に失敗しました");
+        output.status.code().expect("終了コードがありません")
+    }
+
+    /// 検証に成功した場合は 0 で終了するテスト
+    #[test]
+    fn test_exit_success() {
+        let dir: PathBuf =
+            setup_test_dir("success", "vm.swappiness = 10\n", "vm.swappiness -> int\n");
+        assert_eq!(run(&dir, &[]), 0);
+        assert_eq!(run(&dir, &["--fail-on-warnings"]), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// スキーマ検証に失敗した場合は 1 で終了するテスト
+    #[test]
+    fn test_exit_validation_failure() {
+        let dir: PathBuf = setup_test_dir(
+            "validation",
+            "vm.swappiness = 10.1\n",
+            "vm.swappiness -> int\n",
+        );
+        assert_eq!(run(&dir, &[]), 1);
+        assert_eq!(run(&dir, &["dump"]), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 不正な引数の場合は 2 で終了するテスト
+    #[test]
+    fn test_exit_usage_error() {
+        let dir: PathBuf =
+            setup_test_dir("usage", "vm.swappiness = 10\n", "vm.swappiness -> int\n");
+        assert_eq!(run(&dir, &["--no-such-option"]), 2);
+        assert_eq!(run(&dir, &["no-such-command"]), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 警告のみの場合は --fail-on-warnings 指定時のみ 3 で終了するテスト
+    #[test]
+    fn test_exit_warnings_only() {
+        let dir: PathBuf = setup_test_dir(
+            "warnings",
+            "vm.swappiness = 10\nthis line has no separator\n",
+            "vm.swappiness -> int\n",
+        );
+        assert_eq!(run(&dir, &[]), 0);
+        assert_eq!(run(&dir, &["--fail-on-warnings"]), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 型定義ファイルが読み込めない場合は 4 で終了するテスト
+    #[test]
+    fn test_exit_io_failure() {
+        let dir: PathBuf = setup_test_dir("io", "vm.swappiness = 10\n", "vm.swappiness -> int\n");
+        fs::remove_file(dir.join("schema.txt")).unwrap();
+        assert_eq!(run(&dir, &[]), 4);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
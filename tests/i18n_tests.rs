@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::cli::Cli;
+    use linux_conf_parser::i18n::{lang, set_lang, tr, Lang, Msg};
+    use std::process::Command;
+
+    /// 言語名・ロケール名から言語を判定するテスト
+    #[test]
+    fn test_lang_parse() {
+        assert_eq!(Lang::parse("ja"), Some(Lang::Ja));
+        assert_eq!(Lang::parse("ja_JP.UTF-8"), Some(Lang::Ja));
+        assert_eq!(Lang::parse("en"), Some(Lang::En));
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse("C"), Some(Lang::En));
+        assert_eq!(Lang::parse("fr_FR.UTF-8"), None);
+
+        let cli: Cli = Cli::parse(["--lang", "en"].iter().map(|arg| arg.to_string())).unwrap();
+        assert_eq!(cli.lang, Some(Lang::En));
+        assert!(Cli::parse(["--lang", "fr"].iter().map(|arg| arg.to_string())).is_err());
+    }
+
+    /// 言語を切り替えてメッセージを取得するテスト（グローバルな状態を変更するため1つのテストにまとめる）
+    #[test]
+    fn test_tr_switches_language() {
+        assert_eq!(lang(), Lang::Ja);
+        assert_eq!(
+            tr(Msg::UnknownKey, &[&"vm.swappiness"]),
+            "キー 'vm.swappiness' はスキーマに存在しません。"
+        );
+
+        set_lang(Lang::En);
+        assert_eq!(
+            tr(Msg::UnknownKey, &[&"vm.swappiness"]),
+            "Key 'vm.swappiness' is not defined in the schema."
+        );
+        // 引数の順序は言語ごとに異なってもよい
+        assert_eq!(
+            tr(Msg::TypeMismatch, &[&"a.b", &"x", &"int"]),
+            "The value 'x' of key 'a.b' has the wrong type. Expected type: 'int'"
+        );
+        set_lang(Lang::Ja);
+    }
+
+    /// --lang と環境変数でメッセージの言語が切り替わり、診断コードは変わらないテスト
+    #[test]
+    fn test_binary_language_selection() {
+        let run = |lang_env: &str, args: &[&str]| -> String {
+            let output = Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .env_remove("LC_ALL")
+                .env_remove("LC_MESSAGES")
+                .env("LANG", lang_env)
+                .args(args)
+                .output()
+                .expect("バイナリの実行に失敗しました");
+            String::from_utf8_lossy(&output.stderr).to_string()
+        };
+
+        let english: String = run("en_US.UTF-8", &["--unknown"]);
+        assert!(english.contains("Unknown option '--unknown'."));
+        assert!(english.contains("Usage: linux-conf-parser"));
+
+        let japanese: String = run("ja_JP.UTF-8", &["--unknown"]);
+        assert!(japanese.contains("不明なオプション '--unknown' が指定されました。"));
+
+        // --lang は環境変数より優先される
+        let dir = std::env::temp_dir().join("linux_conf_parser_i18n");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("99-test.conf"), "vm.swappiness = abc\n").unwrap();
+        std::fs::write(dir.join("schema.txt"), "vm.swappiness -> int\n").unwrap();
+        let schema = dir.join("schema.txt");
+        let output: String = run(
+            "ja_JP.UTF-8",
+            &[
+                "--quiet",
+                "--lang",
+                "en",
+                "--dir",
+                dir.to_str().unwrap(),
+                "--schema",
+                schema.to_str().unwrap(),
+            ],
+        );
+        assert!(output.contains("error[E001]"));
+        assert!(output.contains("expected type 'int'"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,411 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::directory_parser::SourceParser;
+    use linux_conf_parser::core::gzip::{decompress_gzip, decompress_gzip_with_limit};
+    use linux_conf_parser::core::image::{apply_layer, load_image};
+    use linux_conf_parser::core::vfs::{FileKind, FileSource, MemoryFs};
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use rustc_hash::FxHashMap;
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// 固定ハフマン符号のブロックのみのgzip（"a = 1\n"）
+    const FIXED_GZIP: &str = "1f8b08000000000002034b54b05530e4020068da2faf06000000";
+    /// 動的ハフマン符号のブロックを含むgzip（`dynamic_text`の内容）
+    const DYNAMIC_GZIP: &str = concat!(
+        "1f8b080000000000020355963b721c310c44739f4227701100bf818fa348558a9cf8f6261adae9de90bb33e4",
+        "1bfc1ebf3ffffefefafcd73efe7cb45fdfb5b0bbb0d7c2efa2bf167117e7b5e8f9d87cadc65df978ade65dc5",
+        "f3dfca3d9ef7f65dcd67cb7357fb39cd12c41a51c0e2fc3f71ac3f6f5b00e2d9db00759e930d58e4b2094cfe",
+        "9f68bef97ec28573ff830f613492af93cf93af77fe8f70ede77d4fbee1cffe9e7c633de77bf24df279f24df9",
+        "3ff996bc9f7c4bf647f8787e24df215f24df217f207e8d1f18086063040211348628065240c698f881418e84",
+        "b46016625796784a62da601e3bf23c09da91e8c94ae8205d2c940ed2cd3aea956b965947b21b493bb26d2cd2",
+        "8e747be31e49ead1784a927a6f0fc768a818928e24f5294f24a92fee310245c5534692fa916649d210d28186",
+        "317ecb585588dc0395198cc7406976466c36d42a496792c662d46792c6665e6692c661e666477d33b73349bb",
+        "647f26690fd6c744777756d04cd23e59633349fb62152e749194e94ad2d158c72b4987b1d0171a29d8090b9d",
+        "d4d92a2b4987f4d29ae835792249c7913d308a9a9c72d08ee4d80dc38aa43b49e7e0b76c47c7f26b7792cecd",
+        "78ec245d8d11db034d4dd29da42b18f58db61fcccb46df2f666e27e93acced6918a4243d49ba9df5711cb342",
+        "466f92eec91a3b49ba37abf00c8c13929e24bda7f289243d9db57e36260ebbe124e9d94d66fccf909731dfac",
+        "e6923e55a3aa73276b35ac264fbbffd7fc2291350c2c13eafb0a7ef290a730b4ee9092bd30b66c4e391183cb",
+        "b618aa14e56f922a4b8578aa44e5435455aef225b62a5df91161c15816ea2c48cba28bb6ec67e48ab9a02e8b",
+        "23f282bdacdb147fb61acd420f87d9ad0b79aa34bbd9190693d930768f416636821d66f09969171a946663eb",
+        "53a09f6f7b817e869e08faa95c51da90c961309c2dfdc692dcea1289d2dcd27895e8d691a896eab6c6be6477",
+        "ad234f817e6b1e4b78a7e9c5e4949da4264a7a4726b495f68ed617c4771b41aa10eaf3a6b50af9795b52d1a5",
+        "bfb7baff1160487794024d7ba82498f8fc091a74edc712a19ad04a85aebd5d320c93bd4a872133c24a88b184",
+        "0b4abce2157a48d17bc837428bde656619c4784529f1821a7dc8ecb3591ad7d8438f3e64861a04e95700dc0b",
+        "8af429b3d82049bfb5442e68d2d7db4d13f46b487d4195bec40d0659fa2d393911f45b1c6310e6bda60a3d94",
+        "e9475c6590a69f213d046ddef9229d0671debb87f4e3aaeb88762de419ede853abee28ba172e25f723e4445c",
+        "4b5c5c6e90e8bdcb083d341a2e77028348234c2201954674bd9f833eb644153a0dbda318847a07a664084a8d",
+        "2e771d83546384641b5a8d2177263b75add2790fb5deab98d417e47ae7b15421f47aaf5f52ab10ec9d9752d1",
+        "506c6cad7b48f64e42e90e6836b6dc290da2bde35e3a0daabdd788db8fff01c64d5485d80d0000",
+    );
+
+    /// tarアーカイブのエントリ（パス・種類・内容・リンク先）
+    type Entry<'a> = (&'a str, u8, &'a [u8], &'a str);
+
+    fn dynamic_text() -> String {
+        (0..200)
+            .map(|i| format!("net.key{} = {}\n", i, i * i))
+            .collect()
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// ustar形式のtarアーカイブを作成する関数
+    fn build_tar(entries: &[Entry]) -> Vec<u8> {
+        let mut archive: Vec<u8> = Vec::new();
+        for (path, entry_type, data, link) in entries {
+            let mut header: Vec<u8> = vec![0; 512];
+            header[..path.len()].copy_from_slice(path.as_bytes());
+            header[100..108].copy_from_slice(b"0000644\0");
+            header[124..136].copy_from_slice(format!("{:011o}\0", data.len()).as_bytes());
+            header[148..156].copy_from_slice(b"        ");
+            header[156] = *entry_type;
+            header[157..157 + link.len()].copy_from_slice(link.as_bytes());
+            header[257..263].copy_from_slice(b"ustar\0");
+            let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+            header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+            archive.extend(header);
+            archive.extend_from_slice(data);
+            archive.resize(archive.len().div_ceil(512) * 512, 0);
+        }
+        archive.extend(vec![0; 1024]);
+        archive
+    }
+
+    /// 無圧縮のブロックのみのgzipを作成する関数
+    fn gzip_stored(data: &[u8]) -> Vec<u8> {
+        let crc: u32 = !data.iter().fold(!0u32, |mut crc, byte| {
+            crc ^= u32::from(*byte);
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    0xedb8_8320 ^ (crc >> 1)
+                } else {
+                    crc >> 1
+                };
+            }
+            crc
+        });
+        let mut output: Vec<u8> = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 3];
+        let chunks: Vec<&[u8]> = data.chunks(65535).collect();
+        for (index, chunk) in chunks.iter().enumerate() {
+            output.push(u8::from(index + 1 == chunks.len()));
+            let length: u16 = chunk.len() as u16;
+            output.extend(length.to_le_bytes());
+            output.extend((!length).to_le_bytes());
+            output.extend_from_slice(chunk);
+        }
+        output.extend(crc.to_le_bytes());
+        output.extend((data.len() as u32).to_le_bytes());
+        output
+    }
+
+    /// ベースとなるレイヤー
+    fn base_layer() -> Vec<u8> {
+        build_tar(&[
+            ("etc/", b'5', b"", ""),
+            ("etc/sysctl.d/", b'5', b"", ""),
+            (
+                "etc/sysctl.d/10-base.conf",
+                b'0',
+                b"vm.swappiness = 60\n",
+                "",
+            ),
+            (
+                "etc/sysctl.d/20-remove.conf",
+                b'0',
+                b"kernel.panic = 5\n",
+                "",
+            ),
+            (
+                "usr/lib/sysctl.d/50-default.conf",
+                b'0',
+                b"net.ipv4.ip_forward = 0\n",
+                "",
+            ),
+            (
+                "usr/lib/sysctl.d/60-old.conf",
+                b'0',
+                b"fs.file-max = 1000\n",
+                "",
+            ),
+            ("usr/bin/tool", b'0', b"binary", ""),
+            ("lib", b'2', b"", "usr/lib"),
+        ])
+    }
+
+    /// 上位のレイヤー（ホワイトアウトと上書き）
+    fn top_layer() -> Vec<u8> {
+        build_tar(&[
+            ("etc/sysctl.d/.wh.20-remove.conf", b'0', b"", ""),
+            (
+                "etc/sysctl.d/10-base.conf",
+                b'0',
+                b"vm.swappiness = 10\n",
+                "",
+            ),
+            ("usr/lib/sysctl.d/.wh..wh..opq", b'0', b"", ""),
+            (
+                "usr/lib/sysctl.d/70-new.conf",
+                b'0',
+                b"net.ipv4.ip_forward = 1\n",
+                "",
+            ),
+        ])
+    }
+
+    /// イメージのファイルシステムの標準の探索ディレクトリをパースする関数
+    fn parse_image(files: MemoryFs) -> (ParseReport, FxHashMap<String, String>) {
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            ..ParseOptions::default()
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        let report: ParseReport = SourceParser::new(files)
+            .parse_all_conf_files_with_options(
+                &["/etc/sysctl.d", "/usr/lib/sysctl.d", "/lib/sysctl.d"],
+                &FxHashMap::default(),
+                &mut result_map,
+                &options,
+            )
+            .unwrap();
+        (report, result_map)
+    }
+
+    /// レイヤーを適用した結果の設定を確認する関数
+    fn assert_layered_config(files: MemoryFs) {
+        let (report, result_map) = parse_image(files);
+        assert_eq!(report.files, 2);
+        let mut keys: Vec<(&str, &str)> = result_map
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            vec![("net.ipv4.ip_forward", "1"), ("vm.swappiness", "10")]
+        );
+    }
+
+    /// gzip（無圧縮・固定ハフマン符号・動的ハフマン符号）の展開のテスト
+    #[test]
+    fn test_decompress_gzip() {
+        assert_eq!(decompress_gzip(&from_hex(FIXED_GZIP)).unwrap(), b"a = 1\n");
+        assert_eq!(
+            decompress_gzip(&from_hex(DYNAMIC_GZIP)).unwrap(),
+            dynamic_text().as_bytes()
+        );
+        let large: Vec<u8> = (0..200_000).map(|i| (i % 251) as u8).collect();
+        assert_eq!(decompress_gzip(&gzip_stored(&large)).unwrap(), large);
+
+        // 連結された複数のメンバー
+        let mut members: Vec<u8> = from_hex(FIXED_GZIP);
+        members.extend(gzip_stored(b"b = 2\n"));
+        assert_eq!(decompress_gzip(&members).unwrap(), b"a = 1\nb = 2\n");
+
+        // CRCが一致しないデータ
+        let mut corrupted: Vec<u8> = from_hex(DYNAMIC_GZIP);
+        let length: usize = corrupted.len();
+        corrupted[length - 8] ^= 1;
+        assert_eq!(
+            decompress_gzip(&corrupted).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        assert!(decompress_gzip(&from_hex(DYNAMIC_GZIP)[..100]).is_err());
+    }
+
+    /// 展開後の大きさが上限を超えるgzip（圧縮爆弾）がエラーとなるテスト
+    #[test]
+    fn test_decompress_gzip_limit() {
+        // 1MiBの0を圧縮した約1KiBのデータ（`gzip -9`の出力）
+        let bomb: Vec<u8> = from_hex(&format!(
+            "1f8b0800000000000203edc13101000000c2a0f54f6d085fa0{}3e031cea38a700001000",
+            "00".repeat(1016)
+        ));
+        assert_eq!(bomb.len(), 1051);
+        assert_eq!(decompress_gzip(&bomb).unwrap(), vec![0; 1 << 20]);
+
+        let error: io::Error = decompress_gzip_with_limit(&bomb, (1 << 20) - 1).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("1048575"));
+        assert_eq!(
+            decompress_gzip_with_limit(&bomb, 1 << 20).unwrap().len(),
+            1 << 20
+        );
+
+        // 無圧縮のブロック・連結された複数のメンバーも合計で上限を判定する
+        let stored: Vec<u8> = gzip_stored(&[1; 100]);
+        assert!(decompress_gzip_with_limit(&stored, 99).is_err());
+        let members: Vec<u8> = [stored.clone(), stored].concat();
+        assert!(decompress_gzip_with_limit(&members, 199).is_err());
+        assert_eq!(
+            decompress_gzip_with_limit(&members, 200).unwrap().len(),
+            200
+        );
+    }
+
+    /// ホワイトアウトで下位のレイヤーのファイルが削除されるテスト
+    #[test]
+    fn test_apply_layer_whiteouts() {
+        let mut files: MemoryFs = MemoryFs::new();
+        apply_layer(&mut files, &base_layer()).unwrap();
+        // .conf以外のファイルは内容を保持しない
+        assert_eq!(
+            files.read(Path::new("/usr/bin/tool")).unwrap().as_ref(),
+            b""
+        );
+        assert_eq!(
+            files.kind(Path::new("/etc/sysctl.d/20-remove.conf")),
+            Some(FileKind::File)
+        );
+
+        apply_layer(&mut files, &top_layer()).unwrap();
+        assert_eq!(files.kind(Path::new("/etc/sysctl.d/20-remove.conf")), None);
+        assert_eq!(
+            files.kind(Path::new("/etc/sysctl.d/.wh.20-remove.conf")),
+            None
+        );
+        assert_eq!(files.children("/usr/lib/sysctl.d"), vec!["70-new.conf"]);
+        assert_layered_config(files);
+    }
+
+    /// docker save の形式のtarballを読み込むテスト
+    #[test]
+    fn test_load_docker_save_image() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_image_docker");
+        fs::create_dir_all(&dir).unwrap();
+        let manifest: String = r#"[{"Config":"config.json","RepoTags":["test:latest"],"Layers":["base/layer.tar","top/layer.tar","shared/layer.tar"]}]"#.to_string();
+        let archive: Vec<u8> = build_tar(&[
+            ("base/layer.tar", b'0', &base_layer(), ""),
+            ("top/layer.tar", b'0', &top_layer(), ""),
+            // 同じレイヤーはシンボリックリンクで共有される
+            ("shared/layer.tar", b'2', b"", "../top/layer.tar"),
+            ("manifest.json", b'0', manifest.as_bytes(), ""),
+        ]);
+        let path: PathBuf = dir.join("image.tar");
+        fs::write(&path, archive).unwrap();
+
+        assert_layered_config(load_image(&path).unwrap());
+
+        // マニフェストの無いtarball
+        fs::write(&path, build_tar(&[("other.txt", b'0', b"", "")])).unwrap();
+        assert_eq!(
+            load_image(&path).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// OCIイメージレイアウトのディレクトリ（gzip圧縮のレイヤー・複数のプラットフォーム）を読み込むテスト
+    #[test]
+    fn test_load_oci_layout() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_image_oci");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("blobs/sha256")).unwrap();
+        let write_blob = |name: &str, data: &[u8]| {
+            fs::write(dir.join("blobs/sha256").join(name), data).unwrap();
+        };
+        write_blob("base", &gzip_stored(&base_layer()));
+        write_blob("top", &top_layer());
+        write_blob(
+            "manifest",
+            br#"{"schemaVersion":2,"layers":[{"digest":"sha256:base"},{"digest":"sha256:top"}]}"#,
+        );
+        write_blob(
+            "other",
+            br#"{"schemaVersion":2,"layers":[{"digest":"sha256:missing"}]}"#,
+        );
+        let architecture: &str = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            other => other,
+        };
+        // 実行中のアーキテクチャのマニフェストが選ばれる
+        let image_index: String = format!(
+            r#"{{"manifests":[{{"digest":"sha256:other","platform":{{"os":"linux","architecture":"none"}}}},{{"digest":"sha256:manifest","platform":{{"os":"linux","architecture":"{}"}}}}]}}"#,
+            architecture
+        );
+        write_blob("index", image_index.as_bytes());
+        fs::write(dir.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#).unwrap();
+        fs::write(
+            dir.join("index.json"),
+            r#"{"schemaVersion":2,"manifests":[{"digest":"sha256:index"}]}"#,
+        )
+        .unwrap();
+
+        assert_layered_config(load_image(&dir).unwrap());
+
+        // 存在しないレイヤー・不正なダイジェスト
+        fs::write(
+            dir.join("index.json"),
+            r#"{"manifests":[{"digest":"sha256:other"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            load_image(&dir).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+        fs::write(
+            dir.join("index.json"),
+            r#"{"manifests":[{"digest":"sha256:../../etc/passwd"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            load_image(&dir).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// --image で指定したイメージを検証するテスト
+    #[test]
+    fn test_check_image_command() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_image_cli");
+        fs::create_dir_all(&dir).unwrap();
+        let manifest: &str = r#"[{"Layers":["base.tar","top.tar"]}]"#;
+        let archive: Vec<u8> = build_tar(&[
+            ("base.tar", b'0', &base_layer(), ""),
+            ("top.tar", b'0', &gzip_stored(&top_layer()), ""),
+            ("manifest.json", b'0', manifest.as_bytes(), ""),
+        ]);
+        fs::write(dir.join("image.tar"), archive).unwrap();
+        fs::write(
+            dir.join("schema.txt"),
+            "vm.swappiness -> int\nnet.ipv4.ip_forward -> int\n",
+        )
+        .unwrap();
+
+        let run = |args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["--lang", "en", "--image"])
+                .arg(dir.join("image.tar"))
+                .arg("--schema")
+                .arg(dir.join("schema.txt"))
+                .args(args)
+                .output()
+                .expect("バイナリの実行に失敗しました")
+        };
+        let output = run(&["--quiet"]);
+        assert_eq!(output.status.code(), Some(0));
+        let output = run(&["dump", "--format", "env"]);
+        let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+        assert!(stdout.contains("VM_SWAPPINESS=10"), "{}", stdout);
+        assert!(!stdout.contains("KERNEL_PANIC"), "{}", stdout);
+
+        // 読み込めないイメージは 4 で終了
+        fs::write(dir.join("image.tar"), "broken").unwrap();
+        let output = run(&["--quiet"]);
+        assert_eq!(output.status.code(), Some(4));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("failed to load the image"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
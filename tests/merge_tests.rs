@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::directory_parser::DirectoryParser;
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use linux_conf_parser::utils::format::render_sysctl_conf;
+    use rustc_hash::FxHashMap;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::{Command, Output};
+
+    /// 2つのディレクトリの設定を統合し、正規化したsysctl.confを出力するテスト
+    #[test]
+    fn test_render_merged_sysctl_conf() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_merge");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("etc")).unwrap();
+        fs::create_dir_all(dir.join("lib")).unwrap();
+        fs::write(
+            dir.join("etc/10-base.conf"),
+            "-net.ipv4.ip_forward = 0\nvm.swappiness=10\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("lib/20-override.conf"),
+            "# 後のファイルが優先される\nnet.ipv4.ip_forward   =   1\n- kernel.unknown = x\n",
+        )
+        .unwrap();
+
+        let etc: String = dir.join("etc").to_string_lossy().to_string();
+        let lib: String = dir.join("lib").to_string_lossy().to_string();
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            ..ParseOptions::default()
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        let report: ParseReport = DirectoryParser
+            .parse_all_conf_files_with_options(
+                &[&etc, &lib],
+                &FxHashMap::default(),
+                &mut result_map,
+                &options,
+            )
+            .unwrap();
+
+        // `-` はキーに含まれない
+        assert_eq!(result_map.get("kernel.unknown"), Some(&"x".to_string()));
+
+        // 有効な値を設定したエントリの `-` のみが保持される
+        assert_eq!(
+            render_sysctl_conf(&result_map, &report.sources, false),
+            "-kernel.unknown = x\nnet.ipv4.ip_forward = 1\nvm.swappiness = 10\n"
+        );
+
+        assert_eq!(
+            render_sysctl_conf(&result_map, &report.sources, true),
+            format!(
+                "# {lib}/20-override.conf:3\n-kernel.unknown = x\n\
+                 # {lib}/20-override.conf:2\nnet.ipv4.ip_forward = 1\n\
+                 # {etc}/10-base.conf:2\nvm.swappiness = 10\n"
+            )
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// パースや検証に失敗した場合は既存の出力先ファイルを書き換えないテスト
+    #[test]
+    fn test_merge_keeps_output_on_failure() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_merge_failure");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("conf")).unwrap();
+        fs::write(dir.join("conf/10-a.conf"), "vm.swappiness = high\n").unwrap();
+        fs::write(dir.join("schema.txt"), "vm.swappiness -> int\n").unwrap();
+        let output_path: PathBuf = dir.join("merged.conf");
+        fs::write(&output_path, "keep me\n").unwrap();
+
+        let run = |schema: &str| -> Output {
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["--lang", "en", "--schema"])
+                .arg(dir.join(schema))
+                .arg("--dir")
+                .arg(dir.join("conf"))
+                .arg("merge")
+                .arg(&output_path)
+                .output()
+                .expect("バイナリの実行に失敗しました")
+        };
+
+        // スキーマを読み込めない
+        let output: Output = run("nonexist.txt");
+        assert_eq!(output.status.code(), Some(4));
+        assert!(!String::from_utf8_lossy(&output.stdout).contains("Wrote"));
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "keep me\n");
+
+        // 検証に失敗
+        let output: Output = run("schema.txt");
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), "keep me\n");
+
+        // 成功した場合のみ置き換える
+        fs::write(dir.join("conf/10-a.conf"), "vm.swappiness = 10\n").unwrap();
+        let output: Output = run("schema.txt");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(
+            fs::read_to_string(&output_path).unwrap(),
+            "vm.swappiness = 10\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
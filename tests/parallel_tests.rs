@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::directory_parser::DirectoryParser;
+    use linux_conf_parser::core::parallel::parallel_map;
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use rustc_hash::FxHashMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// 並列処理の結果が元の順に並ぶテスト
+    #[test]
+    fn test_parallel_map_order() {
+        let items: Vec<u64> = (0..100).collect();
+        let results: Vec<u64> = parallel_map(&items, 8, |item| {
+            // 処理時間に偏りを持たせる
+            std::thread::sleep(std::time::Duration::from_micros((100 - item) * 10));
+            item * 2
+        });
+        assert_eq!(
+            results,
+            items.iter().map(|item| item * 2).collect::<Vec<u64>>()
+        );
+    }
+
+    /// 処理中のパニックは元のメッセージのまま呼び出し元に伝わるテスト
+    #[test]
+    #[should_panic(expected = "item 3")]
+    fn test_parallel_map_panic() {
+        let items: Vec<u32> = (0..10).collect();
+        parallel_map(&items, 4, |item| {
+            if *item == 3 {
+                panic!("item {}", item);
+            }
+        });
+    }
+
+    /// 並列にパースしても、逐次処理と同じ優先順位・診断の結果となるテスト
+    #[test]
+    fn test_parallel_parse_is_deterministic() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_parallel");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        // 同じキーを複数のディレクトリ・ファイルで上書きする（ファイル名はディレクトリごとに異なる）
+        let mut directories: Vec<String> = Vec::new();
+        for d in 0..3 {
+            let sub: PathBuf = dir.join(format!("d{}", d));
+            for f in 0..40 {
+                let nested: PathBuf = sub.join(format!("n{}", f % 4));
+                fs::create_dir_all(&nested).unwrap();
+                fs::write(
+                    nested.join(format!("{}{:02}.conf", d, f)),
+                    format!(
+                        "shared.key = {}-{}\nfile.d{}.f{} = {}\nbroken line\n",
+                        d, f, d, f, f
+                    ),
+                )
+                .unwrap();
+            }
+            directories.push(sub.to_string_lossy().to_string());
+        }
+        let directories: Vec<&str> = directories.iter().map(String::as_str).collect();
+
+        let parse = |jobs: usize| -> (ParseReport, FxHashMap<String, String>) {
+            let options = ParseOptions {
+                verbose: false,
+                validate: false,
+                jobs,
+                ..ParseOptions::default()
+            };
+            let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+            let report: ParseReport = DirectoryParser
+                .parse_all_conf_files_with_options(
+                    &directories,
+                    &FxHashMap::default(),
+                    &mut result_map,
+                    &options,
+                )
+                .unwrap();
+            (report, result_map)
+        };
+
+        let (sequential, sequential_map) = parse(1);
+        assert_eq!(sequential.files, 120);
+        // 全てのディレクトリを通した名前順で最後のファイルが優先される
+        assert_eq!(
+            sequential_map.get("shared.key").map(String::as_str),
+            Some("2-39")
+        );
+        for jobs in [2, 8] {
+            let (parallel, parallel_map) = parse(jobs);
+            assert_eq!(parallel, sequential);
+            assert_eq!(parallel_map, sequential_map);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
     use linux_conf_parser::config::Config;
+    use linux_conf_parser::core::diagnostic::DiagnosticCode;
     use linux_conf_parser::core::directory_parser::DirectoryParser;
-    use linux_conf_parser::core::file_parser::parse_conf_to_map;
+    use linux_conf_parser::core::file_parser::{parse_conf_entries, parse_conf_to_map};
     use linux_conf_parser::core::schema::validate_against_schema;
-    use linux_conf_parser::core::schema::LoadSchema;
+    use linux_conf_parser::core::schema::{infer_type, LoadSchema};
     use linux_conf_parser::core::{ParseFiles, SchemaLoader};
+    use linux_conf_parser::utils::output::{build_schema_skeleton, output_schema_skeleton};
     use rustc_hash::FxHashMap;
     use std::fs::{self, File};
     use std::io::{self, Error, Write};
@@ -60,15 +62,21 @@ mod tests {
         }
     }
 
-    /// 4096文字を超える値が含まれている場合のエラーテスト
+    /// 4096文字を超える値が含まれている場合のエラーテスト（その行は無視される）
     #[test]
-    #[should_panic(expected = "値が4096文字を超えています")]
     fn test_value_too_long() {
         let long_value: String = "A".repeat(Config::MAX_VALUE_LENGTH + 1);
-        let content: String = format!("long.key = {}", long_value);
+        let content: String = format!("long.key = {}\nshort.key = 1", long_value);
         let file_path: PathBuf = setup_test_file("long_value.conf", &content);
 
-        let _ = parse_conf_to_map(&file_path);
+        let (entries, diagnostics) = parse_conf_entries(&file_path).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "short.key");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, DiagnosticCode::ValueTooLong);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].key.as_deref(), Some("long.key"));
+        assert_eq!(diagnostics[0].location.as_ref().map(|l| l.line), Some(1));
         cleanup_test_files();
     }
 
@@ -288,4 +296,161 @@ mod tests {
         let errors: String = result.unwrap_err();
         assert!(errors.contains("キー 'extra_key' はスキーマに存在しません"));
     }
+
+    /// 値からの型推論テスト
+    #[test]
+    fn test_infer_type() {
+        assert_eq!(infer_type("true"), Some("bool"));
+        assert_eq!(infer_type("false"), Some("bool"));
+        assert_eq!(infer_type("1"), Some("int"));
+        assert_eq!(infer_type("-42"), Some("int"));
+        assert_eq!(infer_type("10.1"), Some("float"));
+        assert_eq!(infer_type("example.com"), Some("string"));
+        assert_eq!(infer_type("4096 87380 6291456"), Some("string"));
+        assert_eq!(infer_type(""), None);
+    }
+
+    /// 型定義ファイルの雛形がソート・グループ化されるテスト
+    #[test]
+    fn test_build_schema_skeleton() {
+        let mut config: FxHashMap<String, String> = FxHashMap::default();
+        config.insert("vm.swappiness".to_string(), "10".to_string());
+        config.insert("net.ipv4.tcp_syncookies".to_string(), "1".to_string());
+        config.insert("debug".to_string(), "true".to_string());
+        config.insert("net.core.somaxconn".to_string(), "4096".to_string());
+        config.insert("kernel.domainname".to_string(), "example.com".to_string());
+        config.insert("empty".to_string(), "".to_string());
+
+        let skeleton: String =
+            build_schema_skeleton(&config, &FxHashMap::default(), &FxHashMap::default());
+        assert_eq!(
+            skeleton,
+            "# debug\n\
+             debug -> bool\n\
+             \n\
+             # empty\n\
+             empty ->\n\
+             \n\
+             # kernel\n\
+             kernel.domainname -> string\n\
+             \n\
+             # net\n\
+             net.core.somaxconn -> int\n\
+             net.ipv4.tcp_syncookies -> int\n\
+             \n\
+             # vm\n\
+             vm.swappiness -> int\n"
+        );
+    }
+
+    /// 型が空の既存定義は推論した型を上書きしないテスト
+    #[test]
+    fn test_build_schema_skeleton_blank_existing() {
+        let mut config: FxHashMap<String, String> = FxHashMap::default();
+        config.insert("vm.swappiness".to_string(), "10".to_string());
+        config.insert("kernel.domainname".to_string(), "example.com".to_string());
+
+        let mut existing: FxHashMap<String, String> = FxHashMap::default();
+        existing.insert("vm.swappiness".to_string(), "".to_string());
+        existing.insert("kernel.domainname".to_string(), "string".to_string());
+        existing.insert("kernel.panic".to_string(), "".to_string());
+
+        let skeleton: String = build_schema_skeleton(&config, &existing, &FxHashMap::default());
+        assert_eq!(
+            skeleton,
+            "# kernel\n\
+             kernel.domainname -> string\n\
+             kernel.panic ->\n\
+             \n\
+             # vm\n\
+             vm.swappiness -> int\n"
+        );
+    }
+
+    /// 既存の型定義ファイルとマージされるテスト
+    #[test]
+    fn test_output_schema_skeleton_merges_existing() {
+        let output_dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_skeleton_merge");
+        fs::create_dir_all(&output_dir).unwrap();
+        let output_path: PathBuf = output_dir.join("schema.txt");
+        fs::write(
+            &output_path,
+            "vm.swappiness -> float  # 0〜100\nkernel.panic -> string\n",
+        )
+        .unwrap();
+
+        let mut config: FxHashMap<String, String> = FxHashMap::default();
+        config.insert("vm.swappiness".to_string(), "10".to_string());
+        config.insert("fs.file-max".to_string(), "2097152".to_string());
+
+        output_schema_skeleton(&config, &output_path, false).unwrap();
+        let schema: FxHashMap<String, String> = LoadSchema.load_schema(&output_path).unwrap();
+
+        // 既存の定義が優先され、新しいキーには推論された型が付与される
+        assert_eq!(schema.get("vm.swappiness").unwrap(), "float");
+        assert_eq!(schema.get("kernel.panic").unwrap(), "string");
+        assert_eq!(schema.get("fs.file-max").unwrap(), "int");
+
+        // 補足説明も保持される
+        let notes: FxHashMap<String, String> = LoadSchema.load_schema_notes(&output_path).unwrap();
+        assert_eq!(notes.get("vm.swappiness").unwrap(), "0〜100");
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    /// 書き込みに失敗しても既存の型定義ファイルが残るテスト
+    #[test]
+    fn test_output_schema_skeleton_keeps_existing_on_failure() {
+        let output_dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_skeleton_failure");
+        let _ = fs::remove_dir_all(&output_dir);
+        fs::create_dir_all(&output_dir).unwrap();
+        let output_path: PathBuf = output_dir.join("schema.txt");
+        let existing: &str = "vm.swappiness -> int  # 0〜100\n";
+        fs::write(&output_path, existing).unwrap();
+
+        // 置き換えに使用する一時ファイルを作成できないようにする
+        let temporary: PathBuf = output_dir.join(format!(".schema.txt.{}.tmp", std::process::id()));
+        fs::create_dir(&temporary).unwrap();
+
+        let config: FxHashMap<String, String> =
+            FxHashMap::from_iter([("fs.file-max".to_string(), "2097152".to_string())]);
+        assert!(output_schema_skeleton(&config, &output_path, true).is_err());
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), existing);
+
+        // 書き込めるようになれば置き換えられる
+        fs::remove_dir(&temporary).unwrap();
+        output_schema_skeleton(&config, &output_path, true).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output_path).unwrap(),
+            "# fs\nfs.file-max -> int\n\n# vm\nvm.swappiness -> int  # 0〜100\n"
+        );
+
+        fs::remove_dir_all(&output_dir).unwrap();
+    }
+
+    /// --quiet では雛形を作成したことを表示しないテスト
+    #[test]
+    fn test_schema_init_quiet() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_skeleton_quiet");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf")).unwrap();
+        fs::write(dir.join("conf/10-a.conf"), "vm.swappiness = 10\n").unwrap();
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+            .arg("--quiet")
+            .arg("--dir")
+            .arg(dir.join("conf"))
+            .args(["schema", "init"])
+            .arg(dir.join("schema.txt"))
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "");
+        assert_eq!(
+            fs::read_to_string(dir.join("schema.txt")).unwrap(),
+            "# vm\nvm.swappiness -> int\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
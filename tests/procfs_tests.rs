@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::Location;
+    use linux_conf_parser::core::procfs::{
+        apply_values, check_drift, sysctl_path, ApplyResult, ApplyStatus, DriftKind, KeyDrift,
+    };
+    use linux_conf_parser::core::EntrySource;
+    use rustc_hash::FxHashMap;
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+
+    /// テスト用の /proc/sys 形式のディレクトリを作成する関数
+    fn setup_proc_sys(name: &str, values: &[(&str, &str)]) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_proc_{}", name));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        for (path, value) in values {
+            let file: PathBuf = dir.join("proc/sys").join(path);
+            fs::create_dir_all(file.parent().unwrap()).unwrap();
+            fs::write(file, value).unwrap();
+        }
+        dir
+    }
+
+    /// キーから /proc/sys 配下のパスへの変換テスト
+    #[test]
+    fn test_sysctl_path() {
+        let root: &Path = Path::new("/proc/sys");
+        assert_eq!(
+            sysctl_path(root, "net.ipv4.ip_forward"),
+            PathBuf::from("/proc/sys/net/ipv4/ip_forward")
+        );
+        // `/` 区切りのキーではインターフェース名の `.` を保持する
+        assert_eq!(
+            sysctl_path(root, "net/ipv4/conf/eth0.100/rp_filter"),
+            PathBuf::from("/proc/sys/net/ipv4/conf/eth0.100/rp_filter")
+        );
+        // 最初の区切りが `.` の場合は `.` と `/` を入れ替える（systemd-sysctl と同じ）
+        assert_eq!(
+            sysctl_path(root, "net.ipv4.conf.eth0/100.rp_filter"),
+            PathBuf::from("/proc/sys/net/ipv4/conf/eth0.100/rp_filter")
+        );
+        // 最初の区切りが `/` の場合は後の `.` も区切りとして扱わない
+        assert_eq!(
+            sysctl_path(root, "kernel/sched.child_runs_first"),
+            PathBuf::from("/proc/sys/kernel/sched.child_runs_first")
+        );
+        // ルートの外を指すことはない
+        assert_eq!(
+            sysctl_path(root, "net/../../etc/passwd"),
+            PathBuf::from("/proc/sys/net/etc/passwd")
+        );
+        assert!(sysctl_path(root, "../../etc/passwd").starts_with(root));
+    }
+
+    /// 実行中の値との違いを検出するテスト
+    #[test]
+    fn test_check_drift() {
+        let dir: PathBuf = setup_proc_sys(
+            "drift",
+            &[
+                ("vm/swappiness", "60\n"),
+                ("net/ipv4/ip_local_port_range", "32768\t60999\n"),
+                ("kernel/panic", "5\n"),
+            ],
+        );
+        let values: FxHashMap<String, String> = [
+            ("vm.swappiness", "10"),
+            ("net.ipv4.ip_local_port_range", "32768 60999"),
+            ("kernel.panic", "5"),
+            ("net.unknown", "1"),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+
+        let drifts: Vec<KeyDrift> = check_drift(
+            &values,
+            &BTreeMap::<String, EntrySource>::new(),
+            &dir.join("proc/sys"),
+        );
+        let summary: Vec<(&str, &DriftKind)> = drifts
+            .iter()
+            .map(|drift| (drift.key.as_str(), &drift.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("net.unknown", &DriftKind::Missing),
+                (
+                    "vm.swappiness",
+                    &DriftKind::Mismatch {
+                        actual: "60".to_string()
+                    }
+                ),
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// drift コマンドの終了コードのテスト
+    #[test]
+    fn test_drift_command() {
+        let dir: PathBuf = setup_proc_sys("command", &[("vm/swappiness", "60\n")]);
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        let run = |conf: &str| -> i32 {
+            fs::write(dir.join("sysctl.d/99-test.conf"), conf).unwrap();
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["drift", "--quiet", "--format", "json"])
+                .arg("--dir")
+                .arg(dir.join("sysctl.d"))
+                .arg("--proc-sys")
+                .arg(dir.join("proc/sys"))
+                .output()
+                .expect("バイナリの実行に失敗しました")
+                .status
+                .code()
+                .expect("終了コードがありません")
+        };
+
+        assert_eq!(run("vm.swappiness = 60\n"), 0);
+        assert_eq!(run("vm.swappiness = 10\n"), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// /proc/sys への書き込みと ignore-failure・ドライランのテスト
+    #[test]
+    fn test_apply_values() {
+        let dir: PathBuf = setup_proc_sys(
+            "apply",
+            &[("vm/swappiness", "60\n"), ("kernel/panic", "0\n")],
+        );
+        let proc_sys: PathBuf = dir.join("proc/sys");
+        let values: FxHashMap<String, String> = [
+            ("vm.swappiness", "10"),
+            ("kernel.panic", "5"),
+            ("net.missing", "1"),
+            ("net.optional", "1"),
+        ]
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+        let mut sources: BTreeMap<String, EntrySource> = BTreeMap::new();
+        sources.insert(
+            "net.optional".to_string(),
+            EntrySource {
+                location: Location {
+                    file: PathBuf::from("99-test.conf"),
+                    line: 1,
+                    column: 1,
+                    end_column: 1,
+                },
+                ignore_failure: true,
+            },
+        );
+
+        // ドライランではファイルを変更しない
+        let results: Vec<ApplyResult> = apply_values(&values, &sources, &proc_sys, true);
+        assert!(results
+            .iter()
+            .all(|result| result.status == ApplyStatus::DryRun));
+        assert_eq!(
+            fs::read_to_string(proc_sys.join("vm/swappiness")).unwrap(),
+            "60\n"
+        );
+
+        let results: Vec<ApplyResult> = apply_values(&values, &sources, &proc_sys, false);
+        let summary: Vec<(&str, &str)> = results
+            .iter()
+            .map(|result| (result.key.as_str(), result.status.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("kernel.panic", "applied"),
+                ("net.missing", "failed"),
+                ("net.optional", "ignored"),
+                ("vm.swappiness", "applied"),
+            ]
+        );
+        assert_eq!(
+            fs::read_to_string(proc_sys.join("vm/swappiness")).unwrap(),
+            "10\n"
+        );
+        // 存在しないキーのファイルは作成しない
+        assert!(!proc_sys.join("net/missing").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// apply コマンドの終了コードのテスト（検証に失敗した場合は書き込まない）
+    #[test]
+    fn test_apply_command() {
+        let dir: PathBuf = setup_proc_sys("apply_command", &[("vm/swappiness", "60\n")]);
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        fs::write(dir.join("schema.txt"), "vm.swappiness -> int\n").unwrap();
+        let run = |conf: &str| -> i32 {
+            fs::write(dir.join("sysctl.d/99-test.conf"), conf).unwrap();
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["apply", "--quiet"])
+                .arg("--schema")
+                .arg(dir.join("schema.txt"))
+                .arg("--dir")
+                .arg(dir.join("sysctl.d"))
+                .arg("--proc-sys")
+                .arg(dir.join("proc/sys"))
+                .output()
+                .expect("バイナリの実行に失敗しました")
+                .status
+                .code()
+                .expect("終了コードがありません")
+        };
+        let swappiness = || fs::read_to_string(dir.join("proc/sys/vm/swappiness")).unwrap();
+
+        assert_eq!(run("vm.swappiness = high\n"), 1);
+        assert_eq!(swappiness(), "60\n");
+        assert_eq!(run("vm.swappiness = 10\n"), 0);
+        assert_eq!(swappiness(), "10\n");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::config::Config;
+    use linux_conf_parser::core::directory_parser::SourceParser;
+    use linux_conf_parser::core::query::{matches_query, query_config, QueryMatch};
+    use linux_conf_parser::core::vfs::MemoryFs;
+    use linux_conf_parser::core::{ParseOptions, ParseReport};
+    use rustc_hash::FxHashMap;
+    use serde_json::Value;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Output};
+
+    /// キーとその配下のキーのみが一致するテスト
+    #[test]
+    fn test_matches_query() {
+        assert!(matches_query("net.ipv4.ip_forward", "net.ipv4"));
+        assert!(matches_query("net.ipv4.ip_forward", "net.ipv4.ip_forward"));
+        assert!(matches_query("net.ipv4.conf.all.rp_filter", "net/ipv4/"));
+        assert!(matches_query("net.ipv4.ip_forward", " net.ipv4. "));
+        // 名前の途中では区切らない
+        assert!(!matches_query("net.ipv46.x", "net.ipv4"));
+        assert!(!matches_query("net.ipv4", "net.ipv4.ip_forward"));
+        assert!(!matches_query("net.ipv4", ""));
+        assert!(!matches_query("net.ipv4", "."));
+        // 最初の区切りが`.`の場合、`/`はインターフェース名の一部
+        let vlan: &str = "net.ipv4.conf.eth0/100.rp_filter";
+        assert!(matches_query(vlan, "net.ipv4.conf.eth0/100.rp_filter"));
+        assert!(matches_query(vlan, "net/ipv4/conf/eth0.100/rp_filter"));
+        assert!(matches_query(vlan, "net.ipv4.conf.eth0/100"));
+        assert!(!matches_query(vlan, "net.ipv4.conf.eth0"));
+    }
+
+    /// `/`区切りで書かれたキーも`.`区切りのキーとして優先順位が適用されるテスト
+    #[test]
+    fn test_query_mixed_separators() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file(
+            "/etc/sysctl.d/10-a.conf",
+            "net.ipv4.ip_forward = 0\nnet/ipv4/conf/eth0.100/rp_filter = 0\n",
+        );
+        files.add_file(
+            "/etc/sysctl.d/20-b.conf",
+            "net/ipv4/ip_forward = 1\nnet.ipv4.conf.eth0/100.rp_filter = 2\n",
+        );
+
+        let (matches, report) = query_config(
+            "net",
+            &["/etc/sysctl.d"],
+            &SourceParser::new(files),
+            &FxHashMap::default(),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert!(report.diagnostics.is_empty());
+        let values: Vec<(&str, &str)> = matches
+            .iter()
+            .map(|m| (m.key.as_str(), m.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("net.ipv4.conf.eth0/100.rp_filter", "2"),
+                ("net.ipv4.ip_forward", "1"),
+            ]
+        );
+        assert_eq!(
+            matches[1].source.as_ref().unwrap().location.file,
+            Path::new("/etc/sysctl.d/20-b.conf")
+        );
+    }
+
+    /// 優先順位を適用した後の値と定義位置・型を返すテスト
+    #[test]
+    fn test_query_config() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file(
+            "/usr/lib/sysctl.d/50-default.conf",
+            "net.ipv4.ip_forward = 0\nnet.ipv4.tcp_syncookies = 1\nvm.swappiness = 60\n",
+        );
+        files.add_file(
+            "/etc/sysctl.d/99-local.conf",
+            "# local\n-net.ipv4.ip_forward = 1\n",
+        );
+        let schema: FxHashMap<String, String> =
+            FxHashMap::from_iter([("net.ipv4.ip_forward".to_string(), "int".to_string())]);
+
+        let (matches, report): (Vec<QueryMatch>, ParseReport) = query_config(
+            "net.ipv4",
+            &["/etc/sysctl.d", "/usr/lib/sysctl.d"],
+            &SourceParser::new(files),
+            &schema,
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.files, 2);
+        assert!(report.diagnostics.is_empty());
+
+        let keys: Vec<&str> = matches.iter().map(|m| m.key.as_str()).collect();
+        assert_eq!(keys, vec!["net.ipv4.ip_forward", "net.ipv4.tcp_syncookies"]);
+        assert_eq!(matches[0].value, "1");
+        assert_eq!(matches[0].expected_type.as_deref(), Some("int"));
+        let source = matches[0].source.as_ref().unwrap();
+        assert_eq!(
+            source.location.file,
+            Path::new("/etc/sysctl.d/99-local.conf")
+        );
+        assert_eq!(source.location.line, 2);
+        assert!(source.ignore_failure);
+        assert_eq!(matches[1].expected_type, None);
+    }
+
+    /// /etcのファイルが/usr/libのファイルより優先されるテスト（systemd-sysctlと同じ優先順位）
+    #[test]
+    fn test_query_etc_overrides_vendor() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file(
+            "/usr/lib/sysctl.d/50-vendor.conf",
+            "vm.swappiness = 60\nvm.overcommit_memory = 0\n",
+        );
+        files.add_file(
+            "/usr/lib/sysctl.d/60-other.conf",
+            "vm.overcommit_memory = 1\n",
+        );
+        files.add_file("/etc/sysctl.d/99-admin.conf", "vm.swappiness = 1\n");
+        // 同じ名前のファイルは/etcのものに置き換わる
+        files.add_file("/etc/sysctl.d/60-other.conf", "vm.dirty_ratio = 10\n");
+
+        let (matches, _) = query_config(
+            "vm",
+            &Config::DEFAULT_DIRECTORIES,
+            &SourceParser::new(files),
+            &FxHashMap::default(),
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let values: Vec<(&str, &str)> = matches
+            .iter()
+            .map(|m| (m.key.as_str(), m.value.as_str()))
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                ("vm.dirty_ratio", "10"),
+                ("vm.overcommit_memory", "0"),
+                ("vm.swappiness", "1"),
+            ]
+        );
+        assert_eq!(
+            matches[2].source.as_ref().unwrap().location.file,
+            Path::new("/etc/sysctl.d/99-admin.conf")
+        );
+    }
+
+    /// get コマンドの出力形式と終了コードのテスト
+    #[test]
+    fn test_get_command() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_query_cli");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("conf")).unwrap();
+        fs::write(
+            dir.join("conf/10-a.conf"),
+            "net.ipv4.ip_forward = 0\nvm.swappiness = 60\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("conf/20-b.conf"),
+            "\nnet.ipv4.ip_forward = 1\nnet.ipv4.tcp_syncookies = 1\n",
+        )
+        .unwrap();
+        fs::write(dir.join("schema.txt"), "net.ipv4.ip_forward -> int\n").unwrap();
+
+        let run = |args: &[&str]| -> Output {
+            Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+                .args(["--lang", "en", "--schema"])
+                .arg(dir.join("schema.txt"))
+                .arg("--dir")
+                .arg(dir.join("conf"))
+                .args(args)
+                .output()
+                .expect("バイナリの実行に失敗しました")
+        };
+
+        let output: Output = run(&["get", "net.ipv4"]);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "net.ipv4.ip_forward = 1\nnet.ipv4.tcp_syncookies = 1\n"
+        );
+
+        let output: Output = run(&[
+            "query",
+            "net.ipv4.ip_forward",
+            "--show-source",
+            "--show-type",
+        ]);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            format!(
+                "net.ipv4.ip_forward = 1  # {}:2, type: int\n",
+                dir.join("conf/20-b.conf").display()
+            )
+        );
+
+        let output: Output = run(&["get", "net/ipv4/tcp_syncookies", "--format", "json"]);
+        let value: Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(value[0]["key"], "net.ipv4.tcp_syncookies");
+        assert_eq!(value[0]["value"], "1");
+        assert_eq!(value[0]["type"], Value::Null);
+        assert_eq!(value[0]["line"], 3);
+
+        // 一致するキーが無い場合は 1 で終了
+        let output: Output = run(&["get", "kernel"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("no key matching kernel"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,285 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::{Diagnostic, DiagnosticCode, Location};
+    use linux_conf_parser::core::directory_parser::{
+        collect_conf_files, DirectoryParser, SourceParser,
+    };
+    use linux_conf_parser::core::file_parser::{parse_conf_entries, ConfEntry};
+    use linux_conf_parser::core::vfs::MemoryFs;
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use linux_conf_parser::utils::report::{
+        render_human, render_human_from, render_json_lines, render_sarif, use_color,
+    };
+    use rustc_hash::FxHashMap;
+    use serde_json::Value;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// テスト用のディレクトリと設定ファイルを作成する関数
+    fn setup_test_dir(name: &str, conf: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_report_{}", name));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("99-test.conf"), conf).unwrap();
+        dir
+    }
+
+    /// ディレクトリをパースして診断を返す関数
+    fn parse_dir(dir: &Path, schema: &[(&str, &str)]) -> ParseReport {
+        let schema: FxHashMap<String, String> = schema
+            .iter()
+            .map(|(key, value_type)| (key.to_string(), value_type.to_string()))
+            .collect();
+        let options = ParseOptions {
+            verbose: false,
+            ..ParseOptions::default()
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        DirectoryParser
+            .parse_all_conf_files_with_options(
+                &[dir.to_str().unwrap()],
+                &schema,
+                &mut result_map,
+                &options,
+            )
+            .unwrap()
+    }
+
+    /// エントリに行番号と値の列番号が記録されるテスト
+    #[test]
+    fn test_entry_locations() {
+        let dir: PathBuf = setup_test_dir(
+            "locations",
+            "# comment\n  vm.swappiness =   10\nkernel.domainname=example.com\nbroken line\n",
+        );
+        let (entries, warnings) = parse_conf_entries(&dir.join("99-test.conf")).unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                ConfEntry {
+                    key: "vm.swappiness".to_string(),
+                    value: "10".to_string(),
+                    line: 2,
+                    column: 21,
+                    ignore_failure: false,
+                },
+                ConfEntry {
+                    key: "kernel.domainname".to_string(),
+                    value: "example.com".to_string(),
+                    line: 3,
+                    column: 19,
+                    ignore_failure: false,
+                },
+            ]
+        );
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].code, DiagnosticCode::MissingSeparator);
+        assert_eq!(warnings[0].location.as_ref().unwrap().line, 4);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// JSON Lines 形式で1件1オブジェクトが出力されるテスト
+    #[test]
+    fn test_render_json_lines() {
+        let dir: PathBuf = setup_test_dir("json", "vm.swappiness = 10.1\n");
+        let report: ParseReport = parse_dir(&dir, &[("vm.swappiness", "int")]);
+
+        let output: String = render_json_lines(&report.diagnostics);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let diagnostic: Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(diagnostic["code"], "E001");
+        assert_eq!(diagnostic["severity"], "error");
+        assert_eq!(diagnostic["key"], "vm.swappiness");
+        assert_eq!(diagnostic["value"], "10.1");
+        assert_eq!(diagnostic["expected"], "int");
+        assert_eq!(
+            diagnostic["file"],
+            dir.join("99-test.conf").to_string_lossy().as_ref()
+        );
+        assert_eq!(diagnostic["line"], 1);
+        assert_eq!(diagnostic["column"], 17);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// SARIF 2.1.0 形式の出力テスト
+    #[test]
+    fn test_render_sarif() {
+        let diagnostics: Vec<Diagnostic> = vec![
+            Diagnostic::new(DiagnosticCode::UnknownKey, "unknown".to_string())
+                .with_key("extra_key")
+                .with_location(Some(Location {
+                    file: PathBuf::from("sysctl.d/99-test.conf"),
+                    line: 3,
+                    column: 13,
+                    end_column: 18,
+                })),
+            Diagnostic::new(DiagnosticCode::DuplicateKey, "duplicate".to_string()),
+        ];
+
+        let sarif: Value = serde_json::from_str(&render_sarif(&diagnostics)).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+
+        let run: &Value = &sarif["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "linux-conf-parser");
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            DiagnosticCode::ALL.len()
+        );
+
+        let results: &Vec<Value> = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0]["ruleId"], "E003");
+        assert_eq!(results[0]["level"], "error");
+        let location: &Value = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "sysctl.d/99-test.conf");
+        assert_eq!(location["region"]["startLine"], 3);
+        assert_eq!(location["region"]["startColumn"], 13);
+        assert_eq!(location["region"]["endColumn"], 18);
+        assert_eq!(results[1]["ruleId"], "W002");
+        assert_eq!(results[1]["level"], "warning");
+        assert!(results[1].get("locations").is_none());
+    }
+
+    /// SARIFのURIでパスの部分がパーセントエンコードされるテスト
+    #[test]
+    fn test_render_sarif_uri_encoding() {
+        let location = |file: &str| {
+            Diagnostic::new(DiagnosticCode::UnknownKey, "unknown".to_string()).with_location(Some(
+                Location {
+                    file: PathBuf::from(file),
+                    line: 1,
+                    column: 1,
+                    end_column: 2,
+                },
+            ))
+        };
+        let diagnostics: Vec<Diagnostic> = vec![
+            location("/etc/sysctl.d/my conf#1.conf"),
+            location("sysctl.d/50-日本語%.conf"),
+        ];
+
+        let sarif: Value = serde_json::from_str(&render_sarif(&diagnostics)).unwrap();
+        let uris: Vec<&str> = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                result["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]
+                    .as_str()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                "file:///etc/sysctl.d/my%20conf%231.conf",
+                "sysctl.d/50-%E6%97%A5%E6%9C%AC%E8%AA%9E%25.conf",
+            ]
+        );
+    }
+
+    /// 該当行と値の範囲の下線、スキーマのヘルプが表示されるテスト
+    #[test]
+    fn test_render_human_snippet() {
+        let dir: PathBuf = setup_test_dir("human", "# comment\nvm.swappiness = 10.1\n");
+        let mut report: ParseReport = parse_dir(&dir, &[("vm.swappiness", "int")]);
+        report.diagnostics[0].help = Some("0〜100 の整数で指定して下さい".to_string());
+
+        let output: String = render_human(&report.diagnostics, false);
+        let expected: String = format!(
+            "error[E001]: キー 'vm.swappiness' の値 '10.1' の型が一致しません。期待される型は 'int'\n \
+             --> {}:2:17\n  \
+             |\n\
+             2 | vm.swappiness = 10.1\n  \
+             |                 ^^^^ 期待される型は 'int'\n  \
+             |\n  \
+             = help: 0〜100 の整数で指定して下さい\n\n",
+            dir.join("99-test.conf").display()
+        );
+        assert_eq!(output, expected);
+
+        // 色付きの場合のみエスケープシーケンスを含む
+        assert!(!output.contains('\x1b'));
+        assert!(render_human(&report.diagnostics, true).contains("\x1b[1;31m"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// イメージなどの読み込み元の診断では、該当行を読み込み元から表示するテスト
+    #[test]
+    fn test_render_human_from_source() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file(
+            "/etc/sysctl.d/99-report-image.conf",
+            "vm.swappiness = 10.1\n",
+        );
+        let parser: SourceParser<MemoryFs> = SourceParser::new(files);
+        let schema: FxHashMap<String, String> =
+            FxHashMap::from_iter([("vm.swappiness".to_string(), "int".to_string())]);
+        let report: ParseReport = parser
+            .parse_all_conf_files_with_options(
+                &["/etc/sysctl.d"],
+                &schema,
+                &mut FxHashMap::default(),
+                &ParseOptions::default(),
+            )
+            .unwrap();
+
+        let output: String = render_human_from(&report.diagnostics, parser.file_source(), false);
+        assert!(output.contains(" --> /etc/sysctl.d/99-report-image.conf:1:17\n"));
+        assert!(output.contains("1 | vm.swappiness = 10.1\n"));
+        assert!(output.contains("|                 ^^^^ "));
+
+        // 実際のファイルシステムには存在しないため、該当行は表示されない
+        let output: String = render_human(&report.diagnostics, false);
+        assert!(!output.contains("1 | "));
+    }
+
+    /// 端末以外への出力では色を付けないテスト
+    #[test]
+    fn test_use_color_non_terminal() {
+        assert!(!use_color(false));
+    }
+
+    /// ファイルの探索順と診断の順序が実行ごとに変わらないテスト
+    #[test]
+    fn test_deterministic_order() {
+        let dir: PathBuf = setup_test_dir("order", "zz.key = 1\n");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("10-first.conf"), "b.key = 1\na.key = 1\n").unwrap();
+        fs::write(dir.join("sub/50-nested.conf"), "c.key = 1\n").unwrap();
+
+        let mut errors: Vec<String> = Vec::new();
+        let files: Vec<PathBuf> = collect_conf_files(&[dir.to_str().unwrap()], None, &mut errors);
+        assert_eq!(
+            files,
+            vec![
+                dir.join("10-first.conf"),
+                dir.join("99-test.conf"),
+                dir.join("sub/50-nested.conf"),
+            ]
+        );
+
+        // スキーマに存在しないキーはキーの順に報告される
+        let report: ParseReport = parse_dir(&dir, &[]);
+        let keys: Vec<&str> = report
+            .diagnostics
+            .iter()
+            .filter_map(|diagnostic| diagnostic.key.as_deref())
+            .collect();
+        assert_eq!(keys, vec!["a.key", "b.key", "c.key", "zz.key"]);
+        assert_eq!(parse_dir(&dir, &[]), report);
+
+        let sources: Vec<&String> = report.sources.keys().collect();
+        assert_eq!(sources, vec!["a.key", "b.key", "c.key", "zz.key"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
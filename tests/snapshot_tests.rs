@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diff::{
+        diff_configs, load_resolved_config, ChangeKind, KeyChange, ResolvedConfig,
+    };
+    use linux_conf_parser::core::procfs::{ApplyResult, ApplyStatus};
+    use linux_conf_parser::core::snapshot::{
+        capture_snapshot, format_timestamp, restore_snapshot, Snapshot, SNAPSHOT_FORMAT,
+        SNAPSHOT_VERSION,
+    };
+    use linux_conf_parser::i18n::{tr, Msg};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// UNIX時間から RFC 3339 形式の日時への変換テスト
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_792_412_096), "2026-10-19T12:14:56Z");
+    }
+
+    /// スナップショットの保存・読み込み・比較・書き戻しのテスト
+    #[test]
+    fn test_snapshot_and_restore() {
+        let dir: PathBuf = std::env::temp_dir().join("linux_conf_parser_snapshot");
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        let proc_sys: PathBuf = dir.join("proc/sys");
+        fs::create_dir_all(proc_sys.join("vm")).unwrap();
+        fs::create_dir_all(proc_sys.join("net/ipv4")).unwrap();
+        fs::write(proc_sys.join("vm/swappiness"), "60\n").unwrap();
+        fs::write(
+            proc_sys.join("net/ipv4/ip_local_port_range"),
+            "32768\t60999\n",
+        )
+        .unwrap();
+
+        let keys: Vec<String> = vec![
+            "vm.swappiness".to_string(),
+            "net.ipv4.ip_local_port_range".to_string(),
+            "net.unknown".to_string(),
+        ];
+        let snapshot: Snapshot = capture_snapshot(&keys, &proc_sys);
+        assert_eq!(snapshot.missing, vec!["net.unknown"]);
+        assert_eq!(
+            snapshot.values.get("net.ipv4.ip_local_port_range"),
+            Some(&"32768 60999".to_string())
+        );
+
+        // JSON に保存して読み込み直しても同じ内容になる
+        let path: PathBuf = dir.join("before.json");
+        fs::write(&path, snapshot.to_json()).unwrap();
+        assert_eq!(Snapshot::load(&path).unwrap(), snapshot);
+
+        // 値を変更した後、diff でスナップショットと比較できる
+        fs::write(proc_sys.join("vm/swappiness"), "10\n").unwrap();
+        let after: PathBuf = dir.join("after.json");
+        fs::write(&after, capture_snapshot(&keys, &proc_sys).to_json()).unwrap();
+        let load =
+            |path: &PathBuf| -> ResolvedConfig { load_resolved_config(path, &[]).unwrap().0 };
+        let changes: Vec<KeyChange> = diff_configs(&load(&path), &load(&after));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].key, "vm.swappiness");
+        assert_eq!(changes[0].kind, ChangeKind::Changed);
+
+        // スナップショットの値を書き戻す
+        let results: Vec<ApplyResult> = restore_snapshot(&snapshot, &proc_sys, false);
+        assert!(results
+            .iter()
+            .all(|result| result.status == ApplyStatus::Applied));
+        assert_eq!(
+            fs::read_to_string(proc_sys.join("vm/swappiness")).unwrap(),
+            "60\n"
+        );
+
+        // スナップショット以外の JSON は読み込めない
+        fs::write(&path, r#"{"vm": {"swappiness": 60}}"#).unwrap();
+        let error = Snapshot::load(&path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            error.to_string(),
+            tr(Msg::SnapshotInvalid, &[&SNAPSHOT_FORMAT, &SNAPSHOT_VERSION])
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,162 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::DiagnosticCode;
+    use linux_conf_parser::core::file_parser::{parse_conf_str, ConfEntryRef};
+    use linux_conf_parser::core::syntax::{ConfDocument, EntryLine, LineEnding, LineKind};
+    use std::io;
+    use std::path::Path;
+
+    const CONTENT: &str = "# Kernel tuning\r\n\
+        vm.swappiness = 10\r\n\
+        \t-  kernel.panic\t=5  \n\
+        \n\
+        ; legacy comment\n\
+        broken line\n\
+        net.core.somaxconn=\n\
+        vm.swappiness   =    60 # not a comment\n\
+        \u{3000}fs.file-max = ファイル数\r";
+
+    /// 行の断片を組み合わせた入力（疑似乱数で決まる）を作成する関数
+    fn generate_input(seed: u64) -> String {
+        const FRAGMENTS: [&str; 14] = [
+            "a.b", " ", "\t", "=", "-", "#", ";", "\r", "値", "x=y", "  ", "10", "\u{3000}", "",
+        ];
+        const ENDINGS: [&str; 3] = ["\n", "\r\n", ""];
+        let mut state: u64 = seed;
+        let mut next = |bound: usize| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as usize % bound
+        };
+        let mut input: String = String::new();
+        for _ in 0..next(8) {
+            for _ in 0..next(6) {
+                input.push_str(FRAGMENTS[next(FRAGMENTS.len())]);
+            }
+            input.push_str(ENDINGS[next(2)]);
+        }
+        if next(2) == 0 {
+            input.push_str(ENDINGS[next(ENDINGS.len())]);
+        }
+        input
+    }
+
+    /// 出力がパース前と完全に一致し、エントリが`parse_conf_str`と一致するテスト
+    #[test]
+    fn test_round_trip() {
+        let document: ConfDocument = ConfDocument::parse(CONTENT);
+        assert_eq!(document.to_string(), CONTENT);
+        assert_eq!(document.lines.len(), 9);
+        assert_eq!(document.lines[0].ending, LineEnding::CrLf);
+        assert_eq!(document.lines[8].ending, LineEnding::None);
+        assert!(matches!(document.lines[3].kind, LineKind::Blank(_)));
+        assert!(matches!(document.lines[4].kind, LineKind::Comment(_)));
+        assert!(matches!(document.lines[5].kind, LineKind::Invalid(_)));
+        assert_eq!(
+            document.lines[2].kind,
+            LineKind::Entry(EntryLine {
+                indent: "\t".to_string(),
+                ignore_prefix: Some("-  ".to_string()),
+                key: "kernel.panic".to_string(),
+                before_separator: "\t".to_string(),
+                after_separator: String::new(),
+                value: "5".to_string(),
+                trailing: "  ".to_string(),
+            })
+        );
+        assert_eq!(document.get("vm.swappiness"), Some("60 # not a comment"));
+        assert_eq!(document.get("net.core.somaxconn"), Some(""));
+        assert_eq!(document.get("missing"), None);
+
+        for input in std::iter::once(CONTENT.to_string()).chain((0..500).map(generate_input)) {
+            let document: ConfDocument = ConfDocument::parse(&input);
+            assert_eq!(document.to_string(), input);
+            let (entries, _) = parse_conf_str(&input, Path::new("test.conf"));
+            assert_eq!(
+                document.entries().collect::<Vec<ConfEntryRef>>(),
+                entries,
+                "{:?}",
+                input
+            );
+        }
+        assert_eq!(ConfDocument::parse("").to_string(), "");
+    }
+
+    /// 値の置き換え・追加・削除で、変更した行以外の書式が残るテスト
+    #[test]
+    fn test_edit_preserves_formatting() {
+        let mut document: ConfDocument = ConfDocument::parse(CONTENT);
+
+        // 重複したキーは有効な値（最後の行）のみを置き換える
+        document.set("vm.swappiness", "30").unwrap();
+        document.set("kernel.panic", "10").unwrap();
+        let expected: String = CONTENT
+            .replace("60 # not a comment", "30")
+            .replace("=5  ", "=10  ");
+        assert_eq!(document.to_string(), expected);
+
+        // 新しいキーは末尾に追加し、改行と`=`の前後の空白は既存の行に合わせる
+        document.set("net.ipv4.ip_forward", "1").unwrap();
+        assert_eq!(
+            document.to_string(),
+            format!("{}\r\nnet.ipv4.ip_forward = 1\r\n", expected)
+        );
+
+        // 重複したキーは全て削除する
+        assert_eq!(document.remove("vm.swappiness"), 2);
+        assert_eq!(document.remove("vm.swappiness"), 0);
+        assert_eq!(document.get("vm.swappiness"), None);
+        let edited: String = document.to_string();
+        assert!(edited.starts_with("# Kernel tuning\r\n\t-  kernel.panic\t=10  \n\n"));
+
+        // 編集後もパース結果が一致する
+        let (entries, warnings) = parse_conf_str(&edited, Path::new("test.conf"));
+        assert_eq!(document.entries().collect::<Vec<ConfEntryRef>>(), entries);
+        assert!(warnings
+            .iter()
+            .all(|w| w.code != DiagnosticCode::DuplicateKey));
+
+        let mut empty: ConfDocument = ConfDocument::default();
+        empty.set("kernel.panic", "5").unwrap();
+        assert_eq!(empty.to_string(), "kernel.panic = 5\n");
+    }
+
+    /// `/`区切りで書かれたキーも`.`区切りのキーと同じキーとして編集・パースされるテスト
+    #[test]
+    fn test_slash_separated_keys() {
+        let content: &str = "net/ipv4/ip_forward = 0\nnet/ipv4/conf/eth0.100/rp_filter = 1\n";
+        let mut document: ConfDocument = ConfDocument::parse(content);
+        assert_eq!(document.get("net.ipv4.ip_forward"), Some("0"));
+
+        // 既存の行の書き方はそのまま残す
+        document.set("net.ipv4.ip_forward", "1").unwrap();
+        assert_eq!(
+            document.to_string(),
+            content.replace("ip_forward = 0", "ip_forward = 1")
+        );
+        assert_eq!(document.remove("net.ipv4.conf.eth0/100.rp_filter"), 1);
+
+        let (entries, _) = parse_conf_str(content, Path::new("test.conf"));
+        let keys: Vec<&str> = entries.iter().map(|entry| entry.key.as_ref()).collect();
+        assert_eq!(
+            keys,
+            vec!["net.ipv4.ip_forward", "net.ipv4.conf.eth0/100.rp_filter"]
+        );
+    }
+
+    /// パースし直すと同じ内容にならないキー・値がエラーとなるテスト
+    #[test]
+    fn test_invalid_edit() {
+        let mut document: ConfDocument = ConfDocument::parse("a = 1\n");
+        for key in ["", "a b", "a=b", "#a", ";a", "-a"] {
+            let error: io::Error = document.set(key, "1").unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput, "{:?}", key);
+        }
+        for value in ["1\n2", "1\r", " 1", "1\t", &"x".repeat(4097)] {
+            let error: io::Error = document.set("a", value).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert_eq!(document.to_string(), "a = 1\n");
+    }
+}
@@ -0,0 +1,211 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::config::Config;
+    use linux_conf_parser::core::directory_parser::{collect_conf_files, DirectoryParser};
+    use linux_conf_parser::core::sysroot::resolve_in_root;
+    use linux_conf_parser::core::{ParseFiles, ParseOptions};
+    use rustc_hash::FxHashMap;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::path::{Path, PathBuf};
+    use std::process::{Command, Output};
+
+    /// テスト用のルートディレクトリを作成する関数（テストごとに別ディレクトリ）
+    fn setup_root(name: &str) -> PathBuf {
+        let root: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_root_{}", name));
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn write_file(root: &Path, path: &str, content: &str) {
+        let file_path: PathBuf = root.join(path);
+        fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+        fs::write(file_path, content).unwrap();
+    }
+
+    /// 絶対パスのシンボリックリンクがルート配下で解決されるテスト
+    #[test]
+    fn test_resolve_absolute_symlink() {
+        let root: PathBuf = setup_root("absolute_symlink");
+        write_file(
+            &root,
+            "usr/lib/sysctl.d/50-default.conf",
+            "vm.swappiness = 10",
+        );
+        fs::create_dir_all(root.join("etc/sysctl.d")).unwrap();
+        symlink(
+            "/usr/lib/sysctl.d/50-default.conf",
+            root.join("etc/sysctl.d/50-default.conf"),
+        )
+        .unwrap();
+
+        let resolved: PathBuf =
+            resolve_in_root(&root, Path::new("/etc/sysctl.d/50-default.conf")).unwrap();
+        assert_eq!(resolved, root.join("usr/lib/sysctl.d/50-default.conf"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// `..` やリンク先がルートの外に出ないテスト
+    #[test]
+    fn test_resolve_does_not_escape_root() {
+        let root: PathBuf = setup_root("escape");
+        fs::create_dir_all(root.join("etc")).unwrap();
+        symlink("../../../../etc", root.join("etc/escape")).unwrap();
+
+        let resolved: PathBuf = resolve_in_root(&root, Path::new("/../../etc/escape")).unwrap();
+        assert_eq!(resolved, root.join("etc"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 循環するシンボリックリンクはエラーになるテスト
+    #[test]
+    fn test_resolve_symlink_loop() {
+        let root: PathBuf = setup_root("loop");
+        symlink("/b", root.join("a")).unwrap();
+        symlink("/a", root.join("b")).unwrap();
+
+        assert!(resolve_in_root(&root, Path::new("/a")).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// 標準の探索パスがルート配下で探索されるテスト
+    #[test]
+    fn test_parse_standard_directories_in_root() {
+        let root: PathBuf = setup_root("standard");
+        write_file(
+            &root,
+            "usr/lib/sysctl.d/50-default.conf",
+            "vm.swappiness = 10",
+        );
+        write_file(&root, "etc/sysctl.d/99-local.conf", "fs.file-max = 2097152");
+        // /dev/null へのリンク（systemdのマスク）は読み込まない
+        symlink("/dev/null", root.join("etc/sysctl.d/60-masked.conf")).unwrap();
+        // ルート外を指すリンクもルート配下で解決される
+        fs::create_dir_all(root.join("run/sysctl.d")).unwrap();
+        symlink(
+            "/usr/lib/sysctl.d/50-default.conf",
+            root.join("run/sysctl.d/50-default.conf"),
+        )
+        .unwrap();
+
+        let mut errors: Vec<String> = Vec::new();
+        let files: Vec<PathBuf> =
+            collect_conf_files(&Config::DEFAULT_DIRECTORIES, Some(&root), &mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().all(|file| file.starts_with(&root)));
+
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            root: Some(root.clone()),
+            cache: None,
+            jobs: 0,
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        DirectoryParser
+            .parse_all_conf_files_with_options(
+                &Config::DEFAULT_DIRECTORIES,
+                &FxHashMap::default(),
+                &mut result_map,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(result_map.get("vm.swappiness"), Some(&"10".to_string()));
+        assert_eq!(result_map.get("fs.file-max"), Some(&"2097152".to_string()));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    /// systemd-sysctlと同じ優先順位（/etc優先・同名ファイルの置き換え・/dev/nullによるマスク）のテスト
+    #[test]
+    fn test_systemd_precedence_in_root() {
+        let root: PathBuf = setup_root("precedence");
+        // 名前順で後の/etcのファイルがベンダーのファイルより優先される
+        write_file(
+            &root,
+            "usr/lib/sysctl.d/50-vendor.conf",
+            "vm.swappiness = 60\n",
+        );
+        write_file(&root, "etc/sysctl.d/99-admin.conf", "vm.swappiness = 1\n");
+        // 同じ名前のファイルは/etcのものが使用され、/usr/libのものは読み込まれない
+        write_file(
+            &root,
+            "usr/lib/sysctl.d/99-admin.conf",
+            "kernel.panic = 5\n",
+        );
+        write_file(
+            &root,
+            "run/sysctl.d/60-runtime.conf",
+            "kernel.pid_max = 4096\n",
+        );
+        write_file(
+            &root,
+            "lib/sysctl.d/60-runtime.conf",
+            "kernel.pid_max = 1\n",
+        );
+        // /dev/nullへのリンクは同じ名前のベンダーのファイルを無効にする
+        write_file(
+            &root,
+            "usr/lib/sysctl.d/70-masked.conf",
+            "net.ipv4.ip_forward = 1\n",
+        );
+        symlink("/dev/null", root.join("etc/sysctl.d/70-masked.conf")).unwrap();
+
+        let mut errors: Vec<String> = Vec::new();
+        let files: Vec<PathBuf> =
+            collect_conf_files(&Config::DEFAULT_DIRECTORIES, Some(&root), &mut errors);
+        assert!(errors.is_empty());
+        assert_eq!(
+            files,
+            vec![
+                root.join("usr/lib/sysctl.d/50-vendor.conf"),
+                root.join("run/sysctl.d/60-runtime.conf"),
+                root.join("etc/sysctl.d/99-admin.conf"),
+            ]
+        );
+
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            root: Some(root.clone()),
+            cache: None,
+            jobs: 0,
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        DirectoryParser
+            .parse_all_conf_files_with_options(
+                &Config::DEFAULT_DIRECTORIES,
+                &FxHashMap::default(),
+                &mut result_map,
+                &options,
+            )
+            .unwrap();
+        assert_eq!(result_map.get("vm.swappiness"), Some(&"1".to_string()));
+        assert_eq!(result_map.get("kernel.pid_max"), Some(&"4096".to_string()));
+        assert_eq!(result_map.get("kernel.panic"), None);
+        assert_eq!(result_map.get("net.ipv4.ip_forward"), None);
+
+        // 存在しない標準のディレクトリ（/usr/local/lib/sysctl.d）は通知しない
+        let output: Output = Command::new(env!("CARGO_BIN_EXE_linux-conf-parser"))
+            .args(["--lang", "en", "--root"])
+            .arg(&root)
+            .args(["get", "vm.swappiness"])
+            .output()
+            .expect("バイナリの実行に失敗しました");
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "vm.swappiness = 1\n"
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,244 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::directory_parser::{collect_source_files, SourceParser};
+    use linux_conf_parser::core::tar::{read_tar, TarEntryKind};
+    use linux_conf_parser::core::vfs::{FileKind, FileSource, MemoryFs, TarFs};
+    use linux_conf_parser::core::{ParseFiles, ParseOptions, ParseReport};
+    use rustc_hash::FxHashMap;
+    use std::borrow::Cow;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    /// tarアーカイブのエントリ（パス・種類・内容・リンク先）
+    type Entry<'a> = (&'a str, u8, &'a [u8], &'a str);
+
+    /// ustar形式のヘッダを作成する関数
+    fn tar_header(path: &str, entry_type: u8, size: usize, link: &str) -> Vec<u8> {
+        let mut header: Vec<u8> = vec![0; 512];
+        let mut put = |offset: usize, value: &[u8]| {
+            header[offset..offset + value.len()].copy_from_slice(value);
+        };
+        put(0, &path.as_bytes()[..path.len().min(100)]);
+        put(100, b"0000644\0");
+        put(108, b"0000000\0");
+        put(116, b"0000000\0");
+        put(124, format!("{:011o}\0", size).as_bytes());
+        put(136, b"00000000000\0");
+        put(148, b"        ");
+        put(156, &[entry_type]);
+        put(157, link.as_bytes());
+        put(257, b"ustar\0");
+        put(263, b"00");
+        let checksum: u32 = header.iter().map(|byte| u32::from(*byte)).sum();
+        header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+        header
+    }
+
+    /// 内容をブロックサイズに合わせて追加する関数
+    fn push_data(archive: &mut Vec<u8>, data: &[u8]) {
+        archive.extend_from_slice(data);
+        archive.resize(archive.len().div_ceil(512) * 512, 0);
+    }
+
+    /// tarアーカイブを作成する関数（100バイト以上のパスはGNU形式の長いパス名とする）
+    fn build_tar(entries: &[Entry]) -> Vec<u8> {
+        let mut archive: Vec<u8> = Vec::new();
+        for (path, entry_type, data, link) in entries {
+            if path.len() >= 100 {
+                archive.extend(tar_header("././@LongLink", b'L', path.len() + 1, ""));
+                push_data(&mut archive, format!("{}\0", path).as_bytes());
+            }
+            archive.extend(tar_header(path, *entry_type, data.len(), link));
+            push_data(&mut archive, data);
+        }
+        archive.extend(vec![0; 1024]);
+        archive
+    }
+
+    /// 読み込み元のディレクトリをパースする関数
+    fn parse_source(
+        source: impl FileSource,
+        directories: &[&str],
+    ) -> (ParseReport, FxHashMap<String, String>) {
+        let options = ParseOptions {
+            verbose: false,
+            validate: false,
+            ..ParseOptions::default()
+        };
+        let mut result_map: FxHashMap<String, String> = FxHashMap::default();
+        let report: ParseReport = SourceParser::new(source)
+            .parse_all_conf_files_with_options(
+                directories,
+                &FxHashMap::default(),
+                &mut result_map,
+                &options,
+            )
+            .unwrap();
+        (report, result_map)
+    }
+
+    /// メモリ上のファイルを実際のファイルシステムと同じ規則で探索・パースするテスト
+    #[test]
+    fn test_memory_fs_parse() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file("/etc/sysctl.d/60-local.conf", "vm.swappiness = 10\n");
+        files.add_file("/etc/sysctl.d/README.txt", "vm.swappiness = 99\n");
+        files.add_file(
+            "/usr/lib/sysctl.d/50-default.conf",
+            "vm.swappiness = 60\nkernel.panic = 5\n",
+        );
+        // /lib/sysctl.dは/usr/lib/sysctl.dと同じディレクトリ
+        files.add_symlink("/lib", "usr/lib");
+        // ループするシンボリックリンクは一度だけ探索する
+        files.add_symlink("/etc/sysctl.d/self", "/etc/sysctl.d");
+
+        let directories: [&str; 4] = [
+            "/etc/sysctl.d",
+            "/run/sysctl.d",
+            "/usr/lib/sysctl.d",
+            "/lib/sysctl.d",
+        ];
+        let mut errors: Vec<String> = Vec::new();
+        assert_eq!(
+            collect_source_files(&files, &directories, &mut errors, 1),
+            vec![
+                PathBuf::from("/usr/lib/sysctl.d/50-default.conf"),
+                PathBuf::from("/etc/sysctl.d/60-local.conf"),
+            ]
+        );
+        assert!(errors.is_empty());
+
+        let (report, result_map) = parse_source(files, &directories);
+        assert_eq!(report.files, 2);
+        assert_eq!(
+            result_map.get("vm.swappiness").map(String::as_str),
+            Some("10")
+        );
+        assert_eq!(
+            report.sources["kernel.panic"].location.file,
+            Path::new("/usr/lib/sysctl.d/50-default.conf")
+        );
+    }
+
+    /// メモリ上のファイルの追加・置き換え・削除とパスの解決のテスト
+    #[test]
+    fn test_memory_fs_operations() {
+        let mut files: MemoryFs = MemoryFs::new();
+        files.add_file("etc/sysctl.d/a.conf", "a = 1\n");
+        files.add_file("/etc/sysctl.d/b.conf", "b = 2\n");
+        files.add_symlink("/etc/link", "../../../etc/sysctl.d");
+
+        assert_eq!(files.children("/etc/sysctl.d"), vec!["a.conf", "b.conf"]);
+        // ルートの外を指すリンクもルート内で解決される
+        let resolved: PathBuf = files.resolve(Path::new("/etc/link/a.conf")).unwrap();
+        assert_eq!(resolved, Path::new("/etc/sysctl.d/a.conf"));
+        assert_eq!(files.kind(&resolved), Some(FileKind::File));
+        assert!(matches!(
+            files.read(&resolved).unwrap(),
+            Cow::Borrowed(b"a = 1\n")
+        ));
+
+        // ディレクトリをファイルで置き換えると配下も削除される
+        files.add_file("/etc/sysctl.d", "");
+        assert_eq!(files.kind(Path::new("/etc/sysctl.d")), Some(FileKind::File));
+        assert_eq!(files.kind(Path::new("/etc/sysctl.d/a.conf")), None);
+
+        files.remove("/etc");
+        assert!(files.children("/").is_empty());
+        assert_eq!(
+            files.read(Path::new("/etc/sysctl.d")).unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        // 解決できないループはエラーとなる
+        files.add_symlink("/a", "/b");
+        files.add_symlink("/b", "/a");
+        assert!(files.resolve(Path::new("/a/x.conf")).is_err());
+    }
+
+    /// tarアーカイブ内のファイルを展開せずにパースするテスト
+    #[test]
+    fn test_tar_fs_parse() {
+        let long_name: String = format!("./usr/lib/sysctl.d/50-{}.conf", "x".repeat(120));
+        let archive: Vec<u8> = build_tar(&[
+            ("./etc/", b'5', b"", ""),
+            ("./etc/sysctl.d/", b'5', b"", ""),
+            (
+                "./etc/sysctl.d/99-custom.conf",
+                b'0',
+                b"vm.swappiness = 10\n",
+                "",
+            ),
+            (
+                "./etc/sysctl.d/98-copy.conf",
+                b'1',
+                b"",
+                "./etc/sysctl.d/99-custom.conf",
+            ),
+            (
+                &long_name,
+                b'0',
+                b"kernel.panic = 5\nvm.swappiness = 60\n",
+                "",
+            ),
+            ("./lib", b'2', b"", "usr/lib"),
+            ("./dev/null", b'3', b"", ""),
+        ]);
+
+        let entries = read_tar(&archive).unwrap();
+        assert_eq!(entries.len(), 7);
+        assert_eq!(entries[4].path, Path::new(&long_name));
+        assert_eq!(
+            entries[5].kind,
+            TarEntryKind::Symlink(PathBuf::from("usr/lib"))
+        );
+
+        let source: TarFs = TarFs::from_bytes(&archive).unwrap();
+        assert_eq!(
+            source
+                .read(Path::new("/etc/sysctl.d/98-copy.conf"))
+                .unwrap()
+                .as_ref(),
+            b"vm.swappiness = 10\n"
+        );
+        let (report, result_map) = parse_source(source, &["/lib/sysctl.d", "/etc/sysctl.d"]);
+        assert_eq!(report.files, 3);
+        assert_eq!(
+            result_map.get("vm.swappiness").map(String::as_str),
+            Some("10")
+        );
+        assert_eq!(
+            result_map.get("kernel.panic").map(String::as_str),
+            Some("5")
+        );
+    }
+
+    /// PAX形式の長いパス名と、壊れたtarアーカイブのテスト
+    #[test]
+    fn test_tar_pax_and_errors() {
+        let path: &str = "etc/sysctl.d/pax.conf";
+        let record_length: usize = " path=\n".len() + path.len() + 2;
+        let record: String = format!("{} path={}\n", record_length, path);
+        assert_eq!(record.len(), record_length);
+        let mut archive: Vec<u8> = tar_header("PaxHeader", b'x', record.len(), "");
+        push_data(&mut archive, record.as_bytes());
+        archive.extend(build_tar(&[("short.conf", b'0', b"a = 1\n", "")]));
+        let entries = read_tar(&archive).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, Path::new(path));
+        assert_eq!(entries[0].data, b"a = 1\n");
+
+        // チェックサムが一致しないヘッダ
+        let mut corrupted: Vec<u8> = archive.clone();
+        corrupted[10] ^= 1;
+        let error: io::Error = TarFs::from_bytes(&corrupted).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // 途中で終わっているアーカイブ
+        let truncated: &[u8] = &archive[..archive.len() - 1024 - 512 + 3];
+        assert_eq!(
+            read_tar(truncated).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
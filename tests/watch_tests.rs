@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use linux_conf_parser::core::diagnostic::DiagnosticCode;
+    use linux_conf_parser::core::diff::ChangeKind;
+    use linux_conf_parser::core::watch::{DirectoryWatcher, WatchDelta, WatchState};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    /// テスト用のディレクトリ（sysctl.d と schema.txt）を作成する関数
+    fn setup(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format!("linux_conf_parser_watch_{}", name));
+        if dir.exists() {
+            fs::remove_dir_all(&dir).unwrap();
+        }
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        fs::write(
+            dir.join("schema.txt"),
+            "vm.swappiness -> int\nkernel.panic -> int\n",
+        )
+        .unwrap();
+        fs::write(dir.join("sysctl.d/10-base.conf"), "vm.swappiness = 60\n").unwrap();
+        fs::write(dir.join("sysctl.d/20-extra.conf"), "kernel.panic = 5\n").unwrap();
+        dir
+    }
+
+    fn new_state(dir: &Path) -> WatchState {
+        let sysctl_d: String = dir.join("sysctl.d").to_string_lossy().to_string();
+        WatchState::new(&[sysctl_d.as_str()], None, &dir.join("schema.txt"))
+    }
+
+    /// 変更されたファイルのみを再パースし、値と診断の変化を返すテスト
+    #[test]
+    fn test_incremental_refresh() {
+        let dir: PathBuf = setup("refresh");
+        let mut state: WatchState = new_state(&dir);
+
+        // 最初は全てのファイルをパースし、全てのキーが追加となる
+        let delta: WatchDelta = state.refresh();
+        assert_eq!(delta.parsed.len(), 2);
+        assert_eq!(delta.changes.len(), 2);
+        assert!(delta.new_diagnostics.is_empty());
+
+        // 変更が無ければ何も返さない
+        assert!(state.refresh().is_empty());
+
+        // 型が一致しない値に変更すると、そのファイルのみを再パースして診断が追加される
+        fs::write(dir.join("sysctl.d/20-extra.conf"), "kernel.panic = never\n").unwrap();
+        let delta: WatchDelta = state.refresh();
+        assert_eq!(delta.parsed, vec![dir.join("sysctl.d/20-extra.conf")]);
+        assert_eq!(delta.changes.len(), 1);
+        assert_eq!(delta.changes[0].kind, ChangeKind::Changed);
+        assert_eq!(delta.new_diagnostics.len(), 1);
+        assert_eq!(delta.new_diagnostics[0].code, DiagnosticCode::TypeMismatch);
+
+        // ファイルを削除すると、キーの削除と診断の解消が返される
+        fs::remove_file(dir.join("sysctl.d/20-extra.conf")).unwrap();
+        let delta: WatchDelta = state.refresh();
+        assert_eq!(delta.removed, vec![dir.join("sysctl.d/20-extra.conf")]);
+        assert_eq!(delta.changes[0].kind, ChangeKind::Removed);
+        assert_eq!(delta.resolved_diagnostics.len(), 1);
+        assert!(state.diagnostics().is_empty());
+
+        // 型定義ファイルを変更すると全てのキーを再検証する
+        fs::write(dir.join("schema.txt"), "vm.swappiness -> bool\n").unwrap();
+        let delta: WatchDelta = state.refresh();
+        assert!(delta.schema_reloaded);
+        assert!(delta.parsed.is_empty());
+        assert_eq!(delta.new_diagnostics.len(), 1);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// ファイルの変更を inotify（またはポーリング）で検出するテスト
+    #[test]
+    fn test_directory_watcher() {
+        let dir: PathBuf = setup("watcher");
+        let mut state: WatchState = new_state(&dir);
+        state.refresh();
+
+        let mut watcher: DirectoryWatcher = DirectoryWatcher::new();
+        watcher.watch(&state.watched_directories());
+        fs::write(dir.join("sysctl.d/30-new.conf"), "vm.swappiness = 10\n").unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+
+        let delta: WatchDelta = state.refresh();
+        assert_eq!(delta.parsed, vec![dir.join("sysctl.d/30-new.conf")]);
+        assert_eq!(
+            state
+                .config()
+                .values
+                .get("vm.swappiness")
+                .map(String::as_str),
+            Some("10")
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// 削除して作り直したディレクトリの変更も検出するテスト
+    #[test]
+    fn test_directory_watcher_recreated() {
+        let dir: PathBuf = setup("watcher_recreated");
+        let mut state: WatchState = new_state(&dir);
+        state.refresh();
+
+        let mut watcher: DirectoryWatcher = DirectoryWatcher::new();
+        watcher.watch(&state.watched_directories());
+
+        // イベントを読む前に同じパスに作り直す
+        fs::remove_dir_all(dir.join("sysctl.d")).unwrap();
+        fs::create_dir_all(dir.join("sysctl.d")).unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+        state.refresh();
+
+        watcher.watch(&state.watched_directories());
+        fs::write(dir.join("sysctl.d/30-new.conf"), "vm.swappiness = 10\n").unwrap();
+        assert!(watcher.wait(Duration::from_secs(5)));
+        let delta: WatchDelta = state.refresh();
+        assert_eq!(delta.parsed, vec![dir.join("sysctl.d/30-new.conf")]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}